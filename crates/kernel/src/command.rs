@@ -0,0 +1,256 @@
+pub mod argument;
+pub mod builtin;
+pub mod dispatcher;
+pub mod reader;
+
+use crate::command::argument::{ArgumentValue, Coordinates, EntitySelector};
+use crate::command::dispatcher::CommandDispatcher;
+use crate::command::reader::StringReader;
+use crate::entity::player::{Player, PlayerUpdate};
+use crate::entity::Entity;
+use crate::util::text::TextComponent;
+use crate::WORLD;
+use hashbrown::HashMap;
+use parking_lot::{Mutex, RwLock};
+use std::fmt::{Display, Formatter};
+use std::sync::{Arc, LazyLock};
+use tracing::{info, warn};
+
+/// The commands of the server, run by the players and the console.
+pub static DISPATCHER: LazyLock<RwLock<CommandDispatcher>> = LazyLock::new(|| {
+    let mut dispatcher = CommandDispatcher::new();
+    builtin::register(&mut dispatcher);
+    RwLock::new(dispatcher)
+});
+
+/// The permission level of the console, above every player.
+pub const CONSOLE_PERMISSION_LEVEL: u8 = 4;
+
+/// Who runs a command.
+#[derive(Clone)]
+pub enum CommandSource {
+    Console,
+    Player(Arc<Mutex<Player>>),
+}
+
+impl CommandSource {
+    pub fn has_permission(&self, level: u8) -> bool {
+        match self {
+            CommandSource::Console => true,
+            CommandSource::Player(player) => player.lock().permission_level >= level,
+        }
+    }
+
+    pub fn get_player(&self) -> Option<&Arc<Mutex<Player>>> {
+        match self {
+            CommandSource::Console => None,
+            CommandSource::Player(player) => Some(player),
+        }
+    }
+
+    pub fn get_name(&self) -> String {
+        match self {
+            CommandSource::Console => "Server".to_string(),
+            CommandSource::Player(player) => player.lock().username.clone(),
+        }
+    }
+
+    /// Gets the dimension and the position relative coordinates are resolved from,
+    /// the world spawn point for the console.
+    pub fn get_position(&self) -> (usize, (f64, f64, f64)) {
+        match self {
+            CommandSource::Console => {
+                let spawn = WORLD.get_world_spawn_point();
+                (spawn.0, (spawn.1 as f64, spawn.2 as f64, spawn.3 as f64))
+            }
+            CommandSource::Player(player) => {
+                let player = player.lock();
+                (player.get_data().dimension, player.get_data().pos)
+            }
+        }
+    }
+
+    /// Sends the feedback of a command.
+    ///
+    /// Players get it through their connection's task, so it can be sent from any thread.
+    pub fn send_message(&self, message: TextComponent) {
+        match self {
+            CommandSource::Console => info!("{}", message.to_plain_text()),
+            CommandSource::Player(player) => {
+                let _ = player
+                    .lock()
+                    .connection
+                    .send_update(PlayerUpdate::SystemMessage(message));
+            }
+        }
+    }
+
+    pub fn send_error(&self, message: impl Into<String>) {
+        match self {
+            CommandSource::Console => warn!("{}", message.into()),
+            CommandSource::Player(_) => self.send_message(TextComponent {
+                color: Some("red".to_string()),
+                ..TextComponent::text(message)
+            }),
+        }
+    }
+}
+
+/// The error of a command, shown to its source in red.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandError {
+    pub message: String,
+    /// The input and the position the error was found at, for syntax errors.
+    pub context: Option<(String, usize)>,
+}
+
+impl CommandError {
+    pub fn new(message: impl Into<String>) -> CommandError {
+        CommandError {
+            message: message.into(),
+            context: None,
+        }
+    }
+
+    /// An error in the syntax of a command, at the cursor of the reader.
+    pub fn syntax(message: impl Into<String>, reader: &StringReader) -> CommandError {
+        CommandError {
+            message: message.into(),
+            context: Some((reader.get_input().to_string(), reader.get_cursor())),
+        }
+    }
+}
+
+impl Display for CommandError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)?;
+        if let Some((input, cursor)) = &self.context {
+            let cursor = (*cursor).min(input.len());
+            let start = input[..cursor]
+                .char_indices()
+                .rev()
+                .nth(9)
+                .map_or(0, |(i, _)| i);
+            if start > 0 {
+                write!(f, "\n...{}<--[HERE]", &input[start..cursor])?;
+            } else {
+                write!(f, "\n{}<--[HERE]", &input[..cursor])?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The result of a command, a number such as how many targets it affected.
+pub type CommandResult = Result<i32, CommandError>;
+
+/// A parsed command, handed to its executor.
+pub struct CommandContext<'a> {
+    pub source: &'a CommandSource,
+    pub input: &'a str,
+    pub arguments: HashMap<String, ArgumentValue>,
+}
+
+impl CommandContext<'_> {
+    fn get(&self, name: &str) -> Result<&ArgumentValue, CommandError> {
+        self.arguments
+            .get(name)
+            .ok_or_else(|| CommandError::new(format!("No such argument '{}'", name)))
+    }
+
+    pub fn has_argument(&self, name: &str) -> bool {
+        self.arguments.contains_key(name)
+    }
+
+    pub fn get_bool(&self, name: &str) -> Result<bool, CommandError> {
+        match self.get(name)? {
+            ArgumentValue::Bool(value) => Ok(*value),
+            _ => Err(CommandError::new(format!("'{}' is not a bool", name))),
+        }
+    }
+
+    pub fn get_integer(&self, name: &str) -> Result<i32, CommandError> {
+        match self.get(name)? {
+            ArgumentValue::Integer(value) | ArgumentValue::Time(value) => Ok(*value),
+            _ => Err(CommandError::new(format!("'{}' is not an integer", name))),
+        }
+    }
+
+    pub fn get_long(&self, name: &str) -> Result<i64, CommandError> {
+        match self.get(name)? {
+            ArgumentValue::Long(value) => Ok(*value),
+            _ => Err(CommandError::new(format!("'{}' is not a long", name))),
+        }
+    }
+
+    pub fn get_double(&self, name: &str) -> Result<f64, CommandError> {
+        match self.get(name)? {
+            ArgumentValue::Double(value) => Ok(*value),
+            ArgumentValue::Float(value) => Ok(*value as f64),
+            _ => Err(CommandError::new(format!("'{}' is not a number", name))),
+        }
+    }
+
+    pub fn get_string(&self, name: &str) -> Result<&str, CommandError> {
+        match self.get(name)? {
+            ArgumentValue::String(value) => Ok(value),
+            _ => Err(CommandError::new(format!("'{}' is not a string", name))),
+        }
+    }
+
+    pub fn get_game_mode(&self, name: &str) -> Result<u8, CommandError> {
+        match self.get(name)? {
+            ArgumentValue::GameMode(value) => Ok(*value),
+            _ => Err(CommandError::new(format!("'{}' is not a game mode", name))),
+        }
+    }
+
+    /// Gets the position of a coordinates argument, resolved from the source's position.
+    pub fn get_position(&self, name: &str) -> Result<(f64, f64, f64), CommandError> {
+        match self.get(name)? {
+            ArgumentValue::Coordinates(coordinates) => {
+                Ok(coordinates.resolve(self.source.get_position().1))
+            }
+            _ => Err(CommandError::new(format!("'{}' is not a position", name))),
+        }
+    }
+
+    pub fn get_coordinates(&self, name: &str) -> Result<&Coordinates, CommandError> {
+        match self.get(name)? {
+            ArgumentValue::Coordinates(coordinates) => Ok(coordinates),
+            _ => Err(CommandError::new(format!("'{}' is not a position", name))),
+        }
+    }
+
+    /// Gets the player selected by a single entity argument.
+    ///
+    /// # Errors
+    /// Returns an error if no player or more than one are selected.
+    pub fn get_player(&self, name: &str) -> Result<Arc<Mutex<Player>>, CommandError> {
+        let mut players = self.get_players(name)?;
+        if players.len() > 1 {
+            return Err(CommandError::new(
+                "Only one player is allowed, but the provided selector allows more than one",
+            ));
+        }
+        Ok(players.remove(0))
+    }
+
+    /// Gets the players selected by an entity or game profile argument.
+    ///
+    /// # Errors
+    /// Returns an error if no player is selected.
+    pub fn get_players(&self, name: &str) -> Result<Vec<Arc<Mutex<Player>>>, CommandError> {
+        let players = match self.get(name)? {
+            ArgumentValue::Entity(selector) => selector.resolve(self.source),
+            ArgumentValue::String(name) => {
+                EntitySelector::Player(name.clone()).resolve(self.source)
+            }
+            _ => return Err(CommandError::new(format!("'{}' is not an entity", name))),
+        };
+        if players.is_empty() {
+            return Err(CommandError::new("No player was found"));
+        }
+        Ok(players)
+    }
+}
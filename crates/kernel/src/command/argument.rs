@@ -0,0 +1,537 @@
+use crate::command::reader::StringReader;
+use crate::command::{CommandError, CommandSource};
+use crate::entity::player::Player;
+use crate::gameplay::{map_players, PLAYERS};
+use crate::util::io::WriteExt;
+use parking_lot::Mutex;
+use std::sync::Arc;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use uuid::Uuid;
+
+/// How a string argument is delimited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringKind {
+    /// A single word.
+    SingleWord,
+    /// A single word, or a phrase between quotes.
+    QuotablePhrase,
+    /// The rest of the input.
+    GreedyPhrase,
+}
+
+/// The type of an argument, parsed by the server and mapped to the protocol's parser,
+/// which the client uses to highlight and complete it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgumentType {
+    Bool,
+    Float {
+        min: Option<f32>,
+        max: Option<f32>,
+    },
+    Double {
+        min: Option<f64>,
+        max: Option<f64>,
+    },
+    Integer {
+        min: Option<i32>,
+        max: Option<i32>,
+    },
+    Long {
+        min: Option<i64>,
+        max: Option<i64>,
+    },
+    String(StringKind),
+    /// A player name, a uuid or a selector.
+    Entity {
+        single: bool,
+        players_only: bool,
+    },
+    /// A player name, read as a string.
+    GameProfile,
+    BlockPos,
+    ColumnPos,
+    Vec3,
+    Vec2,
+    /// The rest of the input, read as a string.
+    Message,
+    /// The name of a dimension, read as a string.
+    Dimension,
+    GameMode,
+    /// A duration in ticks, with an optional `d`, `s` or `t` unit.
+    Time {
+        min: i32,
+    },
+    Uuid,
+}
+
+/// The value of a parsed argument.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgumentValue {
+    Bool(bool),
+    Float(f32),
+    Double(f64),
+    Integer(i32),
+    Long(i64),
+    String(String),
+    Entity(EntitySelector),
+    Coordinates(Coordinates),
+    GameMode(u8),
+    Time(i32),
+    Uuid(Uuid),
+}
+
+/// A coordinate, absolute or relative to the source's position.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Coordinate {
+    pub relative: bool,
+    pub value: f64,
+}
+
+impl Coordinate {
+    pub fn resolve(&self, origin: f64) -> f64 {
+        if self.relative {
+            origin + self.value
+        } else {
+            self.value
+        }
+    }
+}
+
+/// The coordinates of a position argument.
+/// The y coordinate of a column position or a 2D vector is relative 0.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Coordinates {
+    pub x: Coordinate,
+    pub y: Coordinate,
+    pub z: Coordinate,
+}
+
+impl Coordinates {
+    pub fn resolve(&self, origin: (f64, f64, f64)) -> (f64, f64, f64) {
+        (
+            self.x.resolve(origin.0),
+            self.y.resolve(origin.1),
+            self.z.resolve(origin.2),
+        )
+    }
+}
+
+/// The players selected by an entity argument.
+///
+/// Only players can be selected, `@e` selects all the players.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EntitySelector {
+    Player(String),
+    Uuid(Uuid),
+    /// `@p`, the nearest player.
+    NearestPlayer,
+    /// `@r`, a random player.
+    RandomPlayer,
+    /// `@a` and `@e`, all the players.
+    AllPlayers,
+    /// `@s`, the player running the command.
+    Executor,
+}
+
+impl EntitySelector {
+    pub fn resolve(&self, source: &CommandSource) -> Vec<Arc<Mutex<Player>>> {
+        match self {
+            EntitySelector::Player(name) => PLAYERS
+                .iter()
+                .filter(|player| player.value().lock().username.eq_ignore_ascii_case(name))
+                .map(|player| player.value().clone())
+                .collect(),
+            EntitySelector::Uuid(uuid) => PLAYERS
+                .get(uuid)
+                .map(|player| player.value().clone())
+                .into_iter()
+                .collect(),
+            EntitySelector::NearestPlayer => {
+                let (dimension, pos) = source.get_position();
+                let mut nearest: Option<(f64, Arc<Mutex<Player>>)> = None;
+                for player in PLAYERS.iter() {
+                    let distance = {
+                        let player = player.value().lock();
+                        if player.entity.dimension != dimension {
+                            continue;
+                        }
+                        let (x, y, z) = player.entity.pos;
+                        (x - pos.0).powi(2) + (y - pos.1).powi(2) + (z - pos.2).powi(2)
+                    };
+                    if nearest
+                        .as_ref()
+                        .is_none_or(|(nearest, _)| distance < *nearest)
+                    {
+                        nearest = Some((distance, player.value().clone()));
+                    }
+                }
+                nearest.map(|(_, player)| player).into_iter().collect()
+            }
+            EntitySelector::RandomPlayer => {
+                let players: Vec<Arc<Mutex<Player>>> = PLAYERS
+                    .iter()
+                    .map(|player| player.value().clone())
+                    .collect();
+                if players.is_empty() {
+                    return players;
+                }
+                vec![players[fastrand::usize(..players.len())].clone()]
+            }
+            EntitySelector::AllPlayers => PLAYERS
+                .iter()
+                .map(|player| player.value().clone())
+                .collect(),
+            EntitySelector::Executor => source.get_player().cloned().into_iter().collect(),
+        }
+    }
+}
+
+const GAME_MODES: [&str; 4] = ["survival", "creative", "adventure", "spectator"];
+
+impl ArgumentType {
+    /// Gets the id of the parser in the `minecraft:command_argument_type` registry.
+    pub fn get_parser_id(&self) -> i32 {
+        match self {
+            ArgumentType::Bool => 0,
+            ArgumentType::Float { .. } => 1,
+            ArgumentType::Double { .. } => 2,
+            ArgumentType::Integer { .. } => 3,
+            ArgumentType::Long { .. } => 4,
+            ArgumentType::String(_) => 5,
+            ArgumentType::Entity { .. } => 6,
+            ArgumentType::GameProfile => 7,
+            ArgumentType::BlockPos => 8,
+            ArgumentType::ColumnPos => 9,
+            ArgumentType::Vec3 => 10,
+            ArgumentType::Vec2 => 11,
+            ArgumentType::Message => 19,
+            ArgumentType::Dimension => 40,
+            ArgumentType::GameMode => 41,
+            ArgumentType::Time { .. } => 42,
+            ArgumentType::Uuid => 53,
+        }
+    }
+
+    /// Encodes the properties of the parser, sent after its id in the commands packet.
+    pub async fn encode_properties<W: AsyncWrite + Unpin>(
+        &self,
+        buf: &mut W,
+    ) -> anyhow::Result<()> {
+        match self {
+            ArgumentType::Float { min, max } => {
+                buf.write_u8(range_flags(min.is_some(), max.is_some()))
+                    .await?;
+                if let Some(min) = min {
+                    buf.write_f32(*min).await?;
+                }
+                if let Some(max) = max {
+                    buf.write_f32(*max).await?;
+                }
+            }
+            ArgumentType::Double { min, max } => {
+                buf.write_u8(range_flags(min.is_some(), max.is_some()))
+                    .await?;
+                if let Some(min) = min {
+                    buf.write_f64(*min).await?;
+                }
+                if let Some(max) = max {
+                    buf.write_f64(*max).await?;
+                }
+            }
+            ArgumentType::Integer { min, max } => {
+                buf.write_u8(range_flags(min.is_some(), max.is_some()))
+                    .await?;
+                if let Some(min) = min {
+                    buf.write_i32(*min).await?;
+                }
+                if let Some(max) = max {
+                    buf.write_i32(*max).await?;
+                }
+            }
+            ArgumentType::Long { min, max } => {
+                buf.write_u8(range_flags(min.is_some(), max.is_some()))
+                    .await?;
+                if let Some(min) = min {
+                    buf.write_i64(*min).await?;
+                }
+                if let Some(max) = max {
+                    buf.write_i64(*max).await?;
+                }
+            }
+            ArgumentType::String(kind) => buf.write_var_int(*kind as i32).await?,
+            ArgumentType::Entity {
+                single,
+                players_only,
+            } => {
+                buf.write_u8(*single as u8 | (*players_only as u8) << 1)
+                    .await?
+            }
+            ArgumentType::Time { min } => buf.write_i32(*min).await?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Parses the argument at the cursor of the reader.
+    pub fn parse(&self, reader: &mut StringReader) -> Result<ArgumentValue, CommandError> {
+        let start = reader.get_cursor();
+        let value = match self {
+            ArgumentType::Bool => ArgumentValue::Bool(reader.read_bool()?),
+            ArgumentType::Float { min, max } => {
+                let value = reader.read_float()?;
+                check_range(reader, start, value, *min, *max)?;
+                ArgumentValue::Float(value)
+            }
+            ArgumentType::Double { min, max } => {
+                let value = reader.read_double()?;
+                check_range(reader, start, value, *min, *max)?;
+                ArgumentValue::Double(value)
+            }
+            ArgumentType::Integer { min, max } => {
+                let value = reader.read_int()?;
+                check_range(reader, start, value, *min, *max)?;
+                ArgumentValue::Integer(value)
+            }
+            ArgumentType::Long { min, max } => {
+                let value = reader.read_long()?;
+                check_range(reader, start, value, *min, *max)?;
+                ArgumentValue::Long(value)
+            }
+            ArgumentType::String(StringKind::SingleWord) => {
+                ArgumentValue::String(reader.read_unquoted_string().to_string())
+            }
+            ArgumentType::String(StringKind::QuotablePhrase) => {
+                ArgumentValue::String(reader.read_string()?)
+            }
+            ArgumentType::String(StringKind::GreedyPhrase) | ArgumentType::Message => {
+                ArgumentValue::String(reader.read_remaining().to_string())
+            }
+            ArgumentType::Entity { .. } => ArgumentValue::Entity(parse_selector(reader)?),
+            ArgumentType::GameProfile | ArgumentType::Dimension => {
+                let value = reader.read_while(|c| c != ' ');
+                if value.is_empty() {
+                    return Err(CommandError::syntax("Expected a name", reader));
+                }
+                ArgumentValue::String(value.to_string())
+            }
+            ArgumentType::BlockPos => ArgumentValue::Coordinates(Coordinates {
+                x: parse_coordinate(reader, true, false)?,
+                y: {
+                    expect_space(reader)?;
+                    parse_coordinate(reader, true, false)?
+                },
+                z: {
+                    expect_space(reader)?;
+                    parse_coordinate(reader, true, false)?
+                },
+            }),
+            ArgumentType::ColumnPos => ArgumentValue::Coordinates(Coordinates {
+                x: parse_coordinate(reader, true, false)?,
+                y: RELATIVE_ZERO,
+                z: {
+                    expect_space(reader)?;
+                    parse_coordinate(reader, true, false)?
+                },
+            }),
+            ArgumentType::Vec3 => ArgumentValue::Coordinates(Coordinates {
+                x: parse_coordinate(reader, false, true)?,
+                y: {
+                    expect_space(reader)?;
+                    parse_coordinate(reader, false, false)?
+                },
+                z: {
+                    expect_space(reader)?;
+                    parse_coordinate(reader, false, true)?
+                },
+            }),
+            ArgumentType::Vec2 => ArgumentValue::Coordinates(Coordinates {
+                x: parse_coordinate(reader, false, true)?,
+                y: RELATIVE_ZERO,
+                z: {
+                    expect_space(reader)?;
+                    parse_coordinate(reader, false, true)?
+                },
+            }),
+            ArgumentType::GameMode => {
+                let name = reader.read_unquoted_string();
+                match GAME_MODES.iter().position(|game_mode| *game_mode == name) {
+                    Some(id) => ArgumentValue::GameMode(id as u8),
+                    None => {
+                        reader.set_cursor(start);
+                        return Err(CommandError::syntax(
+                            format!("Unknown game mode: {}", name),
+                            reader,
+                        ));
+                    }
+                }
+            }
+            ArgumentType::Time { min } => {
+                let value = reader.read_float()?;
+                let unit: f32 = match reader.peek() {
+                    Some('d') => 24000.0,
+                    Some('s') => 20.0,
+                    Some('t') => 1.0,
+                    _ => 0.0,
+                };
+                if unit != 0.0 {
+                    reader.skip();
+                }
+                let ticks = (value * unit.max(1.0)).round() as i32;
+                if ticks < *min {
+                    reader.set_cursor(start);
+                    return Err(CommandError::syntax(
+                        format!("Tick count must not be less than {}, found {}", min, ticks),
+                        reader,
+                    ));
+                }
+                ArgumentValue::Time(ticks)
+            }
+            ArgumentType::Uuid => {
+                let value = reader.read_while(|c| c.is_ascii_hexdigit() || c == '-');
+                match Uuid::try_parse(value) {
+                    Ok(uuid) => ArgumentValue::Uuid(uuid),
+                    Err(_) => {
+                        reader.set_cursor(start);
+                        return Err(CommandError::syntax("Invalid UUID", reader));
+                    }
+                }
+            }
+        };
+        Ok(value)
+    }
+
+    /// Lists the values the client may complete the argument with.
+    pub fn suggest(&self) -> Vec<String> {
+        match self {
+            ArgumentType::Bool => vec!["true".to_string(), "false".to_string()],
+            ArgumentType::Entity { .. } | ArgumentType::GameProfile => {
+                let mut names = map_players(|player| player.username.clone());
+                names.extend(["@a", "@e", "@p", "@r", "@s"].map(String::from));
+                names
+            }
+            ArgumentType::GameMode => GAME_MODES.map(String::from).to_vec(),
+            ArgumentType::Dimension => crate::WORLD
+                .dimensions
+                .iter()
+                .map(|dimension| dimension.dimension_name.clone())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+const RELATIVE_ZERO: Coordinate = Coordinate {
+    relative: true,
+    value: 0.0,
+};
+
+#[inline]
+fn range_flags(min: bool, max: bool) -> u8 {
+    min as u8 | (max as u8) << 1
+}
+
+fn check_range<T: PartialOrd + std::fmt::Display>(
+    reader: &mut StringReader,
+    start: usize,
+    value: T,
+    min: Option<T>,
+    max: Option<T>,
+) -> Result<(), CommandError> {
+    if let Some(min) = min.filter(|min| value < *min) {
+        reader.set_cursor(start);
+        return Err(CommandError::syntax(
+            format!("Value must not be less than {}, found {}", min, value),
+            reader,
+        ));
+    }
+    if let Some(max) = max.filter(|max| value > *max) {
+        reader.set_cursor(start);
+        return Err(CommandError::syntax(
+            format!("Value must not be more than {}, found {}", max, value),
+            reader,
+        ));
+    }
+    Ok(())
+}
+
+fn expect_space(reader: &mut StringReader) -> Result<(), CommandError> {
+    if reader.peek() != Some(' ') {
+        return Err(CommandError::syntax("Incomplete position", reader));
+    }
+    reader.skip();
+    Ok(())
+}
+
+/// Parses a coordinate, `~` followed by an optional offset for a relative one.
+///
+/// # Parameters
+/// - `integer`: Whether an absolute coordinate must be an integer.
+/// - `center`: Whether an absolute integer is moved to the center of its block.
+fn parse_coordinate(
+    reader: &mut StringReader,
+    integer: bool,
+    center: bool,
+) -> Result<Coordinate, CommandError> {
+    if reader.peek() == Some('^') {
+        return Err(CommandError::syntax(
+            "Local coordinates are not supported",
+            reader,
+        ));
+    }
+    let relative = reader.peek() == Some('~');
+    if relative {
+        reader.skip();
+        if matches!(reader.peek(), None | Some(' ')) {
+            return Ok(Coordinate {
+                relative,
+                value: 0.0,
+            });
+        }
+    }
+    let start = reader.get_cursor();
+    let value = if integer && !relative {
+        reader.read_int()? as f64
+    } else {
+        reader.read_double()?
+    };
+    let value =
+        if center && !relative && !reader.get_input()[start..reader.get_cursor()].contains('.') {
+            value + 0.5
+        } else {
+            value
+        };
+    Ok(Coordinate { relative, value })
+}
+
+fn parse_selector(reader: &mut StringReader) -> Result<EntitySelector, CommandError> {
+    let start = reader.get_cursor();
+    if reader.peek() == Some('@') {
+        reader.skip();
+        let selector = match reader.peek() {
+            Some('p') => EntitySelector::NearestPlayer,
+            Some('r') => EntitySelector::RandomPlayer,
+            Some('a' | 'e') => EntitySelector::AllPlayers,
+            Some('s') => EntitySelector::Executor,
+            _ => {
+                reader.set_cursor(start);
+                return Err(CommandError::syntax("Unknown selector type", reader));
+            }
+        };
+        reader.skip();
+        if reader.peek() == Some('[') {
+            return Err(CommandError::syntax(
+                "Selector arguments are not supported",
+                reader,
+            ));
+        }
+        return Ok(selector);
+    }
+    let value = reader.read_while(|c| c != ' ');
+    if value.is_empty() {
+        return Err(CommandError::syntax("Expected a player", reader));
+    }
+    Ok(match Uuid::try_parse(value) {
+        Ok(uuid) => EntitySelector::Uuid(uuid),
+        Err(_) => EntitySelector::Player(value.to_string()),
+    })
+}
@@ -0,0 +1,40 @@
+mod deop;
+mod force_load;
+mod game_mode;
+mod help;
+mod kick;
+mod list;
+mod op;
+mod save_all;
+mod save_off;
+mod save_on;
+mod say;
+mod set_world_spawn;
+mod stop;
+mod teleport;
+mod time;
+mod tps;
+mod weather;
+
+use crate::command::dispatcher::CommandDispatcher;
+
+/// Registers the commands of the server.
+pub fn register(dispatcher: &mut CommandDispatcher) {
+    deop::register(dispatcher);
+    force_load::register(dispatcher);
+    game_mode::register(dispatcher);
+    help::register(dispatcher);
+    kick::register(dispatcher);
+    list::register(dispatcher);
+    op::register(dispatcher);
+    save_all::register(dispatcher);
+    save_off::register(dispatcher);
+    save_on::register(dispatcher);
+    say::register(dispatcher);
+    set_world_spawn::register(dispatcher);
+    stop::register(dispatcher);
+    teleport::register(dispatcher);
+    time::register(dispatcher);
+    tps::register(dispatcher);
+    weather::register(dispatcher);
+}
@@ -0,0 +1,62 @@
+use crate::command::argument::ArgumentType;
+use crate::command::dispatcher::{argument, literal, CommandDispatcher};
+use crate::command::{CommandContext, CommandError, CommandResult};
+use crate::entity::player::PlayerUpdate;
+use crate::gameplay::PLAYERS;
+use crate::server::ops::OPS;
+use crate::util::text::TextComponent;
+use tracing::warn;
+use uuid::Uuid;
+
+pub fn register(dispatcher: &mut CommandDispatcher) {
+    dispatcher.register(
+        literal("deop")
+            .requires(|source| source.has_permission(3))
+            .then(argument("targets", ArgumentType::GameProfile).executes(deop)),
+    );
+}
+
+/// Makes players no longer operators, including the ones not in the game.
+fn deop(context: &CommandContext) -> CommandResult {
+    let targets: Vec<(Uuid, String)> = match context.get_players("targets") {
+        Ok(players) => players
+            .iter()
+            .map(|player| {
+                let player = player.lock();
+                (player.entity.uuid, player.username.clone())
+            })
+            .collect(),
+        Err(err) => {
+            let entry = OPS.find(context.get_string("targets")?).ok_or(err)?;
+            vec![(entry.uuid, entry.name)]
+        }
+    };
+    let mut count = 0;
+    for (uuid, name) in targets {
+        match OPS.remove(uuid) {
+            Ok(true) => {}
+            Ok(false) => continue,
+            Err(err) => {
+                warn!("Error in saving the operators: {:?}", err);
+                return Err(CommandError::new("Failed to save the operators"));
+            }
+        }
+        let connection = PLAYERS
+            .get(&uuid)
+            .map(|player| player.lock().connection.clone());
+        if let Some(connection) = connection {
+            let _ = connection.send_update(PlayerUpdate::SetPermissionLevel(0));
+        }
+        context.source.send_message(TextComponent::text(format!(
+            "Made {} no longer a server operator",
+            name
+        )));
+        count += 1;
+    }
+    if count == 0 {
+        return Err(CommandError::new(
+            "Nothing changed. The player is not an operator",
+        ));
+    }
+    Ok(count)
+}
@@ -0,0 +1,183 @@
+use crate::command::argument::ArgumentType;
+use crate::command::dispatcher::{argument, literal, CommandDispatcher};
+use crate::command::{CommandContext, CommandError, CommandResult};
+use crate::util::text::TextComponent;
+use crate::world::dimension::Dimension;
+use crate::WORLD;
+use std::sync::Arc;
+
+/// The most chunks marked or unmarked at once.
+const MAX_CHUNKS: i64 = 256;
+
+pub fn register(dispatcher: &mut CommandDispatcher) {
+    dispatcher.register(
+        literal("forceload")
+            .requires(|source| source.has_permission(2))
+            .then(
+                literal("add").then(
+                    argument("from", ArgumentType::ColumnPos)
+                        .executes(|context| {
+                            let from = get_chunk_pos(context, "from")?;
+                            change_force_load(context, from, from, true)
+                        })
+                        .then(argument("to", ArgumentType::ColumnPos).executes(|context| {
+                            let from = get_chunk_pos(context, "from")?;
+                            let to = get_chunk_pos(context, "to")?;
+                            change_force_load(context, from, to, true)
+                        })),
+                ),
+            )
+            .then(
+                literal("remove")
+                    .then(
+                        argument("from", ArgumentType::ColumnPos)
+                            .executes(|context| {
+                                let from = get_chunk_pos(context, "from")?;
+                                change_force_load(context, from, from, false)
+                            })
+                            .then(argument("to", ArgumentType::ColumnPos).executes(|context| {
+                                let from = get_chunk_pos(context, "from")?;
+                                let to = get_chunk_pos(context, "to")?;
+                                change_force_load(context, from, to, false)
+                            })),
+                    )
+                    .then(literal("all").executes(remove_all)),
+            )
+            .then(literal("query").executes(list_force_loaded).then(
+                argument("pos", ArgumentType::ColumnPos).executes(|context| {
+                    let pos = get_chunk_pos(context, "pos")?;
+                    query_force_load(context, pos)
+                }),
+            )),
+    );
+}
+
+/// Gets the position of the chunk containing a column position argument.
+fn get_chunk_pos(context: &CommandContext, name: &str) -> Result<(i32, i32), CommandError> {
+    let (x, _, z) = context.get_position(name)?;
+    Ok((x.floor() as i32 >> 4, z.floor() as i32 >> 4))
+}
+
+fn get_dimension(context: &CommandContext) -> Arc<Dimension> {
+    WORLD.dimensions[context.source.get_position().0].clone()
+}
+
+fn format_chunk_pos((x, z): (i32, i32)) -> String {
+    format!("[{}, {}]", x, z)
+}
+
+fn change_force_load(
+    context: &CommandContext,
+    from: (i32, i32),
+    to: (i32, i32),
+    forced: bool,
+) -> CommandResult {
+    let (min_x, max_x) = (from.0.min(to.0), from.0.max(to.0));
+    let (min_z, max_z) = (from.1.min(to.1), from.1.max(to.1));
+    let count = (max_x as i64 - min_x as i64 + 1) * (max_z as i64 - min_z as i64 + 1);
+    if count > MAX_CHUNKS {
+        return Err(CommandError::new(format!(
+            "Too many chunks in the specified area (maximum {}, specified {})",
+            MAX_CHUNKS, count
+        )));
+    }
+    let dimension = get_dimension(context);
+    let mut changed = Vec::new();
+    for x in min_x..=max_x {
+        for z in min_z..=max_z {
+            if dimension.set_chunk_forced(x, z, forced) {
+                changed.push((x, z));
+            }
+        }
+    }
+    let name = &dimension.dimension_name;
+    let message = match (changed.as_slice(), forced) {
+        ([], true) => return Err(CommandError::new("No chunks were marked for force loading")),
+        ([], false) => {
+            return Err(CommandError::new(
+                "No chunks were removed from force loading",
+            ))
+        }
+        ([pos], true) => format!(
+            "Marked chunk {} in {} to be force loaded",
+            format_chunk_pos(*pos),
+            name
+        ),
+        ([pos], false) => format!(
+            "Unmarked chunk {} in {} for force loading",
+            format_chunk_pos(*pos),
+            name
+        ),
+        (_, true) => format!(
+            "Marked {} chunks in {} from {} to {} to be force loaded",
+            changed.len(),
+            name,
+            format_chunk_pos((min_x, min_z)),
+            format_chunk_pos((max_x, max_z))
+        ),
+        (_, false) => format!(
+            "Unmarked {} chunks in {} from {} to {} for force loading",
+            changed.len(),
+            name,
+            format_chunk_pos((min_x, min_z)),
+            format_chunk_pos((max_x, max_z))
+        ),
+    };
+    context.source.send_message(TextComponent::text(message));
+    Ok(changed.len() as i32)
+}
+
+fn remove_all(context: &CommandContext) -> CommandResult {
+    let dimension = get_dimension(context);
+    for (x, z) in dimension.get_forced_chunks() {
+        dimension.set_chunk_forced(x, z, false);
+    }
+    context.source.send_message(TextComponent::text(format!(
+        "Unmarked all force loaded chunks in {}",
+        dimension.dimension_name
+    )));
+    Ok(0)
+}
+
+fn query_force_load(context: &CommandContext, pos: (i32, i32)) -> CommandResult {
+    let dimension = get_dimension(context);
+    if !dimension.get_forced_chunks().contains(&pos) {
+        return Err(CommandError::new(format!(
+            "Chunk at {} in {} is not marked for force loading",
+            format_chunk_pos(pos),
+            dimension.dimension_name
+        )));
+    }
+    context.source.send_message(TextComponent::text(format!(
+        "Chunk at {} in {} is marked for force loading",
+        format_chunk_pos(pos),
+        dimension.dimension_name
+    )));
+    Ok(1)
+}
+
+fn list_force_loaded(context: &CommandContext) -> CommandResult {
+    let dimension = get_dimension(context);
+    let forced = dimension.get_forced_chunks();
+    let name = &dimension.dimension_name;
+    let message = match forced.as_slice() {
+        [] => format!("No force loaded chunks were found in {}", name),
+        [pos] => format!(
+            "A force loaded chunk was found in {} at: {}",
+            name,
+            format_chunk_pos(*pos)
+        ),
+        _ => format!(
+            "{} force loaded chunks were found in {} at: {}",
+            forced.len(),
+            name,
+            forced
+                .iter()
+                .map(|pos| format_chunk_pos(*pos))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    };
+    context.source.send_message(TextComponent::text(message));
+    Ok(forced.len() as i32)
+}
@@ -0,0 +1,66 @@
+use crate::command::argument::ArgumentType;
+use crate::command::dispatcher::{argument, literal, CommandDispatcher};
+use crate::command::{CommandContext, CommandError, CommandResult};
+use crate::entity::player::{Player, PlayerUpdate};
+use crate::util::text::TextComponent;
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+const GAME_MODE_NAMES: [&str; 4] = ["Survival", "Creative", "Adventure", "Spectator"];
+
+pub fn register(dispatcher: &mut CommandDispatcher) {
+    dispatcher.register(
+        literal("gamemode")
+            .requires(|source| source.has_permission(2))
+            .then(
+                argument("gamemode", ArgumentType::GameMode)
+                    .executes(|context| {
+                        let player = context.source.get_player().ok_or(CommandError::new(
+                            "A player is required to run this command here",
+                        ))?;
+                        set_game_mode(context, vec![player.clone()])
+                    })
+                    .then(
+                        argument(
+                            "target",
+                            ArgumentType::Entity {
+                                single: false,
+                                players_only: true,
+                            },
+                        )
+                        .executes(|context| set_game_mode(context, context.get_players("target")?)),
+                    ),
+            ),
+    );
+}
+
+fn set_game_mode(context: &CommandContext, targets: Vec<Arc<Mutex<Player>>>) -> CommandResult {
+    let game_mode = context.get_game_mode("gamemode")?;
+    let mode = format!("{} Mode", GAME_MODE_NAMES[game_mode as usize]);
+    for target in targets.iter() {
+        let (name, connection) = {
+            let target = target.lock();
+            (target.username.clone(), target.connection.clone())
+        };
+        let _ = connection.send_update(PlayerUpdate::SetGameMode(game_mode));
+        let own = context
+            .source
+            .get_player()
+            .is_some_and(|player| Arc::ptr_eq(player, target));
+        if own {
+            context.source.send_message(TextComponent::text(format!(
+                "Set own game mode to {}",
+                mode
+            )));
+        } else {
+            context.source.send_message(TextComponent::text(format!(
+                "Set {}'s game mode to {}",
+                name, mode
+            )));
+            let _ = connection.send_update(PlayerUpdate::SystemMessage(TextComponent::text(
+                format!("Your game mode has been updated to {}", mode),
+            )));
+        }
+    }
+    Ok(targets.len() as i32)
+}
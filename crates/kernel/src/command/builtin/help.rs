@@ -0,0 +1,47 @@
+use crate::command::argument::{ArgumentType, StringKind};
+use crate::command::dispatcher::{argument, literal, CommandDispatcher};
+use crate::command::{CommandContext, CommandError, CommandResult, DISPATCHER};
+use crate::util::text::TextComponent;
+
+pub fn register(dispatcher: &mut CommandDispatcher) {
+    dispatcher.register(literal("help").executes(help).then(
+        argument("command", ArgumentType::String(StringKind::GreedyPhrase)).executes(help_command),
+    ));
+}
+
+fn help(context: &CommandContext) -> CommandResult {
+    // The dispatcher is already read by the command running.
+    let dispatcher = DISPATCHER.read_recursive();
+    let mut usages: Vec<String> = dispatcher
+        .get_children(CommandDispatcher::ROOT, context.source)
+        .into_iter()
+        .map(|node| dispatcher.get_usage(node, context.source))
+        .collect();
+    usages.sort();
+    for usage in usages.iter() {
+        context
+            .source
+            .send_message(TextComponent::text(format!("/{}", usage)));
+    }
+    Ok(usages.len() as i32)
+}
+
+fn help_command(context: &CommandContext) -> CommandResult {
+    let command = context.get_string("command")?;
+    let name = command.split(' ').next().unwrap_or_default();
+    let dispatcher = DISPATCHER.read_recursive();
+    match dispatcher.find_command(name) {
+        Some(node)
+            if dispatcher
+                .get_children(CommandDispatcher::ROOT, context.source)
+                .contains(&node) =>
+        {
+            context.source.send_message(TextComponent::text(format!(
+                "/{}",
+                dispatcher.get_usage(node, context.source)
+            )));
+            Ok(1)
+        }
+        _ => Err(CommandError::new(format!("Unknown command '{}'", name))),
+    }
+}
@@ -0,0 +1,44 @@
+use crate::command::argument::ArgumentType;
+use crate::command::dispatcher::{argument, literal, CommandDispatcher};
+use crate::command::{CommandContext, CommandResult};
+use crate::entity::player::PlayerUpdate;
+use crate::util::text::TextComponent;
+
+const DEFAULT_REASON: &str = "Kicked by an operator";
+
+pub fn register(dispatcher: &mut CommandDispatcher) {
+    dispatcher.register(
+        literal("kick")
+            .requires(|source| source.has_permission(3))
+            .then(
+                argument(
+                    "targets",
+                    ArgumentType::Entity {
+                        single: false,
+                        players_only: true,
+                    },
+                )
+                .executes(|context| kick(context, DEFAULT_REASON))
+                .then(
+                    argument("reason", ArgumentType::Message)
+                        .executes(|context| kick(context, context.get_string("reason")?)),
+                ),
+            ),
+    );
+}
+
+fn kick(context: &CommandContext, reason: &str) -> CommandResult {
+    let players = context.get_players("targets")?;
+    for player in players.iter() {
+        // Not while holding the player's lock, the source may be the player itself.
+        let (name, connection) = {
+            let player = player.lock();
+            (player.username.clone(), player.connection.clone())
+        };
+        let _ = connection.send_update(PlayerUpdate::Kick(TextComponent::text(reason)));
+        context
+            .source
+            .send_message(TextComponent::text(format!("Kicked {}: {}", name, reason)));
+    }
+    Ok(players.len() as i32)
+}
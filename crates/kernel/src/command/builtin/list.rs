@@ -0,0 +1,21 @@
+use crate::command::dispatcher::{literal, CommandDispatcher};
+use crate::command::{CommandContext, CommandResult};
+use crate::config::MAX_PLAYERS;
+use crate::gameplay::map_players;
+use crate::util::text::TextComponent;
+
+pub fn register(dispatcher: &mut CommandDispatcher) {
+    dispatcher.register(literal("list").executes(list));
+}
+
+fn list(context: &CommandContext) -> CommandResult {
+    let mut names = map_players(|player| player.username.clone());
+    names.sort();
+    context.source.send_message(TextComponent::text(format!(
+        "There are {} of a max of {} players online: {}",
+        names.len(),
+        *MAX_PLAYERS,
+        names.join(", ")
+    )));
+    Ok(names.len() as i32)
+}
@@ -0,0 +1,51 @@
+use crate::command::argument::ArgumentType;
+use crate::command::dispatcher::{argument, literal, CommandDispatcher};
+use crate::command::{CommandContext, CommandError, CommandResult};
+use crate::config::OP_PERMISSION_LEVEL;
+use crate::entity::player::PlayerUpdate;
+use crate::server::ops::OPS;
+use crate::util::text::TextComponent;
+use tracing::warn;
+
+pub fn register(dispatcher: &mut CommandDispatcher) {
+    dispatcher.register(
+        literal("op")
+            .requires(|source| source.has_permission(3))
+            .then(argument("targets", ArgumentType::GameProfile).executes(op)),
+    );
+}
+
+/// Makes online players operators, sending them the commands they may now use.
+fn op(context: &CommandContext) -> CommandResult {
+    let mut count = 0;
+    for player in context.get_players("targets")? {
+        let (uuid, name, connection) = {
+            let player = player.lock();
+            (
+                player.entity.uuid,
+                player.username.clone(),
+                player.connection.clone(),
+            )
+        };
+        match OPS.add(uuid, &name) {
+            Ok(true) => {}
+            Ok(false) => continue,
+            Err(err) => {
+                warn!("Error in saving the operators: {:?}", err);
+                return Err(CommandError::new("Failed to save the operators"));
+            }
+        }
+        let _ = connection.send_update(PlayerUpdate::SetPermissionLevel(*OP_PERMISSION_LEVEL));
+        context.source.send_message(TextComponent::text(format!(
+            "Made {} a server operator",
+            name
+        )));
+        count += 1;
+    }
+    if count == 0 {
+        return Err(CommandError::new(
+            "Nothing changed. The player already is an operator",
+        ));
+    }
+    Ok(count)
+}
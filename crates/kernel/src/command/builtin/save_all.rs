@@ -0,0 +1,44 @@
+use crate::command::dispatcher::{literal, CommandDispatcher};
+use crate::command::{CommandContext, CommandResult, CommandSource};
+use crate::server;
+use crate::util::text::TextComponent;
+use tokio::runtime::Handle;
+use tracing::warn;
+
+pub fn register(dispatcher: &mut CommandDispatcher) {
+    dispatcher.register(
+        literal("save-all")
+            .requires(|source| source.has_permission(4))
+            .executes(save_all)
+            // Every chunk is written before the game is reported as saved, so flushing does not wait any longer.
+            .then(literal("flush").executes(save_all)),
+    );
+}
+
+/// Saves the players, the chunks of every dimension, including the unloaded ones waiting to be saved,
+/// and the level data, reporting the game as saved only once all of it is written.
+///
+/// The save runs on a blocking thread, so neither the player's connection nor the dispatcher wait for it.
+fn save_all(context: &CommandContext) -> CommandResult {
+    context.source.send_message(TextComponent::text(
+        "Saving the game (this may take a moment!)",
+    ));
+    let source = context.source.clone();
+    match Handle::try_current() {
+        Ok(handle) => {
+            handle.spawn_blocking(move || save(&source));
+        }
+        // The console runs its commands on its own thread.
+        Err(_) => save(&source),
+    }
+    Ok(1)
+}
+
+fn save(source: &CommandSource) {
+    if let Err(err) = server::save_all() {
+        warn!("Error in saving the world: {:?}", err);
+        source.send_error("Saving failed");
+        return;
+    }
+    source.send_message(TextComponent::text("Saved the game"));
+}
@@ -0,0 +1,20 @@
+use crate::command::dispatcher::{literal, CommandDispatcher};
+use crate::command::{CommandError, CommandResult};
+use crate::server;
+use crate::util::text::TextComponent;
+
+pub fn register(dispatcher: &mut CommandDispatcher) {
+    dispatcher.register(
+        literal("save-off")
+            .requires(|source| source.has_permission(4))
+            .executes(|context| -> CommandResult {
+                if !server::set_saving(false) {
+                    return Err(CommandError::new("Saving is already turned off"));
+                }
+                context
+                    .source
+                    .send_message(TextComponent::text("Automatic saving is now disabled"));
+                Ok(1)
+            }),
+    );
+}
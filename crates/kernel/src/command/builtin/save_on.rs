@@ -0,0 +1,20 @@
+use crate::command::dispatcher::{literal, CommandDispatcher};
+use crate::command::{CommandError, CommandResult};
+use crate::server;
+use crate::util::text::TextComponent;
+
+pub fn register(dispatcher: &mut CommandDispatcher) {
+    dispatcher.register(
+        literal("save-on")
+            .requires(|source| source.has_permission(4))
+            .executes(|context| -> CommandResult {
+                if server::set_saving(true) {
+                    return Err(CommandError::new("Saving is already turned on"));
+                }
+                context
+                    .source
+                    .send_message(TextComponent::text("Automatic saving is now enabled"));
+                Ok(1)
+            }),
+    );
+}
@@ -0,0 +1,31 @@
+use crate::command::argument::ArgumentType;
+use crate::command::dispatcher::{argument, literal, CommandDispatcher};
+use crate::command::{CommandContext, CommandResult};
+use crate::entity::player::PlayerUpdate;
+use crate::gameplay::map_players;
+use crate::util::text::TextComponent;
+use tracing::info;
+
+pub fn register(dispatcher: &mut CommandDispatcher) {
+    dispatcher.register(
+        literal("say")
+            .requires(|source| source.has_permission(2))
+            .then(argument("message", ArgumentType::Message).executes(say)),
+    );
+}
+
+/// Sends a message to every player, as `[source] message`.
+fn say(context: &CommandContext) -> CommandResult {
+    let message = TextComponent::text(format!(
+        "[{}] {}",
+        context.source.get_name(),
+        context.get_string("message")?
+    ));
+    info!("{}", message.text);
+    map_players(|player| {
+        let _ = player
+            .connection
+            .send_update(PlayerUpdate::SystemMessage(message.clone()));
+    });
+    Ok(1)
+}
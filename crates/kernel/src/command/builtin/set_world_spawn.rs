@@ -0,0 +1,36 @@
+use crate::command::argument::ArgumentType;
+use crate::command::dispatcher::{argument, literal, CommandDispatcher};
+use crate::command::{CommandContext, CommandError, CommandResult};
+use crate::util::text::TextComponent;
+use crate::WORLD;
+
+pub fn register(dispatcher: &mut CommandDispatcher) {
+    dispatcher.register(
+        literal("setworldspawn")
+            .requires(|source| source.has_permission(2))
+            .executes(|context| set_world_spawn(context, context.source.get_position().1))
+            .then(
+                argument("pos", ArgumentType::BlockPos)
+                    .executes(|context| set_world_spawn(context, context.get_position("pos")?)),
+            ),
+    );
+}
+
+fn set_world_spawn(context: &CommandContext, pos: (f64, f64, f64)) -> CommandResult {
+    if context.source.get_position().0 != WORLD.get_world_spawn_point().0 {
+        return Err(CommandError::new(
+            "Can only set the world spawn for the default dimension",
+        ));
+    }
+    let pos = (
+        pos.0.floor() as i32,
+        pos.1.floor() as i32,
+        pos.2.floor() as i32,
+    );
+    WORLD.set_world_spawn_point(pos, 0.0);
+    context.source.send_message(TextComponent::text(format!(
+        "Set the world spawn point to {}, {}, {}",
+        pos.0, pos.1, pos.2
+    )));
+    Ok(1)
+}
@@ -0,0 +1,13 @@
+use crate::command::dispatcher::{literal, CommandDispatcher};
+use crate::server;
+
+pub fn register(dispatcher: &mut CommandDispatcher) {
+    dispatcher.register(
+        literal("stop")
+            .requires(|source| source.has_permission(4))
+            .executes(|_| {
+                server::stop();
+                Ok(1)
+            }),
+    );
+}
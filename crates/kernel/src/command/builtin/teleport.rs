@@ -0,0 +1,113 @@
+use crate::command::argument::ArgumentType;
+use crate::command::dispatcher::{argument, literal, CommandDispatcher};
+use crate::command::{CommandContext, CommandError, CommandResult};
+use crate::entity::player::{Player, PlayerUpdate};
+use crate::entity::Entity;
+use crate::util::text::TextComponent;
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+const TARGETS: ArgumentType = ArgumentType::Entity {
+    single: false,
+    players_only: true,
+};
+const DESTINATION: ArgumentType = ArgumentType::Entity {
+    single: true,
+    players_only: true,
+};
+
+pub fn register(dispatcher: &mut CommandDispatcher) {
+    dispatcher.register(
+        literal("teleport")
+            .requires(|source| source.has_permission(2))
+            .then(
+                argument("location", ArgumentType::Vec3).executes(|context| {
+                    let (dimension, pos) = to_location(context)?;
+                    teleport(context, vec![get_source_player(context)?], dimension, pos)
+                }),
+            )
+            .then(argument("destination", DESTINATION).executes(|context| {
+                let (dimension, pos) = to_destination(context)?;
+                teleport(context, vec![get_source_player(context)?], dimension, pos)
+            }))
+            .then(
+                argument("targets", TARGETS)
+                    .then(
+                        argument("location", ArgumentType::Vec3).executes(|context| {
+                            let (dimension, pos) = to_location(context)?;
+                            teleport(context, context.get_players("targets")?, dimension, pos)
+                        }),
+                    )
+                    .then(argument("destination", DESTINATION).executes(|context| {
+                        let (dimension, pos) = to_destination(context)?;
+                        teleport(context, context.get_players("targets")?, dimension, pos)
+                    })),
+            ),
+    );
+    let _ = dispatcher.register_alias("tp", "teleport");
+}
+
+fn get_source_player(context: &CommandContext) -> Result<Arc<Mutex<Player>>, CommandError> {
+    context
+        .source
+        .get_player()
+        .cloned()
+        .ok_or(CommandError::new(
+            "A player is required to run this command here",
+        ))
+}
+
+/// Gets the location argument, in the dimension of the source.
+fn to_location(context: &CommandContext) -> Result<(usize, (f64, f64, f64)), CommandError> {
+    Ok((
+        context.source.get_position().0,
+        context.get_position("location")?,
+    ))
+}
+
+/// Gets the position of the destination player.
+fn to_destination(context: &CommandContext) -> Result<(usize, (f64, f64, f64)), CommandError> {
+    let destination = context.get_player("destination")?;
+    let destination = destination.lock();
+    Ok((destination.get_data().dimension, destination.get_data().pos))
+}
+
+fn teleport(
+    context: &CommandContext,
+    targets: Vec<Arc<Mutex<Player>>>,
+    dimension: usize,
+    pos: (f64, f64, f64),
+) -> CommandResult {
+    let targets: Vec<(String, usize, _)> = targets
+        .iter()
+        .map(|target| {
+            let target = target.lock();
+            (
+                target.username.clone(),
+                target.get_data().dimension,
+                target.connection.clone(),
+            )
+        })
+        .collect();
+    if targets.iter().any(|target| target.1 != dimension) {
+        return Err(CommandError::new(
+            "Players cannot be teleported to another dimension",
+        ));
+    }
+    for (_, _, connection) in targets.iter() {
+        let _ = connection.send_update(PlayerUpdate::Teleport(pos));
+    }
+    let (x, y, z) = pos;
+    let message = match targets.as_slice() {
+        [(name, _, _)] => format!("Teleported {} to {:.2}, {:.2}, {:.2}", name, x, y, z),
+        targets => format!(
+            "Teleported {} players to {:.2}, {:.2}, {:.2}",
+            targets.len(),
+            x,
+            y,
+            z
+        ),
+    };
+    context.source.send_message(TextComponent::text(message));
+    Ok(targets.len() as i32)
+}
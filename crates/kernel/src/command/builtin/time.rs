@@ -0,0 +1,66 @@
+use crate::command::argument::ArgumentType;
+use crate::command::dispatcher::{argument, literal, CommandDispatcher};
+use crate::command::{CommandContext, CommandResult};
+use crate::util::text::TextComponent;
+use crate::world::level::DAY_LENGTH;
+use crate::WORLD;
+
+const NAMED_TIMES: [(&str, i64); 4] = [
+    ("day", 1000),
+    ("noon", 6000),
+    ("night", 13000),
+    ("midnight", 18000),
+];
+
+pub fn register(dispatcher: &mut CommandDispatcher) {
+    let mut set = literal("set").then(
+        argument("time", ArgumentType::Time { min: 0 })
+            .executes(|context| set_time(context, context.get_integer("time")? as i64)),
+    );
+    for (name, time) in NAMED_TIMES {
+        set = set.then(literal(name).executes(move |context| set_time(context, time)));
+    }
+    dispatcher.register(
+        literal("time")
+            .requires(|source| source.has_permission(2))
+            .then(set)
+            .then(
+                literal("add").then(argument("time", ArgumentType::Time { min: 0 }).executes(
+                    |context| {
+                        let day_time = WORLD.level_data.lock().day_time;
+                        set_time(context, day_time + context.get_integer("time")? as i64)
+                    },
+                )),
+            )
+            .then(
+                literal("query")
+                    .then(literal("daytime").executes(|context| {
+                        query(context, WORLD.level_data.lock().day_time % DAY_LENGTH)
+                    }))
+                    .then(
+                        literal("gametime")
+                            .executes(|context| query(context, WORLD.level_data.lock().game_time)),
+                    )
+                    .then(
+                        literal("day")
+                            .executes(|context| query(context, WORLD.level_data.lock().get_day())),
+                    ),
+            ),
+    );
+}
+
+fn set_time(context: &CommandContext, day_time: i64) -> CommandResult {
+    WORLD.set_day_time(day_time);
+    context
+        .source
+        .send_message(TextComponent::text(format!("Set the time to {}", day_time)));
+    Ok((day_time % DAY_LENGTH) as i32)
+}
+
+fn query(context: &CommandContext, time: i64) -> CommandResult {
+    let time = (time % i32::MAX as i64) as i32;
+    context
+        .source
+        .send_message(TextComponent::text(format!("The time is {}", time)));
+    Ok(time)
+}
@@ -0,0 +1,24 @@
+use crate::command::dispatcher::{literal, CommandDispatcher};
+use crate::command::{CommandContext, CommandResult};
+use crate::server::TICK_TIMES;
+use crate::util::text::TextComponent;
+
+pub fn register(dispatcher: &mut CommandDispatcher) {
+    dispatcher.register(
+        literal("tps")
+            .requires(|source| source.has_permission(2))
+            .executes(tps),
+    );
+}
+
+fn tps(context: &CommandContext) -> CommandResult {
+    let (tps, mspt) = {
+        let tick_times = TICK_TIMES.lock();
+        (tick_times.get_tps(), tick_times.get_mspt())
+    };
+    context.source.send_message(TextComponent::text(format!(
+        "TPS: {:.1}, mean tick time: {:.2} ms",
+        tps, mspt
+    )));
+    Ok(tps.round() as i32)
+}
@@ -0,0 +1,48 @@
+use crate::command::argument::ArgumentType;
+use crate::command::dispatcher::{argument, literal, CommandDispatcher};
+use crate::command::{CommandContext, CommandResult};
+use crate::util::text::TextComponent;
+use crate::WORLD;
+
+const WEATHERS: [(&str, bool, bool, &str); 3] = [
+    ("clear", false, false, "clear"),
+    ("rain", true, false, "rain"),
+    ("thunder", true, true, "rain & thunder"),
+];
+
+pub fn register(dispatcher: &mut CommandDispatcher) {
+    let mut weather = literal("weather").requires(|source| source.has_permission(2));
+    for (name, raining, thundering, description) in WEATHERS {
+        weather = weather.then(
+            literal(name)
+                .executes(move |context| {
+                    set_weather(context, raining, thundering, None, description)
+                })
+                .then(
+                    argument("duration", ArgumentType::Time { min: 1 }).executes(move |context| {
+                        let duration = context.get_integer("duration")?;
+                        set_weather(context, raining, thundering, Some(duration), description)
+                    }),
+                ),
+        );
+    }
+    dispatcher.register(weather);
+}
+
+fn set_weather(
+    context: &CommandContext,
+    raining: bool,
+    thundering: bool,
+    duration: Option<i32>,
+    description: &str,
+) -> CommandResult {
+    WORLD
+        .level_data
+        .lock()
+        .set_weather(raining, thundering, duration);
+    context.source.send_message(TextComponent::text(format!(
+        "Set the weather to {}",
+        description
+    )));
+    Ok(duration.unwrap_or(1))
+}
@@ -0,0 +1,499 @@
+use crate::command::argument::{ArgumentType, ArgumentValue, StringKind};
+use crate::command::reader::StringReader;
+use crate::command::{CommandContext, CommandError, CommandResult, CommandSource};
+use crate::network::packet::s2c::commands::CommandNodeData;
+use hashbrown::HashMap;
+use std::sync::Arc;
+
+pub type Executor = Arc<dyn Fn(&CommandContext) -> CommandResult + Send + Sync>;
+pub type Requirement = Arc<dyn Fn(&CommandSource) -> bool + Send + Sync>;
+/// Lists the values of an argument, asked by the client as it types.
+pub type SuggestionProvider = Arc<dyn Fn(&CommandSource) -> Vec<String> + Send + Sync>;
+
+#[derive(Clone)]
+pub enum NodeKind {
+    Root,
+    Literal(String),
+    Argument {
+        name: String,
+        argument_type: ArgumentType,
+    },
+}
+
+impl NodeKind {
+    fn same_as(&self, other: &NodeKind) -> bool {
+        match (self, other) {
+            (NodeKind::Literal(a), NodeKind::Literal(b)) => a == b,
+            (NodeKind::Argument { name: a, .. }, NodeKind::Argument { name: b, .. }) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// A node of the command graph, the root, a literal word or an argument.
+#[derive(Clone)]
+pub struct CommandNode {
+    pub kind: NodeKind,
+    /// The indices of the children in the dispatcher's nodes.
+    pub children: Vec<usize>,
+    /// Runs the command, if it may end at this node.
+    pub executor: Option<Executor>,
+    /// Whether a source may use the node, it is hidden from the others.
+    pub requirement: Option<Requirement>,
+    /// The node whose children follow this one, for aliases.
+    pub redirect: Option<usize>,
+    pub suggestions: Option<SuggestionProvider>,
+}
+
+/// Builds a branch of the command graph, registered with [`CommandDispatcher::register`].
+pub struct CommandBuilder {
+    kind: NodeKind,
+    children: Vec<CommandBuilder>,
+    executor: Option<Executor>,
+    requirement: Option<Requirement>,
+    suggestions: Option<SuggestionProvider>,
+}
+
+/// Starts a branch with a literal word.
+pub fn literal(name: &str) -> CommandBuilder {
+    CommandBuilder::new(NodeKind::Literal(name.to_string()))
+}
+
+/// Starts a branch with an argument, stored under its name in the context.
+pub fn argument(name: &str, argument_type: ArgumentType) -> CommandBuilder {
+    CommandBuilder::new(NodeKind::Argument {
+        name: name.to_string(),
+        argument_type,
+    })
+}
+
+impl CommandBuilder {
+    fn new(kind: NodeKind) -> CommandBuilder {
+        CommandBuilder {
+            kind,
+            children: Vec::new(),
+            executor: None,
+            requirement: None,
+            suggestions: None,
+        }
+    }
+
+    pub fn then(mut self, child: CommandBuilder) -> CommandBuilder {
+        self.children.push(child);
+        self
+    }
+
+    pub fn executes<F>(mut self, executor: F) -> CommandBuilder
+    where
+        F: Fn(&CommandContext) -> CommandResult + Send + Sync + 'static,
+    {
+        self.executor = Some(Arc::new(executor));
+        self
+    }
+
+    pub fn requires<F>(mut self, requirement: F) -> CommandBuilder
+    where
+        F: Fn(&CommandSource) -> bool + Send + Sync + 'static,
+    {
+        self.requirement = Some(Arc::new(requirement));
+        self
+    }
+
+    /// Makes the client ask the server for the suggestions of the argument.
+    pub fn suggests<F>(mut self, suggestions: F) -> CommandBuilder
+    where
+        F: Fn(&CommandSource) -> Vec<String> + Send + Sync + 'static,
+    {
+        self.suggestions = Some(Arc::new(suggestions));
+        self
+    }
+}
+
+/// The graph of the registered commands, parsing and running them.
+///
+/// Like Brigadier, a command is parsed word by word along the graph,
+/// a literal taking precedence over the arguments of the same node.
+pub struct CommandDispatcher {
+    nodes: Vec<CommandNode>,
+}
+
+impl Default for CommandDispatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CommandDispatcher {
+    pub const ROOT: usize = 0;
+
+    pub fn new() -> CommandDispatcher {
+        CommandDispatcher {
+            nodes: vec![CommandNode {
+                kind: NodeKind::Root,
+                children: Vec::new(),
+                executor: None,
+                requirement: None,
+                redirect: None,
+                suggestions: None,
+            }],
+        }
+    }
+
+    pub fn get_node(&self, index: usize) -> &CommandNode {
+        &self.nodes[index]
+    }
+
+    /// Registers a command, merged into an existing one of the same name.
+    ///
+    /// # Returns
+    /// The index of the command's node.
+    pub fn register(&mut self, command: CommandBuilder) -> usize {
+        self.insert(Self::ROOT, command)
+    }
+
+    fn insert(&mut self, parent: usize, builder: CommandBuilder) -> usize {
+        let existing = self.nodes[parent]
+            .children
+            .iter()
+            .copied()
+            .find(|child| self.nodes[*child].kind.same_as(&builder.kind));
+        let index = match existing {
+            Some(index) => {
+                let node = &mut self.nodes[index];
+                node.kind = builder.kind;
+                if builder.executor.is_some() {
+                    node.executor = builder.executor;
+                }
+                if builder.requirement.is_some() {
+                    node.requirement = builder.requirement;
+                }
+                if builder.suggestions.is_some() {
+                    node.suggestions = builder.suggestions;
+                }
+                index
+            }
+            None => {
+                self.nodes.push(CommandNode {
+                    kind: builder.kind,
+                    children: Vec::new(),
+                    executor: builder.executor,
+                    requirement: builder.requirement,
+                    redirect: None,
+                    suggestions: builder.suggestions,
+                });
+                let index = self.nodes.len() - 1;
+                self.nodes[parent].children.push(index);
+                index
+            }
+        };
+        for child in builder.children {
+            self.insert(index, child);
+        }
+        index
+    }
+
+    /// Registers another name for a command.
+    ///
+    /// # Errors
+    /// Returns an error if the command is not registered.
+    pub fn register_alias(&mut self, alias: &str, command: &str) -> Result<usize, CommandError> {
+        let target = self
+            .find_command(command)
+            .ok_or_else(|| CommandError::new(format!("Unknown command '{}'", command)))?;
+        let index = self.insert(Self::ROOT, literal(alias));
+        let executor = self.nodes[target].executor.clone();
+        let requirement = self.nodes[target].requirement.clone();
+        let node = &mut self.nodes[index];
+        node.redirect = Some(target);
+        node.executor = executor;
+        node.requirement = requirement;
+        Ok(index)
+    }
+
+    /// Finds the node of a registered command by its name.
+    pub fn find_command(&self, name: &str) -> Option<usize> {
+        self.nodes[Self::ROOT].children.iter().copied().find(|child| {
+            matches!(&self.nodes[*child].kind, NodeKind::Literal(literal) if literal == name)
+        })
+    }
+
+    fn can_use(&self, node: usize, source: &CommandSource) -> bool {
+        self.nodes[node]
+            .requirement
+            .as_ref()
+            .is_none_or(|requirement| requirement(source))
+    }
+
+    /// Gets the children a source may use after a node, following its redirect.
+    pub fn get_children(&self, node: usize, source: &CommandSource) -> Vec<usize> {
+        let node = self.nodes[node].redirect.unwrap_or(node);
+        self.nodes[node]
+            .children
+            .iter()
+            .copied()
+            .filter(|child| self.can_use(*child, source))
+            .collect()
+    }
+
+    /// Parses and runs a command, without its leading slash.
+    pub fn execute(&self, source: &CommandSource, input: &str) -> CommandResult {
+        let mut reader = StringReader::new(input);
+        let mut arguments = HashMap::new();
+        let node = self.parse_children(Self::ROOT, source, &mut reader, &mut arguments)?;
+        let executor = self.nodes[node]
+            .executor
+            .as_ref()
+            .ok_or_else(|| CommandError::syntax("Unknown or incomplete command", &reader))?;
+        executor(&CommandContext {
+            source,
+            input,
+            arguments,
+        })
+    }
+
+    /// Parses the children of a node at the cursor, until the end of the input.
+    ///
+    /// # Returns
+    /// The last node of the command.
+    fn parse_children(
+        &self,
+        node: usize,
+        source: &CommandSource,
+        reader: &mut StringReader,
+        arguments: &mut HashMap<String, ArgumentValue>,
+    ) -> Result<usize, CommandError> {
+        let start = reader.get_cursor();
+        let children = self.get_children(node, source);
+        let word = &reader.get_remaining()[..reader
+            .get_remaining()
+            .find(' ')
+            .unwrap_or(reader.get_remaining().len())];
+        let literals: Vec<usize> = children
+            .iter()
+            .copied()
+            .filter(
+                |child| matches!(&self.nodes[*child].kind, NodeKind::Literal(name) if name == word),
+            )
+            .collect();
+        let candidates = if literals.is_empty() {
+            children
+                .into_iter()
+                .filter(|child| matches!(self.nodes[*child].kind, NodeKind::Argument { .. }))
+                .collect()
+        } else {
+            literals
+        };
+        let mut error: Option<CommandError> = None;
+        for child in candidates {
+            reader.set_cursor(start);
+            let mut parsed = arguments.clone();
+            let result = match &self.nodes[child].kind {
+                NodeKind::Argument {
+                    name,
+                    argument_type,
+                } => argument_type.parse(reader).map(|value| {
+                    parsed.insert(name.clone(), value);
+                }),
+                _ => {
+                    reader.set_cursor(start + word.len());
+                    Ok(())
+                }
+            };
+            let result = result.and_then(|_| match reader.peek() {
+                None => Ok(child),
+                Some(' ') => {
+                    reader.skip();
+                    self.parse_children(child, source, reader, &mut parsed)
+                }
+                Some(_) => Err(CommandError::syntax(
+                    "Expected whitespace to end one argument, but found trailing data",
+                    reader,
+                )),
+            });
+            match result {
+                Ok(node) => {
+                    *arguments = parsed;
+                    return Ok(node);
+                }
+                Err(err) => {
+                    if error
+                        .as_ref()
+                        .is_none_or(|error| cursor(&err) > cursor(error))
+                    {
+                        error = Some(err);
+                    }
+                }
+            }
+        }
+        // The word that matched nothing is shown before the error's position.
+        reader.set_cursor(start + word.len());
+        Err(error.unwrap_or_else(|| {
+            if node == Self::ROOT {
+                CommandError::syntax("Unknown command", reader)
+            } else {
+                CommandError::syntax("Incorrect argument for command", reader)
+            }
+        }))
+    }
+
+    /// Lists the completions of the last word of a command being typed.
+    ///
+    /// # Returns
+    /// The position in the input the completions replace, and the completions.
+    pub fn suggest(&self, source: &CommandSource, input: &str) -> (usize, Vec<String>) {
+        let mut suggestions = (0, Vec::new());
+        self.suggest_children(Self::ROOT, source, input, 0, &mut suggestions);
+        suggestions
+    }
+
+    fn suggest_children(
+        &self,
+        node: usize,
+        source: &CommandSource,
+        input: &str,
+        start: usize,
+        suggestions: &mut (usize, Vec<String>),
+    ) {
+        let remaining = &input[start..];
+        for child in self.get_children(node, source) {
+            let mut reader = StringReader::new(input);
+            reader.set_cursor(start);
+            let (parsed, greedy) = match &self.nodes[child].kind {
+                NodeKind::Argument { argument_type, .. } => (
+                    argument_type.parse(&mut reader).is_ok(),
+                    matches!(
+                        argument_type,
+                        ArgumentType::Message | ArgumentType::String(StringKind::GreedyPhrase)
+                    ),
+                ),
+                NodeKind::Literal(name) => {
+                    let matched = remaining.starts_with(name.as_str())
+                        && matches!(remaining[name.len()..].chars().next(), None | Some(' '));
+                    if matched {
+                        reader.set_cursor(start + name.len());
+                    }
+                    (matched, false)
+                }
+                NodeKind::Root => (false, false),
+            };
+            if parsed && reader.peek() == Some(' ') {
+                self.suggest_children(child, source, input, reader.get_cursor() + 1, suggestions);
+            } else if greedy || !remaining.contains(' ') {
+                let typed = remaining.to_lowercase();
+                let completions: Vec<String> = self
+                    .get_completions(child, source)
+                    .into_iter()
+                    .filter(|completion| completion.to_lowercase().starts_with(&typed))
+                    .collect();
+                if completions.is_empty() || start < suggestions.0 {
+                    continue;
+                }
+                if start > suggestions.0 {
+                    *suggestions = (start, Vec::new());
+                }
+                suggestions.1.extend(completions);
+            }
+        }
+    }
+
+    fn get_completions(&self, node: usize, source: &CommandSource) -> Vec<String> {
+        let node = &self.nodes[node];
+        match (&node.kind, &node.suggestions) {
+            (NodeKind::Literal(name), _) => vec![name.clone()],
+            (_, Some(suggestions)) => suggestions(source),
+            (NodeKind::Argument { argument_type, .. }, None) => argument_type.suggest(),
+            (NodeKind::Root, None) => Vec::new(),
+        }
+    }
+
+    /// Describes how to use a node, such as `gamemode <gamemode> [<target>]`.
+    pub fn get_usage(&self, node: usize, source: &CommandSource) -> String {
+        let name = self.get_name(node);
+        let children = self.get_children(node, source);
+        let usage = match children.as_slice() {
+            [] => return name,
+            [child] => self.get_usage(*child, source),
+            children => format!(
+                "({})",
+                children
+                    .iter()
+                    .map(|child| self.get_name(*child))
+                    .collect::<Vec<String>>()
+                    .join("|")
+            ),
+        };
+        if self.nodes[node].executor.is_some() {
+            format!("{} [{}]", name, usage)
+        } else {
+            format!("{} {}", name, usage)
+        }
+    }
+
+    fn get_name(&self, node: usize) -> String {
+        match &self.nodes[node].kind {
+            NodeKind::Root => String::new(),
+            NodeKind::Literal(name) => name.clone(),
+            NodeKind::Argument { name, .. } => format!("<{}>", name),
+        }
+    }
+
+    /// Gets the nodes a source may use, as sent in the commands packet.
+    ///
+    /// # Returns
+    /// The nodes, the root being the first one.
+    pub fn get_nodes(&self, source: &CommandSource) -> Vec<CommandNodeData> {
+        let mut indices: HashMap<usize, usize> = HashMap::new();
+        let mut order = vec![Self::ROOT];
+        indices.insert(Self::ROOT, 0);
+        let mut i = 0;
+        while i < order.len() {
+            let node = &self.nodes[order[i]];
+            let next = node.children.iter().chain(node.redirect.iter());
+            for child in next {
+                if !indices.contains_key(child) && self.can_use(*child, source) {
+                    indices.insert(*child, order.len());
+                    order.push(*child);
+                }
+            }
+            i += 1;
+        }
+        order
+            .iter()
+            .map(|index| {
+                let node = &self.nodes[*index];
+                let (name, argument_type) = match &node.kind {
+                    NodeKind::Root => (None, None),
+                    NodeKind::Literal(name) => (Some(name.clone()), None),
+                    NodeKind::Argument {
+                        name,
+                        argument_type,
+                    } => (Some(name.clone()), Some(argument_type.clone())),
+                };
+                CommandNodeData {
+                    node_type: match node.kind {
+                        NodeKind::Root => 0,
+                        NodeKind::Literal(_) => 1,
+                        NodeKind::Argument { .. } => 2,
+                    },
+                    executable: node.executor.is_some(),
+                    children: node
+                        .children
+                        .iter()
+                        .filter_map(|child| indices.get(child).map(|child| *child as i32))
+                        .collect(),
+                    redirect: node
+                        .redirect
+                        .and_then(|redirect| indices.get(&redirect).map(|index| *index as i32)),
+                    name,
+                    argument_type,
+                    ask_server: node.suggestions.is_some(),
+                }
+            })
+            .collect()
+    }
+}
+
+#[inline]
+fn cursor(error: &CommandError) -> usize {
+    error.context.as_ref().map_or(0, |(_, cursor)| *cursor)
+}
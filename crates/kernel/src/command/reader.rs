@@ -0,0 +1,161 @@
+use crate::command::CommandError;
+
+/// A cursor over the input of a command, reading its words and values.
+pub struct StringReader<'a> {
+    input: &'a str,
+    cursor: usize,
+}
+
+impl<'a> StringReader<'a> {
+    pub fn new(input: &'a str) -> StringReader<'a> {
+        StringReader { input, cursor: 0 }
+    }
+
+    pub fn get_input(&self) -> &'a str {
+        self.input
+    }
+
+    /// Gets the position of the cursor, in bytes.
+    pub fn get_cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn set_cursor(&mut self, cursor: usize) {
+        self.cursor = cursor;
+    }
+
+    pub fn get_remaining(&self) -> &'a str {
+        &self.input[self.cursor..]
+    }
+
+    pub fn can_read(&self) -> bool {
+        self.cursor < self.input.len()
+    }
+
+    pub fn peek(&self) -> Option<char> {
+        self.get_remaining().chars().next()
+    }
+
+    pub fn skip(&mut self) {
+        if let Some(c) = self.peek() {
+            self.cursor += c.len_utf8();
+        }
+    }
+
+    /// Reads the rest of the input.
+    pub fn read_remaining(&mut self) -> &'a str {
+        let remaining = self.get_remaining();
+        self.cursor = self.input.len();
+        remaining
+    }
+
+    /// Reads characters while they match the predicate.
+    pub fn read_while(&mut self, predicate: impl Fn(char) -> bool) -> &'a str {
+        let start = self.cursor;
+        while let Some(c) = self.peek() {
+            if !predicate(c) {
+                break;
+            }
+            self.cursor += c.len_utf8();
+        }
+        &self.input[start..self.cursor]
+    }
+
+    /// Reads a word of the characters allowed in unquoted strings.
+    pub fn read_unquoted_string(&mut self) -> &'a str {
+        self.read_while(is_allowed_in_unquoted_string)
+    }
+
+    /// Reads a string between double or single quotes, with backslash escapes.
+    pub fn read_quoted_string(&mut self) -> Result<String, CommandError> {
+        let quote = match self.peek() {
+            Some(c @ ('"' | '\'')) => c,
+            _ => {
+                return Err(CommandError::syntax(
+                    "Expected quote to start a string",
+                    self,
+                ))
+            }
+        };
+        self.skip();
+        let mut value = String::new();
+        let mut escaped = false;
+        while let Some(c) = self.peek() {
+            self.skip();
+            if escaped {
+                if c != quote && c != '\\' {
+                    return Err(CommandError::syntax(
+                        format!("Invalid escape sequence '{}' in quoted string", c),
+                        self,
+                    ));
+                }
+                value.push(c);
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == quote {
+                return Ok(value);
+            } else {
+                value.push(c);
+            }
+        }
+        Err(CommandError::syntax("Unclosed quoted string", self))
+    }
+
+    /// Reads a quoted string, or an unquoted word.
+    pub fn read_string(&mut self) -> Result<String, CommandError> {
+        match self.peek() {
+            Some('"' | '\'') => self.read_quoted_string(),
+            _ => Ok(self.read_unquoted_string().to_string()),
+        }
+    }
+
+    fn read_number<T: std::str::FromStr>(&mut self, kind: &str) -> Result<T, CommandError> {
+        let start = self.cursor;
+        let number = self.read_while(|c| c.is_ascii_digit() || c == '.' || c == '-');
+        if number.is_empty() {
+            return Err(CommandError::syntax(format!("Expected {}", kind), self));
+        }
+        number.parse().map_err(|_| {
+            self.cursor = start;
+            CommandError::syntax(format!("Invalid {} '{}'", kind, number), self)
+        })
+    }
+
+    pub fn read_int(&mut self) -> Result<i32, CommandError> {
+        self.read_number("integer")
+    }
+
+    pub fn read_long(&mut self) -> Result<i64, CommandError> {
+        self.read_number("long")
+    }
+
+    pub fn read_float(&mut self) -> Result<f32, CommandError> {
+        self.read_number("float")
+    }
+
+    pub fn read_double(&mut self) -> Result<f64, CommandError> {
+        self.read_number("double")
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool, CommandError> {
+        let start = self.cursor;
+        match self.read_unquoted_string() {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            "" => Err(CommandError::syntax("Expected bool", self)),
+            value => {
+                self.cursor = start;
+                Err(CommandError::syntax(
+                    format!("Invalid bool, expected true or false but found '{}'", value),
+                    self,
+                ))
+            }
+        }
+    }
+}
+
+#[inline]
+pub fn is_allowed_in_unquoted_string(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '+')
+}
@@ -1,4 +1,3 @@
-use sha2::{Digest, Sha256};
 use std::sync::LazyLock;
 use toml::{toml, Value};
 
@@ -7,9 +6,22 @@ static DEFAULT: LazyLock<Value> = LazyLock::new(|| {
         max-players = 20
         view-distance = 12
         simulation-distance = 16
+        entity-tracking-range = 48
         seed = 0
         port = 25565
+        online-mode = true
+        session-server-url = "https://sessionserver.mojang.com"
+        op-permission-level = 4
+        enforce-secure-chat = true
+        player-certificate-key = ""
+        player-info-forwarding-mode = "none"
+        forwarding-secret = ""
+        network-compression-threshold = 256
         worldgen-implementation = "super_flat"
+        level-name = "world"
+        autosave-interval = 6000
+        motd = "A Spot Server"
+        server-icon = "server-icon.png"
     })
 });
 static TOML: LazyLock<Value> = LazyLock::new(|| {
@@ -26,12 +38,80 @@ pub static WORLDGEN_IMPLEMENTATION: LazyLock<&str> = LazyLock::new(|| {
         .as_str()
         .unwrap()
 });
+/// The directory of the world, holding the region files of every dimension.
+pub static LEVEL_NAME: LazyLock<&str> = LazyLock::new(|| {
+    TOML.get("level-name")
+        .unwrap_or_else(|| DEFAULT.get("level-name").unwrap())
+        .as_str()
+        .unwrap()
+});
+/// The ticks between two saves of the changed chunks, 0 turning the autosave off.
+pub static AUTOSAVE_INTERVAL: LazyLock<u64> = LazyLock::new(|| {
+    TOML.get("autosave-interval")
+        .unwrap_or_else(|| DEFAULT.get("autosave-interval").unwrap())
+        .as_integer()
+        .unwrap()
+        .max(0) as u64
+});
 pub static PORT: LazyLock<i32> = LazyLock::new(|| {
     TOML.get("port")
         .unwrap_or_else(|| DEFAULT.get("port").unwrap())
         .as_integer()
         .unwrap() as i32
 });
+pub static ONLINE_MODE: LazyLock<bool> = LazyLock::new(|| {
+    TOML.get("online-mode")
+        .unwrap_or_else(|| DEFAULT.get("online-mode").unwrap())
+        .as_bool()
+        .unwrap()
+});
+pub static SESSION_SERVER_URL: LazyLock<&str> = LazyLock::new(|| {
+    TOML.get("session-server-url")
+        .unwrap_or_else(|| DEFAULT.get("session-server-url").unwrap())
+        .as_str()
+        .unwrap()
+});
+/// The permission level given to the players made operators by `op`.
+pub static OP_PERMISSION_LEVEL: LazyLock<u8> = LazyLock::new(|| {
+    TOML.get("op-permission-level")
+        .unwrap_or_else(|| DEFAULT.get("op-permission-level").unwrap())
+        .as_integer()
+        .unwrap()
+        .clamp(0, 4) as u8
+});
+/// Whether chat messages must be signed by the player's profile key.
+pub static ENFORCE_SECURE_CHAT: LazyLock<bool> = LazyLock::new(|| {
+    TOML.get("enforce-secure-chat")
+        .unwrap_or_else(|| DEFAULT.get("enforce-secure-chat").unwrap())
+        .as_bool()
+        .unwrap()
+});
+/// The base64 DER public key which signs the profile keys of the players,
+/// as listed in the `playerCertificateKeys` of the Mojang services, fetched from them when empty.
+pub static PLAYER_CERTIFICATE_KEY: LazyLock<&str> = LazyLock::new(|| {
+    TOML.get("player-certificate-key")
+        .unwrap_or_else(|| DEFAULT.get("player-certificate-key").unwrap())
+        .as_str()
+        .unwrap()
+});
+pub static PLAYER_INFO_FORWARDING_MODE: LazyLock<&str> = LazyLock::new(|| {
+    TOML.get("player-info-forwarding-mode")
+        .unwrap_or_else(|| DEFAULT.get("player-info-forwarding-mode").unwrap())
+        .as_str()
+        .unwrap()
+});
+pub static FORWARDING_SECRET: LazyLock<&str> = LazyLock::new(|| {
+    TOML.get("forwarding-secret")
+        .unwrap_or_else(|| DEFAULT.get("forwarding-secret").unwrap())
+        .as_str()
+        .unwrap()
+});
+pub static NETWORK_COMPRESSION_THRESHOLD: LazyLock<i32> = LazyLock::new(|| {
+    TOML.get("network-compression-threshold")
+        .unwrap_or_else(|| DEFAULT.get("network-compression-threshold").unwrap())
+        .as_integer()
+        .unwrap() as i32
+});
 pub static MAX_PLAYERS: LazyLock<i32> = LazyLock::new(|| {
     TOML.get("max-players")
         .unwrap_or_else(|| DEFAULT.get("max-players").unwrap())
@@ -50,16 +130,29 @@ pub static SIMULATION_DISTANCE: LazyLock<i32> = LazyLock::new(|| {
         .as_integer()
         .unwrap() as i32
 });
+/// How far away, in blocks, players see other entities.
+pub static ENTITY_TRACKING_RANGE: LazyLock<i32> = LazyLock::new(|| {
+    TOML.get("entity-tracking-range")
+        .unwrap_or_else(|| DEFAULT.get("entity-tracking-range").unwrap())
+        .as_integer()
+        .unwrap() as i32
+});
+/// The seed of new worlds, the seed in `level.dat` is used for existing ones.
 pub static SEED: LazyLock<i64> = LazyLock::new(|| {
     TOML.get("seed")
         .unwrap_or_else(|| DEFAULT.get("seed").unwrap())
         .as_integer()
         .unwrap()
 });
-pub static HASHED_SEED: LazyLock<i64> = LazyLock::new(|| {
-    let mut sha = Sha256::new();
-    sha.update(SEED.to_be_bytes());
-    let hash_result = sha.finalize();
-    let bytes_array: [u8; 8] = hash_result[..8].to_vec().try_into().unwrap();
-    i64::from_be_bytes(bytes_array)
+pub static MOTD: LazyLock<&str> = LazyLock::new(|| {
+    TOML.get("motd")
+        .unwrap_or_else(|| DEFAULT.get("motd").unwrap())
+        .as_str()
+        .unwrap()
+});
+pub static SERVER_ICON: LazyLock<&str> = LazyLock::new(|| {
+    TOML.get("server-icon")
+        .unwrap_or_else(|| DEFAULT.get("server-icon").unwrap())
+        .as_str()
+        .unwrap()
 });
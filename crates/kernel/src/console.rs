@@ -0,0 +1,124 @@
+use crate::command::{CommandSource, DISPATCHER};
+use crate::server;
+use parking_lot::Mutex;
+use rustyline::error::ReadlineError;
+use rustyline::{DefaultEditor, ExternalPrinter};
+use std::io::{stdin, stdout, IsTerminal, Write};
+use std::thread;
+use tracing::warn;
+use tracing_subscriber::fmt::MakeWriter;
+
+const PROMPT: &str = "> ";
+
+/// Prints above the prompt while a command is being typed in the terminal.
+static PRINTER: Mutex<Option<Box<dyn ExternalPrinter + Send>>> = Mutex::new(None);
+
+/// Starts reading the commands of the console from the standard input, on its own thread.
+///
+/// Lines can be edited and recalled when the input is a terminal,
+/// otherwise they are read as they come.
+pub fn start() {
+    thread::Builder::new()
+        .name("Console".to_string())
+        .spawn(|| {
+            if stdin().is_terminal() {
+                if let Err(err) = read_terminal() {
+                    *PRINTER.lock() = None;
+                    warn!("Error in reading commands from the terminal: {:?}", err);
+                }
+            } else {
+                read_lines();
+            }
+        })
+        .expect("Error in starting the console thread.");
+}
+
+fn read_terminal() -> rustyline::Result<()> {
+    let mut editor = DefaultEditor::new()?;
+    *PRINTER.lock() = Some(Box::new(editor.create_external_printer()?));
+    loop {
+        match editor.readline(PROMPT) {
+            Ok(line) => {
+                if !line.trim().is_empty() {
+                    editor.add_history_entry(line.as_str())?;
+                }
+                run(&line);
+            }
+            Err(ReadlineError::Interrupted | ReadlineError::Eof) => server::stop(),
+            Err(err) => return Err(err),
+        }
+        // The terminal must not be left in raw mode when the process exits.
+        if server::is_stopping() {
+            *PRINTER.lock() = None;
+            return Ok(());
+        }
+    }
+}
+
+fn read_lines() {
+    for line in stdin().lines() {
+        match line {
+            Ok(line) => run(&line),
+            Err(err) => {
+                warn!("Error in reading commands from stdin: {:?}", err);
+                return;
+            }
+        }
+    }
+}
+
+/// Runs a line of the console as a command, with or without its leading slash.
+fn run(line: &str) {
+    let line = line.trim();
+    let command = line.strip_prefix('/').unwrap_or(line);
+    if command.is_empty() {
+        return;
+    }
+    let source = CommandSource::Console;
+    let result = DISPATCHER.read().execute(&source, command);
+    if let Err(err) = result {
+        source.send_error(err.to_string());
+    }
+}
+
+/// Writes the log to the standard output, or above the prompt of the console.
+pub struct ConsoleWriter;
+
+impl<'a> MakeWriter<'a> for ConsoleWriter {
+    type Writer = LogWriter;
+
+    fn make_writer(&'a self) -> LogWriter {
+        LogWriter(Vec::new())
+    }
+}
+
+/// Buffers a log event, printed at once when dropped.
+pub struct LogWriter(Vec<u8>);
+
+impl Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for LogWriter {
+    fn drop(&mut self) {
+        if self.0.is_empty() {
+            return;
+        }
+        if let Some(printer) = PRINTER.lock().as_mut() {
+            if printer
+                .print(String::from_utf8_lossy(&self.0).into_owned())
+                .is_ok()
+            {
+                return;
+            }
+        }
+        let _ = stdout().write_all(&self.0);
+    }
+}
@@ -1,3 +1,5 @@
+use crate::entity::metadata::{EntityMetadata, Pose, CUSTOM_NAME, FLAGS, POSE};
+use crate::util::text::TextComponent;
 use crate::world::dimension::Dimension;
 use crate::WORLD;
 use downcast_rs::{impl_downcast, DowncastSync};
@@ -5,12 +7,16 @@ use std::sync::Arc;
 use uuid::Uuid;
 
 pub mod entity_manager;
+pub mod metadata;
 pub mod player;
+pub mod tracker;
 
 pub trait Entity: Send + Sync + DowncastSync {
     fn get_type(&self) -> u32;
     fn get_data_mut(&mut self) -> &mut EntityData;
     fn get_data(&self) -> &EntityData;
+    fn get_metadata_mut(&mut self) -> &mut EntityMetadata;
+    fn get_metadata(&self) -> &EntityMetadata;
     fn get_position(&self) -> (f64, f64, f64) {
         self.get_data().pos
     }
@@ -58,6 +64,29 @@ pub trait Entity: Send + Sync + DowncastSync {
         dat.pitch = pitch;
         dat.on_ground = on_ground;
     }
+
+    fn get_head_yaw(&self) -> f32 {
+        self.get_data().head_yaw
+    }
+
+    fn set_head_yaw(&mut self, head_yaw: f32) {
+        self.get_data_mut().head_yaw = head_yaw;
+    }
+
+    /// Sets one of the [`flags`](metadata::flags) of the entity.
+    fn set_flag(&mut self, flag: u8, value: bool) {
+        let flags = self.get_metadata().get(FLAGS).unwrap_or(0) as u8;
+        let flags = if value { flags | flag } else { flags & !flag };
+        self.get_metadata_mut().set(FLAGS, flags as i8);
+    }
+
+    fn set_pose(&mut self, pose: Pose) {
+        self.get_metadata_mut().set(POSE, pose);
+    }
+
+    fn set_custom_name(&mut self, name: Option<TextComponent>) {
+        self.get_metadata_mut().set(CUSTOM_NAME, name);
+    }
 }
 impl_downcast!(sync Entity);
 
@@ -78,6 +107,7 @@ pub struct EntityData {
     pub on_ground: bool,
     pub yaw: f32,
     pub pitch: f32,
+    pub head_yaw: f32,
 }
 impl EntityData {
     pub fn new(entity_id: i32, dimension: usize, pos: (f64, f64, f64)) -> EntityData {
@@ -91,6 +121,7 @@ impl EntityData {
             on_ground: false,
             yaw: 0.0,
             pitch: 0.0,
+            head_yaw: 0.0,
         }
     }
 
@@ -110,13 +141,14 @@ impl EntityData {
             on_ground: false,
             yaw: 0.0,
             pitch: 0.0,
+            head_yaw: 0.0,
         }
     }
 }
 
 #[macro_export]
 macro_rules! impl_entity {
-    ($class:ty ,$field_name:tt, $id:expr) => {
+    ($class:ty ,$field_name:tt, $metadata:tt, $id:expr) => {
         impl PartialEq<dyn Entity> for $class {
             fn eq(&self, other: &dyn Entity) -> bool {
                 self.get_eid() == other.get_eid()
@@ -134,6 +166,14 @@ macro_rules! impl_entity {
             fn get_data(&self) -> &EntityData {
                 &self.$field_name
             }
+
+            fn get_metadata_mut(&mut self) -> &mut EntityMetadata {
+                &mut self.$metadata
+            }
+
+            fn get_metadata(&self) -> &EntityMetadata {
+                &self.$metadata
+            }
         }
     };
 }
@@ -147,27 +187,14 @@ macro_rules! impl_living_entity {
             }
 
             fn set_health(&mut self, health: f32) -> bool {
-                if health > self.$max_health as f32 {
-                    self.$health = self.$max_health as f32;
-                    return false;
-                }
-                if health <= 0.0 {
-                    self.$health = 0.0;
-                    return true;
-                }
+                let health = health.clamp(0.0, self.$max_health as f32);
                 self.$health = health;
-                false
+                self.get_metadata_mut().set(HEALTH, health);
+                self.$health <= 0.0
             }
 
             fn decrease_health(&mut self, amount: f32) -> bool {
-                self.$health -= amount;
-                if self.$health <= 0.0 {
-                    return true;
-                }
-                if self.$health > self.$max_health as f32 {
-                    self.$health = self.$max_health as f32;
-                }
-                false
+                self.set_health(self.$health - amount)
             }
         }
     };
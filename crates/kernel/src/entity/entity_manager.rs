@@ -31,6 +31,15 @@ impl EntityManager {
         self.entities.remove(&eid)
     }
 
+    /// Calls `f` with every entity and its id, locking one entity at a time.
+    ///
+    /// Unlike [`EntityManager::lookup`], the entities are visited in place without being copied.
+    pub fn for_each<F: FnMut(i32, &dyn Entity)>(&self, mut f: F) {
+        self.entities
+            .iter()
+            .for_each(|e| f(*e.key(), &*e.value().lock()));
+    }
+
     pub fn lookup(&self) -> EntityLookup {
         EntityLookup {
             entities: self.entities.clone(),
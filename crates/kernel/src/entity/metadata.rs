@@ -0,0 +1,219 @@
+use crate::util::io::WriteExt;
+use crate::util::text::TextComponent;
+use std::marker::PhantomData;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// A value of the entity metadata, tagged with the protocol's serializer type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetadataValue {
+    Byte(i8),
+    VarInt(i32),
+    VarLong(i64),
+    Float(f32),
+    String(String),
+    TextComponent(TextComponent),
+    OptionalTextComponent(Option<TextComponent>),
+    Boolean(bool),
+    Pose(Pose),
+}
+
+impl MetadataValue {
+    /// Gets the id of the serializer type sent before the value.
+    pub fn get_type(&self) -> i32 {
+        match self {
+            MetadataValue::Byte(_) => 0,
+            MetadataValue::VarInt(_) => 1,
+            MetadataValue::VarLong(_) => 2,
+            MetadataValue::Float(_) => 3,
+            MetadataValue::String(_) => 4,
+            MetadataValue::TextComponent(_) => 5,
+            MetadataValue::OptionalTextComponent(_) => 6,
+            MetadataValue::Boolean(_) => 8,
+            MetadataValue::Pose(_) => 21,
+        }
+    }
+
+    pub async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> anyhow::Result<()> {
+        buf.write_var_int(self.get_type()).await?;
+        match self {
+            MetadataValue::Byte(value) => buf.write_i8(*value).await?,
+            MetadataValue::VarInt(value) => buf.write_var_int(*value).await?,
+            MetadataValue::VarLong(value) => buf.write_var_long(*value).await?,
+            MetadataValue::Float(value) => buf.write_f32(*value).await?,
+            MetadataValue::String(value) => buf.write_str(value).await?,
+            MetadataValue::TextComponent(value) => buf.write_all(&value.to_network_nbt()).await?,
+            MetadataValue::OptionalTextComponent(value) => match value {
+                Some(value) => {
+                    buf.write_bool(true).await?;
+                    buf.write_all(&value.to_network_nbt()).await?;
+                }
+                None => buf.write_bool(false).await?,
+            },
+            MetadataValue::Boolean(value) => buf.write_bool(*value).await?,
+            MetadataValue::Pose(value) => buf.write_var_int(*value as i32).await?,
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pose {
+    Standing,
+    FallFlying,
+    Sleeping,
+    Swimming,
+    SpinAttack,
+    Sneaking,
+    LongJumping,
+    Dying,
+    Croaking,
+    UsingTongue,
+    Sitting,
+    Roaring,
+    Sniffing,
+    Emerging,
+    Digging,
+    Sliding,
+    Shooting,
+    Inhaling,
+}
+
+/// A Rust type stored in the metadata as one of the serializer types.
+pub trait MetadataType: Sized {
+    fn into_value(self) -> MetadataValue;
+    fn from_value(value: &MetadataValue) -> Option<Self>;
+}
+
+macro_rules! metadata_type {
+    ($type:ty, $variant:ident) => {
+        impl MetadataType for $type {
+            fn into_value(self) -> MetadataValue {
+                MetadataValue::$variant(self)
+            }
+
+            fn from_value(value: &MetadataValue) -> Option<Self> {
+                match value {
+                    MetadataValue::$variant(value) => Some(value.clone()),
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+metadata_type!(i8, Byte);
+metadata_type!(i32, VarInt);
+metadata_type!(i64, VarLong);
+metadata_type!(f32, Float);
+metadata_type!(String, String);
+metadata_type!(TextComponent, TextComponent);
+metadata_type!(Option<TextComponent>, OptionalTextComponent);
+metadata_type!(bool, Boolean);
+metadata_type!(Pose, Pose);
+
+/// A field of the entity metadata, its index and the type of its value.
+pub struct MetadataField<T: MetadataType> {
+    pub index: u8,
+    _type: PhantomData<T>,
+}
+
+impl<T: MetadataType> MetadataField<T> {
+    pub const fn new(index: u8) -> MetadataField<T> {
+        MetadataField {
+            index,
+            _type: PhantomData,
+        }
+    }
+}
+
+// Fields of all entities.
+pub const FLAGS: MetadataField<i8> = MetadataField::new(0);
+pub const AIR_SUPPLY: MetadataField<i32> = MetadataField::new(1);
+pub const CUSTOM_NAME: MetadataField<Option<TextComponent>> = MetadataField::new(2);
+pub const CUSTOM_NAME_VISIBLE: MetadataField<bool> = MetadataField::new(3);
+pub const SILENT: MetadataField<bool> = MetadataField::new(4);
+pub const NO_GRAVITY: MetadataField<bool> = MetadataField::new(5);
+pub const POSE: MetadataField<Pose> = MetadataField::new(6);
+pub const TICKS_FROZEN: MetadataField<i32> = MetadataField::new(7);
+// Fields of living entities.
+pub const LIVING_FLAGS: MetadataField<i8> = MetadataField::new(8);
+pub const HEALTH: MetadataField<f32> = MetadataField::new(9);
+pub const ARROW_COUNT: MetadataField<i32> = MetadataField::new(12);
+pub const STINGER_COUNT: MetadataField<i32> = MetadataField::new(13);
+// Fields of players.
+pub const ADDITIONAL_HEARTS: MetadataField<f32> = MetadataField::new(15);
+pub const SCORE: MetadataField<i32> = MetadataField::new(16);
+pub const SKIN_PARTS: MetadataField<i8> = MetadataField::new(17);
+pub const MAIN_HAND: MetadataField<i8> = MetadataField::new(18);
+
+/// The bits of [`FLAGS`].
+pub mod flags {
+    pub const ON_FIRE: u8 = 0x01;
+    pub const CROUCHING: u8 = 0x02;
+    pub const SPRINTING: u8 = 0x08;
+    pub const SWIMMING: u8 = 0x10;
+    pub const INVISIBLE: u8 = 0x20;
+    pub const GLOWING: u8 = 0x40;
+    pub const FALL_FLYING: u8 = 0x80;
+}
+
+/// The metadata of an entity, the values its clients need to render it.
+///
+/// Only the fields that were set are stored, the others keep the client's defaults.
+/// Every change is numbered, so each viewer can be sent the changes since it last synced.
+#[derive(Debug, Clone, Default)]
+pub struct EntityMetadata {
+    entries: Vec<(u8, MetadataValue, u64)>,
+    version: u64,
+}
+
+impl EntityMetadata {
+    pub fn new() -> EntityMetadata {
+        EntityMetadata {
+            entries: Vec::new(),
+            version: 0,
+        }
+    }
+
+    pub fn get<T: MetadataType>(&self, field: MetadataField<T>) -> Option<T> {
+        self.entries
+            .iter()
+            .find(|(index, _, _)| *index == field.index)
+            .and_then(|(_, value, _)| T::from_value(value))
+    }
+
+    /// Sets a field, marking it dirty if the value changed.
+    pub fn set<T: MetadataType>(&mut self, field: MetadataField<T>, value: T) {
+        let value = value.into_value();
+        match self
+            .entries
+            .iter_mut()
+            .find(|(index, _, _)| *index == field.index)
+        {
+            Some(entry) if entry.1 == value => {}
+            Some(entry) => {
+                self.version += 1;
+                entry.1 = value;
+                entry.2 = self.version;
+            }
+            None => {
+                self.version += 1;
+                self.entries.push((field.index, value, self.version));
+            }
+        }
+    }
+
+    /// Gets the number of the last change.
+    pub fn get_version(&self) -> u64 {
+        self.version
+    }
+
+    /// Gets the fields changed after a version, or all of them for version 0.
+    pub fn changes_since(&self, version: u64) -> Vec<(u8, MetadataValue)> {
+        self.entries
+            .iter()
+            .filter(|(_, _, changed)| *changed > version)
+            .map(|(index, value, _)| (*index, value.clone()))
+            .collect()
+    }
+}
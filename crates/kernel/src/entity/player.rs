@@ -1,23 +1,61 @@
 use crate::config::VIEW_DISTANCE;
+use crate::entity::metadata::{EntityMetadata, HEALTH, MAIN_HAND, SKIN_PARTS};
 use crate::entity::{Entity, EntityData, LivingEntity};
+use crate::item::inventory::Inventory;
+use crate::network::auth::ProfileProperty;
+use crate::network::chat_session::{ChatSession, LastSeenMessages};
+use crate::network::connection::ChatMode;
+use crate::network::outbound::ConnectionHandle;
 use crate::registry::protocol_id::get_protocol_id;
 use crate::util::arc_channel::MultipleReceiver;
+use crate::util::text::TextComponent;
 use crate::world::chunk::{Chunk, ChunkUpdate};
 use crate::{impl_entity, impl_living_entity};
+use simdnbt::owned::NbtCompound;
 use std::sync::Arc;
-use tokio::sync::mpsc::UnboundedSender;
 use uuid::Uuid;
 
 pub struct Player {
     pub health: f32,
     pub max_health: u16,
     pub entity: EntityData,
+    pub metadata: EntityMetadata,
+    pub username: String,
+    /// The properties of the player's game profile, such as its skin.
+    pub properties: Vec<ProfileProperty>,
+    /// The name shown in the tab list instead of the username.
+    pub display_name: Option<TextComponent>,
+    /// Which messages the player's client accepts.
+    pub chat_mode: ChatMode,
+    /// The session the player signs its chat messages in, once its client sent one.
+    pub chat_session: Option<ChatSession>,
+    /// The signed messages sent to the player's client.
+    pub last_seen: LastSeenMessages,
     pub game_mode: u8,
+    /// The permission level of the player's commands, from 0 to 4, given to the operators in `ops.json`.
+    pub permission_level: u8,
     pub previous_game_mode: i8,
     pub death_location: Option<(String, i32, i32, i32)>,
+    /// The main inventory, the armor and the offhand, in the slot numbering of the saved data.
+    pub inventory: Inventory,
+    pub ender_chest: Inventory,
+    /// The slot of the hotbar held in the main hand, from 0 to 8.
+    pub selected_slot: i32,
+    pub experience_level: i32,
+    /// The progress towards the next level, from 0 to 1.
+    pub experience_progress: f32,
+    pub total_experience: i32,
+    pub abilities: PlayerAbilities,
+    /// The saved data of the player, keeping the fields the server does not use.
+    pub(crate) saved_data: NbtCompound,
     pub teleport_id: Option<i32>,
+    /// The latency of the player's connection in milliseconds.
+    pub latency: i32,
+    /// The position of the chunk the player's chunks are loaded around.
+    pub center_chunk: (i32, i32),
     pub chunks: Vec<Arc<Chunk>>,
-    pub tx: UnboundedSender<PlayerUpdate>,
+    /// The handle to send packets to the player's client.
+    pub connection: ConnectionHandle,
     pub recv: MultipleReceiver<ChunkUpdate>,
 }
 
@@ -25,48 +63,143 @@ impl Player {
     pub fn new(
         entity_id: i32,
         dimension: usize,
-        tx: UnboundedSender<PlayerUpdate>,
+        connection: ConnectionHandle,
         pos: (f64, f64, f64),
         uuid: Uuid,
     ) -> Player {
+        let mut metadata = EntityMetadata::new();
+        metadata.set(HEALTH, 20.0);
         Player {
             health: 20.0,
             max_health: 20,
             entity: EntityData::with_uuid(entity_id, uuid, dimension, pos),
+            metadata,
+            username: String::new(),
+            properties: Vec::new(),
+            display_name: None,
+            chat_mode: ChatMode::Enabled,
+            chat_session: None,
+            last_seen: LastSeenMessages::new(),
             game_mode: 0,
+            permission_level: 0,
             previous_game_mode: -1,
             death_location: None,
+            inventory: Inventory::default(),
+            ender_chest: Inventory::default(),
+            selected_slot: 0,
+            experience_level: 0,
+            experience_progress: 0.0,
+            total_experience: 0,
+            abilities: PlayerAbilities::default(),
+            saved_data: NbtCompound::new(),
             teleport_id: None,
+            latency: 0,
+            center_chunk: chunk_pos(pos),
             chunks: Vec::with_capacity(512),
             recv: MultipleReceiver::default(),
-            tx,
+            connection,
         }
     }
 
-    pub fn update(&mut self) {
+    /// Shows the skin parts enabled in the client's settings to the players seeing it.
+    ///
+    /// # Parameters
+    /// - `skin_parts`: The bit mask of the displayed skin parts, as sent by the client.
+    /// - `main_hand`: 0 for the left hand, 1 for the right hand.
+    pub fn set_client_settings(&mut self, skin_parts: u8, main_hand: u8) {
+        self.metadata.set(SKIN_PARTS, skin_parts as i8);
+        self.metadata.set(MAIN_HAND, main_hand as i8);
+    }
+
+    /// Moves the center chunk to the player's position
+    /// and drops the chunks that are no longer within the view distance.
+    ///
+    /// # Returns
+    /// The positions of the dropped chunks.
+    pub fn update(&mut self) -> Vec<(i32, i32)> {
+        self.center_chunk = chunk_pos(self.entity.pos);
+        let (center_x, center_z) = self.center_chunk;
         let mut del: Vec<usize> = Vec::with_capacity(128);
         let mut i = 0;
-        let view_distance = (*VIEW_DISTANCE + 1) as f64 * 16f64;
         while i < self.chunks.len() {
-            let chunk = &self.chunks[i];
-            let (block_x, block_z) = {
-                let (chunk_x, chunk_z) = chunk.get_position();
-                (chunk_x * 16, chunk_z * 16)
-            };
-            if (self.entity.pos.0 - block_x as f64).abs() > view_distance
-                || (self.entity.pos.2 - block_z as f64).abs() > view_distance
+            let (chunk_x, chunk_z) = self.chunks[i].get_position();
+            if (chunk_x - center_x).abs() > *VIEW_DISTANCE
+                || (chunk_z - center_z).abs() > *VIEW_DISTANCE
             {
                 del.push(i);
             }
             i += 1;
         }
+        let mut dropped = Vec::with_capacity(del.len());
         for i in del.iter().rev() {
-            self.chunks.remove(*i).player_exit(self);
+            let chunk = self.chunks.remove(*i);
+            chunk.player_exit(self);
+            dropped.push(chunk.get_position());
+        }
+        dropped
+    }
+}
+
+/// What the player is allowed to do, following its game mode.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PlayerAbilities {
+    pub invulnerable: bool,
+    pub flying: bool,
+    pub may_fly: bool,
+    /// Whether blocks are broken instantly and items are not used up.
+    pub instabuild: bool,
+    pub may_build: bool,
+    pub flying_speed: f32,
+    pub walking_speed: f32,
+}
+
+impl PlayerAbilities {
+    /// Sets the abilities given by a game mode.
+    pub fn update(&mut self, game_mode: u8) {
+        match game_mode {
+            1 => {
+                self.may_fly = true;
+                self.instabuild = true;
+                self.invulnerable = true;
+            }
+            3 => {
+                self.may_fly = true;
+                self.instabuild = false;
+                self.invulnerable = true;
+                self.flying = true;
+            }
+            _ => {
+                self.may_fly = false;
+                self.instabuild = false;
+                self.invulnerable = false;
+                self.flying = false;
+            }
         }
+        self.may_build = game_mode == 0 || game_mode == 1;
     }
 }
 
-impl_entity!(Player, entity, "minecraft:player");
+impl Default for PlayerAbilities {
+    fn default() -> Self {
+        PlayerAbilities {
+            invulnerable: false,
+            flying: false,
+            may_fly: false,
+            instabuild: false,
+            may_build: true,
+            flying_speed: 0.05,
+            walking_speed: 0.1,
+        }
+    }
+}
+
+/// Gets the position of the chunk containing the given position.
+#[inline]
+pub fn chunk_pos(pos: (f64, f64, f64)) -> (i32, i32) {
+    (pos.0.floor() as i32 >> 4, pos.2.floor() as i32 >> 4)
+}
+
+impl_entity!(Player, entity, metadata, "minecraft:player");
 impl_living_entity!(Player, health, max_health);
 
 impl PartialEq<Self> for Player {
@@ -77,4 +210,17 @@ impl PartialEq<Self> for Player {
 
 impl Eq for Player {}
 
-pub enum PlayerUpdate {}
+/// An update handled by the task reading the player's connection,
+/// sent through [`ConnectionHandle::send_update`].
+pub enum PlayerUpdate {
+    /// Disconnects the player with a reason.
+    Kick(TextComponent),
+    /// Shows a message in the chat, such as the feedback of a command.
+    SystemMessage(TextComponent),
+    /// Moves the player within its dimension.
+    Teleport((f64, f64, f64)),
+    /// Changes the game mode of the player.
+    SetGameMode(u8),
+    /// Changes the permission level of the player, sending the commands it may now use.
+    SetPermissionLevel(u8),
+}
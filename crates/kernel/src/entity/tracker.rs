@@ -0,0 +1,271 @@
+use crate::config::{ENTITY_TRACKING_RANGE, VIEW_DISTANCE};
+use crate::entity::metadata::MetadataValue;
+use crate::entity::EntityData;
+use crate::network::outbound::ConnectionHandle;
+use crate::network::packet::s2c::remove_entities::RemoveEntitiesS2C;
+use crate::network::packet::s2c::set_entity_metadata::SetEntityMetadataS2C;
+use crate::network::packet::s2c::set_head_rotation::SetHeadRotationS2C;
+use crate::network::packet::s2c::spawn_entity::SpawnEntityS2C;
+use crate::network::packet::s2c::teleport_entity::TeleportEntityS2C;
+use crate::network::packet::s2c::update_entity_position::UpdateEntityPositionS2C;
+use crate::network::packet::s2c::update_entity_position_and_rotation::UpdateEntityPositionAndRotationS2C;
+use crate::network::packet::s2c::update_entity_rotation::UpdateEntityRotationS2C;
+use crate::util::to_angle;
+use crate::WORLD;
+use anyhow::Result;
+use hashbrown::HashMap;
+
+/// Keeps the entities seen by a player in sync with its client.
+///
+/// Every tick, the entities within the tracking range are spawned,
+/// the ones that left it are removed,
+/// and the movements of the others are sent as deltas of the last sent position.
+pub struct EntityTracker {
+    tracked: HashMap<i32, TrackedEntity>,
+    /// The version of the player's own metadata last sent to it.
+    own_metadata_version: u64,
+}
+
+impl Default for EntityTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The state of an entity as last sent to the client.
+struct TrackedEntity {
+    /// The position in 1/4096 of a block, the precision of relative moves.
+    pos: (i64, i64, i64),
+    yaw: u8,
+    pitch: u8,
+    head_yaw: u8,
+    on_ground: bool,
+    metadata_version: u64,
+}
+
+impl TrackedEntity {
+    fn new(data: &EntityData, metadata_version: u64) -> TrackedEntity {
+        TrackedEntity {
+            metadata_version,
+            pos: encode_pos(data.pos),
+            yaw: to_angle(data.yaw),
+            pitch: to_angle(data.pitch),
+            head_yaw: to_angle(data.head_yaw),
+            on_ground: data.on_ground,
+        }
+    }
+}
+
+/// An entity within the tracking range, copied out of its lock.
+struct VisibleEntity {
+    entity_type: u32,
+    data: EntityData,
+    /// The metadata changed since it was last sent.
+    metadata: Vec<(u8, MetadataValue)>,
+    metadata_version: u64,
+}
+
+#[inline]
+fn encode_pos(pos: (f64, f64, f64)) -> (i64, i64, i64) {
+    (
+        (pos.0 * 4096.0).round() as i64,
+        (pos.1 * 4096.0).round() as i64,
+        (pos.2 * 4096.0).round() as i64,
+    )
+}
+
+impl EntityTracker {
+    pub fn new() -> EntityTracker {
+        EntityTracker {
+            tracked: HashMap::new(),
+            own_metadata_version: 0,
+        }
+    }
+
+    /// Sends the changes of the entities around a player since the last update.
+    ///
+    /// The metadata changed since the last update is sent too, including the player's own.
+    ///
+    /// # Parameters
+    /// - `connection`: The handle of the player's connection.
+    /// - `viewer`: The entity id of the player, which is never tracked.
+    /// - `dimension`: The dimension the player is in.
+    /// - `pos`: The position of the player.
+    pub async fn update(
+        &mut self,
+        connection: &ConnectionHandle,
+        viewer: i32,
+        dimension: usize,
+        pos: (f64, f64, f64),
+    ) -> Result<()> {
+        let range = (*ENTITY_TRACKING_RANGE).min(*VIEW_DISTANCE * 16) as f64;
+        let mut visible: HashMap<i32, VisibleEntity> = HashMap::new();
+        let mut own_metadata = Vec::new();
+        WORLD.entities.for_each(|entity_id, entity| {
+            let data = entity.get_data();
+            // Like vanilla, the range is a square around the player, whatever the height.
+            if data.dimension != dimension
+                || (data.pos.0 - pos.0).abs().max((data.pos.2 - pos.2).abs()) > range
+            {
+                return;
+            }
+            let metadata = entity.get_metadata();
+            if entity_id == viewer {
+                own_metadata = metadata.changes_since(self.own_metadata_version);
+                self.own_metadata_version = metadata.get_version();
+                return;
+            }
+            let synced = self
+                .tracked
+                .get(&entity_id)
+                .map_or(0, |tracked| tracked.metadata_version);
+            visible.insert(
+                entity_id,
+                VisibleEntity {
+                    entity_type: entity.get_type(),
+                    data: *data,
+                    metadata: metadata.changes_since(synced),
+                    metadata_version: metadata.get_version(),
+                },
+            );
+        });
+
+        if !own_metadata.is_empty() {
+            connection
+                .send_packet(&SetEntityMetadataS2C {
+                    entity_id: viewer,
+                    metadata: own_metadata,
+                })
+                .await?;
+        }
+
+        let removed: Vec<i32> = self
+            .tracked
+            .keys()
+            .filter(|entity_id| !visible.contains_key(*entity_id))
+            .copied()
+            .collect();
+        if !removed.is_empty() {
+            for entity_id in removed.iter() {
+                self.tracked.remove(entity_id);
+            }
+            connection
+                .send_packet(&RemoveEntitiesS2C {
+                    entity_ids: removed,
+                })
+                .await?;
+        }
+
+        for (entity_id, visible) in visible {
+            let data = visible.data;
+            let tracked = match self.tracked.get_mut(&entity_id) {
+                Some(tracked) => tracked,
+                None => {
+                    connection
+                        .send_packet(&SpawnEntityS2C {
+                            entity_id,
+                            uuid: data.uuid,
+                            entity_type: visible.entity_type,
+                            pos: data.pos,
+                            yaw: data.yaw,
+                            pitch: data.pitch,
+                            head_yaw: data.head_yaw,
+                            data: 0,
+                            velocity: data.velocity,
+                        })
+                        .await?;
+                    if !visible.metadata.is_empty() {
+                        connection
+                            .send_packet(&SetEntityMetadataS2C {
+                                entity_id,
+                                metadata: visible.metadata,
+                            })
+                            .await?;
+                    }
+                    connection
+                        .send_packet(&SetHeadRotationS2C {
+                            entity_id,
+                            head_yaw: to_angle(data.head_yaw),
+                        })
+                        .await?;
+                    self.tracked.insert(
+                        entity_id,
+                        TrackedEntity::new(&data, visible.metadata_version),
+                    );
+                    continue;
+                }
+            };
+            let current = TrackedEntity::new(&data, visible.metadata_version);
+            let moved = current.pos != tracked.pos || current.on_ground != tracked.on_ground;
+            let rotated = current.yaw != tracked.yaw || current.pitch != tracked.pitch;
+            if moved {
+                let delta = (
+                    i16::try_from(current.pos.0 - tracked.pos.0),
+                    i16::try_from(current.pos.1 - tracked.pos.1),
+                    i16::try_from(current.pos.2 - tracked.pos.2),
+                );
+                match delta {
+                    (Ok(dx), Ok(dy), Ok(dz)) if rotated => {
+                        connection
+                            .send_packet(&UpdateEntityPositionAndRotationS2C {
+                                entity_id,
+                                delta: (dx, dy, dz),
+                                yaw: current.yaw,
+                                pitch: current.pitch,
+                                on_ground: current.on_ground,
+                            })
+                            .await?
+                    }
+                    (Ok(dx), Ok(dy), Ok(dz)) => {
+                        connection
+                            .send_packet(&UpdateEntityPositionS2C {
+                                entity_id,
+                                delta: (dx, dy, dz),
+                                on_ground: current.on_ground,
+                            })
+                            .await?
+                    }
+                    // Too far for a relative move.
+                    _ => {
+                        connection
+                            .send_packet(&TeleportEntityS2C {
+                                entity_id,
+                                pos: data.pos,
+                                yaw: current.yaw,
+                                pitch: current.pitch,
+                                on_ground: current.on_ground,
+                            })
+                            .await?
+                    }
+                }
+            } else if rotated {
+                connection
+                    .send_packet(&UpdateEntityRotationS2C {
+                        entity_id,
+                        yaw: current.yaw,
+                        pitch: current.pitch,
+                        on_ground: current.on_ground,
+                    })
+                    .await?;
+            }
+            if current.head_yaw != tracked.head_yaw {
+                connection
+                    .send_packet(&SetHeadRotationS2C {
+                        entity_id,
+                        head_yaw: current.head_yaw,
+                    })
+                    .await?;
+            }
+            if !visible.metadata.is_empty() {
+                connection
+                    .send_packet(&SetEntityMetadataS2C {
+                        entity_id,
+                        metadata: visible.metadata,
+                    })
+                    .await?;
+            }
+            *tracked = current;
+        }
+        Ok(())
+    }
+}
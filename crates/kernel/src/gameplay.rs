@@ -1,50 +1,464 @@
-use crate::entity::player::Player;
+pub mod chat;
+pub mod command;
+pub mod level;
+pub mod tab_list;
+
+use crate::config::{SIMULATION_DISTANCE, VIEW_DISTANCE};
+use crate::entity::player::{chunk_pos, Player, PlayerUpdate};
 use crate::entity::Entity;
-use crate::network::connection::Connection;
+use crate::network::connection::{ChatMode, Connection};
+use crate::network::packet::c2s::client_info::main_hand_id;
+use crate::network::packet::s2c::block_update::BlockUpdateS2C;
+use crate::network::packet::s2c::chunk_batch_finished::ChunkBatchFinishedS2C;
+use crate::network::packet::s2c::chunk_batch_start::ChunkBatchStartS2C;
+use crate::network::packet::s2c::chunk_data_and_update_light::ChunkDataAndUpdateLightS2C;
+use crate::network::packet::s2c::entity_event::EntityEventS2C;
 use crate::network::packet::s2c::game_event::{GameEvent, GameEventS2C};
 use crate::network::packet::s2c::play_login::PlayLoginS2C;
+use crate::network::packet::s2c::player_info_update::{actions, PlayerInfoEntry};
 use crate::network::packet::s2c::set_center_chunk::SetCenterChunkS2C;
 use crate::network::packet::s2c::synchronize_player_position::SynchronizePlayerPositionS2C;
+use crate::network::packet::s2c::system_chat_message::SystemChatMessageS2C;
+use crate::network::packet::s2c::unload_chunk::UnloadChunkS2C;
+use crate::network::packet::s2c::update_section_blocks::UpdateSectionBlocksS2C;
+use crate::server;
+use crate::server::ops::OPS;
+use crate::util::to_dim_xz;
+use crate::world::chunk::ChunkUpdate::BlockChange;
+use crate::world::player_data;
+use crate::world::ticket::TicketType;
 use crate::WORLD;
-use parking_lot::Mutex;
-use std::sync::Arc;
-use tokio::sync::mpsc::unbounded_channel;
+use anyhow::anyhow;
+use dashmap::DashMap;
+use hashbrown::{HashMap, HashSet};
+use parking_lot::{Mutex, RwLock};
+use spotlight::event::EventCallback;
+use std::sync::{Arc, LazyLock};
+use tracing::{info, warn};
 use uuid::Uuid;
 
-pub(crate) async fn player_join(connection: &mut Connection<'_>) -> anyhow::Result<()> {
+/// The players in the game, by uuid.
+pub static PLAYERS: LazyLock<DashMap<Uuid, Arc<Mutex<Player>>>> = LazyLock::new(DashMap::new);
+
+/// Called when a player leaves the game, before it is removed from the world.
+pub static PLAYER_QUIT_CALLBACK: RwLock<EventCallback<Arc<Mutex<Player>>>> =
+    RwLock::new(EventCallback::new());
+
+pub(crate) async fn player_join(connection: &mut Connection) -> anyhow::Result<()> {
     let eid;
     let arc: Arc<Mutex<Player>>;
     let (chunk_x, chunk_z);
+    let (play_login, synchronize_position);
     {
+        let uuid = Uuid::from_u128(connection.uuid.unwrap());
+        // Read off the connection's task.
+        let saved_data =
+            tokio::task::spawn_blocking(move || player_data::load(&WORLD.directory, uuid))
+                .await
+                .map_err(anyhow::Error::from)
+                .and_then(|result| result)
+                .unwrap_or_else(|err| {
+                    warn!("Failed to load the data of player {}: {:?}", uuid, err);
+                    None
+                });
         let wsp = WORLD.get_world_spawn_point();
         eid = WORLD.entities.generate_eid();
-        let (tx, recv) = unbounded_channel();
-        let player = Player::new(
+        let mut player = Player::new(
             eid,
             wsp.0,
-            tx,
+            connection.handle.clone(),
             (wsp.1 as f64, wsp.2 as f64, wsp.3 as f64),
-            Uuid::from_u128(connection.uuid.unwrap()),
+            uuid,
         );
-        connection.recv = Some(recv);
-        chunk_x = player.entity.pos.0 as i32 >> 4;
-        chunk_z = player.entity.pos.2 as i32 >> 4;
+        if let Some(saved_data) = saved_data {
+            player_data::read(&mut player, saved_data);
+        }
+        player.permission_level = OPS.get_level(uuid).unwrap_or(0);
+        player.username = connection.username.clone().unwrap_or_default();
+        player.properties = connection.properties.clone();
+        player.chat_mode = connection.chat_mode.unwrap_or(ChatMode::Enabled);
+        player.set_client_settings(connection.skin_parts.unwrap_or(0), main_hand_id(connection));
+        (chunk_x, chunk_z) = player.center_chunk;
+        WORLD.dimensions[player.entity.dimension].add_ticket(
+            chunk_x,
+            chunk_z,
+            TicketType::Player,
+            get_player_ticket_radius(),
+        );
+        play_login = PlayLoginS2C::new(&player);
+        synchronize_position = SynchronizePlayerPositionS2C::new(&mut player);
         arc = Arc::new(Mutex::new(player));
         connection.player = Some(arc.clone());
-        WORLD
-            .entities
-            .spawn(&(arc.clone() as Arc<Mutex<dyn Entity>>));
     }
     connection.player_eid = Some(eid);
-    connection.send_packet(&PlayLoginS2C).await?;
+    connection.send_packet(&play_login).await?;
+    // Listed before the tab list is sent, so the players joining at the same time see each other.
+    PLAYERS.insert(Uuid::from_u128(connection.uuid.unwrap()), arc.clone());
+    tab_list::player_join(&arc).await?;
+    let permission_level = arc.lock().permission_level;
+    connection
+        .send_packet(&EntityEventS2C::permission_level(eid, permission_level))
+        .await?;
+    command::send_commands(&arc).await?;
+    WORLD
+        .entities
+        .spawn(&(arc.clone() as Arc<Mutex<dyn Entity>>));
     connection
         .send_packet(&GameEventS2C::empty(GameEvent::StartWaitingForLevelChunks))
         .await?;
+    connection.send_packet(&synchronize_position).await?;
+    level::player_join(connection).await?;
     connection
-        .send_packet(&SynchronizePlayerPositionS2C)
+        .send_packet(&SetCenterChunkS2C { chunk_x, chunk_z })
         .await?;
+    load_chunks(connection, &arc).await
+}
+
+/// Removes the player of a closed connection from the world.
+///
+/// The quit event is fired, the player's data is saved if saving is on, the entity is despawned,
+/// the player is unsubscribed from all the chunks it held and removed from the tab list.
+/// Does nothing if the connection never joined the game.
+pub(crate) async fn player_quit(connection: &mut Connection) {
+    let p = match connection.player.take() {
+        Some(p) => p,
+        None => return,
+    };
+    connection.player_eid = None;
+    PLAYER_QUIT_CALLBACK.read().interact(p.clone());
+    // Not while holding the player's lock, which map_players takes under the map's lock.
+    let uuid = p.lock().get_data().uuid;
+    PLAYERS.remove(&uuid);
+    let saved_data = {
+        let mut player = p.lock();
+        let saved_data = server::is_saving().then(|| player_data::write(&player));
+        WORLD.entities.remove(player.get_eid());
+        let (chunk_x, chunk_z) = player.center_chunk;
+        WORLD.dimensions[player.entity.dimension].remove_ticket(
+            chunk_x,
+            chunk_z,
+            &TicketType::Player,
+        );
+        for chunk in std::mem::take(&mut player.chunks) {
+            chunk.player_exit(&mut player);
+        }
+        saved_data
+    };
+    if let Some(saved_data) = saved_data {
+        // Written off the connection's task, without holding the player.
+        let directory = WORLD.directory.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            player_data::save_data(&directory, uuid, &saved_data)
+        })
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|result| result);
+        if let Err(err) = result {
+            warn!("Failed to save the data of player {}: {:?}", uuid, err);
+        }
+    }
+    tab_list::player_quit(uuid).await;
+    info!(
+        "{} left the game.",
+        connection.username.as_deref().unwrap_or("Player")
+    );
+}
+
+/// Maps every player in the game, while holding its lock.
+///
+/// The callback must not lock any player.
+pub fn map_players<T>(mut f: impl FnMut(&mut Player) -> T) -> Vec<T> {
+    PLAYERS
+        .iter()
+        .map(|player| f(&mut player.value().lock()))
+        .collect()
+}
+
+/// Saves the data of every player in the game.
+///
+/// # Returns
+/// The last error, if the data of some players could not be saved.
+pub fn save_players() -> anyhow::Result<()> {
+    map_players(|player| player_data::save(&WORLD.directory, player))
+        .into_iter()
+        .filter_map(Result::err)
+        .next_back()
+        .map_or(Ok(()), Err)
+}
+
+/// Handles an update sent to the player from another task.
+pub(crate) async fn player_update(
+    connection: &mut Connection,
+    update: PlayerUpdate,
+) -> anyhow::Result<()> {
+    match update {
+        PlayerUpdate::Kick(reason) => connection.disconnect(&reason).await,
+        PlayerUpdate::SystemMessage(message) => {
+            if connection.chat_mode == Some(ChatMode::Hidden) {
+                return Ok(());
+            }
+            connection
+                .send_packet(&SystemChatMessageS2C {
+                    content: &message,
+                    overlay: false,
+                })
+                .await
+        }
+        PlayerUpdate::Teleport((x, y, z)) => {
+            let p = connection.player.clone().ok_or(anyhow!(
+                "Gameplay: player_update: invalid context: player is undefined"
+            ))?;
+            let synchronize_position = {
+                let mut player = p.lock();
+                player.set_position(x, y, z);
+                SynchronizePlayerPositionS2C::new(&mut player)
+            };
+            connection.send_packet(&synchronize_position).await?;
+            player_move(connection).await
+        }
+        PlayerUpdate::SetPermissionLevel(level) => {
+            let p = connection.player.clone().ok_or(anyhow!(
+                "Gameplay: player_update: invalid context: player is undefined"
+            ))?;
+            let eid = {
+                let mut player = p.lock();
+                player.permission_level = level;
+                player.entity.entity_id
+            };
+            connection
+                .send_packet(&EntityEventS2C::permission_level(eid, level))
+                .await?;
+            command::send_commands(&p).await
+        }
+        PlayerUpdate::SetGameMode(game_mode) => {
+            let p = connection.player.clone().ok_or(anyhow!(
+                "Gameplay: player_update: invalid context: player is undefined"
+            ))?;
+            let entry = {
+                let mut player = p.lock();
+                if player.game_mode == game_mode {
+                    return Ok(());
+                }
+                player.previous_game_mode = player.game_mode as i8;
+                player.game_mode = game_mode;
+                player.abilities.update(game_mode);
+                PlayerInfoEntry::new(&player)
+            };
+            connection
+                .send_packet(&GameEventS2C::new(
+                    GameEvent::ChangeGameMode,
+                    game_mode as f32,
+                ))
+                .await?;
+            tab_list::update_player(entry, actions::UPDATE_GAME_MODE).await;
+            Ok(())
+        }
+    }
+}
+
+/// Updates the chunks of a player after it moved.
+///
+/// When the player crossed a chunk border,
+/// the chunks that left the view distance are unloaded and the new ones are queued.
+pub(crate) async fn player_move(connection: &mut Connection) -> anyhow::Result<()> {
+    let p = connection.player.clone().ok_or(anyhow!(
+        "Gameplay: player_move: invalid context: player is undefined"
+    ))?;
+    let (dropped, (chunk_x, chunk_z)) = {
+        let mut player = p.lock();
+        if chunk_pos(player.entity.pos) == player.center_chunk {
+            return Ok(());
+        }
+        let (old_x, old_z) = player.center_chunk;
+        let dropped = player.update();
+        let (chunk_x, chunk_z) = player.center_chunk;
+        let dimension = &WORLD.dimensions[player.entity.dimension];
+        dimension.remove_ticket(old_x, old_z, &TicketType::Player);
+        dimension.add_ticket(
+            chunk_x,
+            chunk_z,
+            TicketType::Player,
+            get_player_ticket_radius(),
+        );
+        (dropped, (chunk_x, chunk_z))
+    };
     connection
         .send_packet(&SetCenterChunkS2C { chunk_x, chunk_z })
         .await?;
+    for (chunk_x, chunk_z) in dropped {
+        // Chunks still waiting for their batch were never sent.
+        if !connection.chunk_sender.remove(chunk_x, chunk_z) {
+            connection
+                .send_packet(&UnloadChunkS2C { chunk_x, chunk_z })
+                .await?;
+        }
+    }
+    load_chunks(connection, &p).await
+}
+
+/// Gets the radius of the tickets of the players,
+/// so the chunks within the simulation distance are ticked.
+fn get_player_ticket_radius() -> u8 {
+    (*SIMULATION_DISTANCE + 1).clamp(1, 33) as u8
+}
+
+/// Queues the chunks within the view distance around the player that it does not have yet,
+/// nearest first, and subscribes the player to their updates.
+///
+/// The chunks are loaded or generated on a blocking thread,
+/// and sent by [`send_chunk_batch`] at the rate the client asks for.
+async fn load_chunks(
+    connection: &mut Connection,
+    player: &Arc<Mutex<Player>>,
+) -> anyhow::Result<()> {
+    let (dimension, (center_x, center_z), loaded) = {
+        let player = player.lock();
+        let loaded: HashSet<(i32, i32)> = player
+            .chunks
+            .iter()
+            .map(|chunk| chunk.get_position())
+            .collect();
+        (
+            WORLD.dimensions[player.entity.dimension].clone(),
+            player.center_chunk,
+            loaded,
+        )
+    };
+    let radius = *VIEW_DISTANCE;
+    let mut positions = Vec::with_capacity(((radius * 2 + 1) * (radius * 2 + 1)) as usize);
+    for x in center_x - radius..=center_x + radius {
+        for z in center_z - radius..=center_z + radius {
+            if !loaded.contains(&(x, z)) {
+                positions.push((x, z));
+            }
+        }
+    }
+    positions.sort_by_key(|(x, z)| (x - center_x).pow(2) + (z - center_z).pow(2));
+    let chunks = tokio::task::spawn_blocking(move || {
+        positions
+            .into_iter()
+            .map(|(x, z)| dimension.get_chunk(x, z))
+            .collect::<Vec<_>>()
+    })
+    .await?;
+    for chunk in chunks {
+        {
+            let mut player = player.lock();
+            chunk.player_enter(&mut player);
+            player.chunks.push(chunk.clone());
+        }
+        connection.chunk_sender.push(chunk);
+    }
     Ok(())
 }
+
+/// Sends the blocks changed in the player's chunks since the last tick.
+///
+/// A section with a single change gets a block update,
+/// the changes of a section with more are merged into one update section blocks packet.
+/// Called by the connection every tick while playing.
+pub(crate) async fn send_block_updates(connection: &mut Connection) -> anyhow::Result<()> {
+    let p = match connection.player.clone() {
+        Some(p) => p,
+        None => return Ok(()),
+    };
+    let mut sections: HashMap<(i32, i32, i32), HashMap<u16, u32>> = HashMap::new();
+    {
+        let player = p.lock();
+        let min_y = WORLD.dimensions[player.entity.dimension]
+            .dimension_type
+            .min_y;
+        for chunk in player.chunks.iter() {
+            let (chunk_x, chunk_z) = chunk.get_position();
+            let receiver = match player.recv.get(to_dim_xz(chunk_x, chunk_z)) {
+                Some(receiver) => receiver,
+                None => continue,
+            };
+            let mut updates = Vec::new();
+            while let Some(update) = receiver.try_receive() {
+                updates.push(update);
+            }
+            // Chunks still waiting for their batch are sent with the changes applied.
+            if updates.is_empty() || connection.chunk_sender.contains(chunk_x, chunk_z) {
+                continue;
+            }
+            for update in updates {
+                match *update {
+                    BlockChange(x, y, z, block) => {
+                        let y = y + min_y;
+                        sections
+                            .entry((chunk_x, y >> 4, chunk_z))
+                            .or_default()
+                            .insert((x << 8 | z << 4 | (y & 15)) as u16, block);
+                    }
+                }
+            }
+        }
+    }
+    for ((section_x, section_y, section_z), blocks) in sections {
+        if blocks.len() == 1 {
+            let (pos, block_state) = blocks.into_iter().next().unwrap();
+            connection
+                .send_packet(&BlockUpdateS2C {
+                    x: section_x * 16 + (pos >> 8) as i32,
+                    y: section_y * 16 + (pos & 15) as i32,
+                    z: section_z * 16 + (pos >> 4 & 15) as i32,
+                    block_state,
+                })
+                .await?;
+        } else {
+            connection
+                .send_packet(&UpdateSectionBlocksS2C {
+                    section_x,
+                    section_y,
+                    section_z,
+                    blocks: blocks.into_iter().collect(),
+                })
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Sends the next batch of queued chunks, if the client is ready for one.
+///
+/// Called by the connection every tick while playing.
+pub(crate) async fn send_chunk_batch(connection: &mut Connection) -> anyhow::Result<()> {
+    let batch = match connection.chunk_sender.next_batch() {
+        Some(batch) => batch,
+        None => return Ok(()),
+    };
+    connection.send_packet(&ChunkBatchStartS2C).await?;
+    for chunk in batch.iter() {
+        let frame = chunk
+            .get_frame(
+                ChunkDataAndUpdateLightS2C::ID,
+                connection.handle.compression_threshold(),
+            )
+            .await?;
+        connection.send_frame(frame).await?;
+    }
+    connection
+        .send_packet(&ChunkBatchFinishedS2C {
+            batch_size: batch.len() as i32,
+        })
+        .await?;
+    Ok(())
+}
+
+/// Sends the entities around the player to its client.
+///
+/// Called by the connection every tick while playing.
+pub(crate) async fn track_entities(connection: &mut Connection) -> anyhow::Result<()> {
+    let (eid, dimension, pos) = match &connection.player {
+        Some(p) => {
+            let player = p.lock();
+            (player.get_eid(), player.entity.dimension, player.entity.pos)
+        }
+        None => return Ok(()),
+    };
+    connection
+        .entity_tracker
+        .update(&connection.handle, eid, dimension, pos)
+        .await
+}
@@ -0,0 +1,256 @@
+use crate::entity::player::Player;
+use crate::entity::Entity;
+use crate::gameplay::map_players;
+use crate::gameplay::tab_list;
+use crate::network::chat_session::{
+    ChatSession, LastSeenUpdate, MessageBody, MessageSignature, ProfilePublicKey, SECURE_CHAT,
+};
+use crate::network::connection::{ChatMode, Connection};
+use crate::network::packet::s2c::player_chat_message::PlayerChatMessageS2C;
+use crate::network::packet::s2c::player_info_update::{actions, PlayerInfoEntry};
+use crate::network::packet::s2c::system_chat_message::SystemChatMessageS2C;
+use crate::util::text::TextComponent;
+use anyhow::anyhow;
+use hashbrown::HashMap;
+use parking_lot::{Mutex, RwLock};
+use spotlight::event::{ActionResult, EventCallback};
+use std::sync::Arc;
+use tracing::info;
+use uuid::Uuid;
+
+/// The longest chat message a client may send, in characters.
+pub const MAX_MESSAGE_LENGTH: usize = 256;
+/// How much a chat message adds to the spam counter, which decreases by one every tick.
+const SPAM_INCREMENT: i32 = 20;
+/// The value of the spam counter above which a player is kicked,
+/// ten messages at once or one message a second.
+const SPAM_THRESHOLD: i32 = 200;
+
+/// Called when a player sends a chat message, before it is broadcast.
+///
+/// Returning [`ActionResult::Fail`] cancels the message.
+pub static PLAYER_CHAT_CALLBACK: RwLock<EventCallback<PlayerChatEvent>> =
+    RwLock::new(EventCallback::new());
+
+pub struct PlayerChatEvent {
+    pub player: Arc<Mutex<Player>>,
+    pub message: String,
+}
+
+/// Whether a character may be sent in the chat.
+#[inline]
+pub fn is_allowed_character(c: char) -> bool {
+    c != '§' && c >= ' ' && c != '\u{7F}'
+}
+
+/// Counts a chat message or a command towards the spam limit.
+///
+/// # Returns
+/// Whether the player sent too many and must be kicked.
+pub(crate) fn is_spamming(connection: &mut Connection) -> bool {
+    connection.chat_spam += SPAM_INCREMENT;
+    connection.chat_spam > SPAM_THRESHOLD
+}
+
+/// Sends a message to every player accepting system messages.
+pub async fn broadcast_system_message(message: &TextComponent) {
+    broadcast(message, |chat_mode| chat_mode != ChatMode::Hidden).await;
+}
+
+/// Sends a message to the players whose chat mode is accepted,
+/// kicking those who fell too far behind rather than waiting for them.
+async fn broadcast(message: &TextComponent, accepts: impl Fn(ChatMode) -> bool) {
+    let packet = SystemChatMessageS2C {
+        content: message,
+        overlay: false,
+    };
+    let connections =
+        map_players(|player| accepts(player.chat_mode).then(|| player.connection.clone()));
+    for connection in connections.into_iter().flatten() {
+        let _ = connection.try_send_packet(&packet).await;
+    }
+}
+
+/// A chat message sent by a player.
+pub struct PlayerMessage {
+    pub message: String,
+    /// The time the message was sent, in milliseconds since the epoch.
+    pub timestamp: i64,
+    pub salt: i64,
+    /// The signature by the player's chat session, if the client signed the message.
+    pub signature: Option<MessageSignature>,
+    pub last_seen: LastSeenUpdate,
+}
+
+/// Starts the chat session a player signs its messages in,
+/// and sends it to every player so they can verify the messages.
+///
+/// Ignored without secure chat.
+pub(crate) async fn player_session(
+    connection: &mut Connection,
+    session_id: Uuid,
+    public_key: ProfilePublicKey,
+) -> anyhow::Result<()> {
+    let player = connection.player.clone().ok_or(anyhow!(
+        "Gameplay: player_session: invalid context: player is undefined"
+    ))?;
+    if !*SECURE_CHAT {
+        return Ok(());
+    }
+    let uuid = Uuid::from_u128(connection.uuid.unwrap_or(0));
+    let session = match ChatSession::new(uuid, session_id, public_key) {
+        Ok(session) => session,
+        Err(err) => {
+            return connection
+                .disconnect(&TextComponent::text(err.to_string()))
+                .await
+        }
+    };
+    let entry = {
+        let mut player = player.lock();
+        player.chat_session = Some(session);
+        PlayerInfoEntry::new(&player)
+    };
+    tab_list::update_player(entry, actions::INITIALIZE_CHAT).await;
+    Ok(())
+}
+
+/// Handles a chat message sent by a player.
+///
+/// The message is checked, passed to [`PLAYER_CHAT_CALLBACK`]
+/// and broadcast to the players with chat enabled.
+/// With secure chat, it must be signed by the player's chat session and is broadcast signed,
+/// otherwise it is broadcast as a system message.
+/// A player sending messages faster than one a second on average is kicked.
+pub(crate) async fn player_chat(
+    connection: &mut Connection,
+    message: PlayerMessage,
+) -> anyhow::Result<()> {
+    let player = connection.player.clone().ok_or(anyhow!(
+        "Gameplay: player_chat: invalid context: player is undefined"
+    ))?;
+    if message.message.chars().count() > MAX_MESSAGE_LENGTH {
+        return connection
+            .disconnect(&TextComponent::text("Chat message is too long"))
+            .await;
+    }
+    if !message.message.chars().all(is_allowed_character) {
+        return connection
+            .disconnect(&TextComponent::text("Illegal characters in chat"))
+            .await;
+    }
+    if !matches!(connection.chat_mode, None | Some(ChatMode::Enabled)) {
+        return connection
+            .send_packet(&SystemChatMessageS2C {
+                content: &TextComponent {
+                    color: Some("red".to_string()),
+                    ..TextComponent::text("Chat disabled in client options.")
+                },
+                overlay: false,
+            })
+            .await;
+    }
+    let uuid = Uuid::from_u128(connection.uuid.unwrap_or(0));
+    let body = MessageBody {
+        message: message.message,
+        timestamp: message.timestamp,
+        salt: message.salt,
+        last_seen: Vec::new(),
+    };
+    let signed = {
+        let mut player = player.lock();
+        let player = &mut *player;
+        match player.last_seen.apply_update(&message.last_seen) {
+            Ok(last_seen) => {
+                let body = MessageBody { last_seen, ..body };
+                match (&mut player.chat_session, &message.signature) {
+                    _ if !*SECURE_CHAT => Ok((None, body)),
+                    (Some(session), Some(signature)) => session
+                        .verify_message(uuid, &body, signature)
+                        .map(|index| (Some((index, *signature)), body)),
+                    (None, _) => Err(anyhow!(
+                        "Chat disabled due to missing profile public key. Please try reconnecting."
+                    )),
+                    (Some(_), None) => Err(anyhow!("Chat message validation failure")),
+                }
+            }
+            Err(_) => Err(anyhow!("Chat message validation failure")),
+        }
+    };
+    let (signed, body) = match signed {
+        Ok(signed) => signed,
+        Err(err) => {
+            return connection
+                .disconnect(&TextComponent::text(err.to_string()))
+                .await
+        }
+    };
+    if is_spamming(connection) {
+        return connection
+            .disconnect(&TextComponent::text("Kicked for spamming"))
+            .await;
+    }
+    let event = PlayerChatEvent {
+        player: player.clone(),
+        message: body.message.clone(),
+    };
+    if let ActionResult::Fail = PLAYER_CHAT_CALLBACK.read().interact(event) {
+        return Ok(());
+    }
+    let name = {
+        let player = player.lock();
+        player
+            .display_name
+            .clone()
+            .unwrap_or_else(|| TextComponent::text(player.username.clone()))
+    };
+    info!("<{}> {}", name.text, body.message);
+    match signed {
+        Some((index, signature)) => {
+            broadcast_signed(
+                &PlayerChatMessageS2C {
+                    sender: uuid,
+                    index,
+                    signature: Some(&signature),
+                    body: &body,
+                    sender_name: &name,
+                },
+                &signature,
+            )
+            .await
+        }
+        None => {
+            let message = TextComponent {
+                extra: vec![
+                    TextComponent::text("<"),
+                    name,
+                    TextComponent::text(format!("> {}", body.message)),
+                ],
+                ..Default::default()
+            };
+            broadcast(&message, |chat_mode| chat_mode == ChatMode::Enabled).await
+        }
+    }
+    Ok(())
+}
+
+/// Sends a signed message to the players with chat enabled,
+/// recording it as seen by their clients in the order it is queued.
+async fn broadcast_signed(packet: &PlayerChatMessageS2C<'_>, signature: &MessageSignature) {
+    let connections = map_players(|player| {
+        (player.chat_mode == ChatMode::Enabled)
+            .then(|| (player.get_eid(), player.connection.clone()))
+    });
+    let mut frames = HashMap::new();
+    for (eid, connection) in connections.into_iter().flatten() {
+        if let Ok(frame) = connection.encode(packet).await {
+            frames.insert(eid, frame);
+        }
+    }
+    map_players(|player| {
+        if let Some(frame) = frames.remove(&player.get_eid()) {
+            player.last_seen.add_pending(signature);
+            let _ = player.connection.try_send_frame(frame);
+        }
+    });
+}
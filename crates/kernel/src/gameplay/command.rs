@@ -0,0 +1,91 @@
+use crate::command::{CommandSource, DISPATCHER};
+use crate::entity::player::Player;
+use crate::gameplay::chat::{is_allowed_character, is_spamming};
+use crate::network::chat_session::LastSeenUpdate;
+use crate::network::connection::Connection;
+use crate::network::packet::s2c::command_suggestions_response::CommandSuggestionsResponseS2C;
+use crate::network::packet::s2c::commands::CommandsS2C;
+use crate::util::text::TextComponent;
+use anyhow::anyhow;
+use parking_lot::Mutex;
+use std::sync::Arc;
+use tracing::info;
+
+/// Sends the commands a player may use to its client.
+///
+/// Must be sent again when the player's permissions change.
+pub async fn send_commands(player: &Arc<Mutex<Player>>) -> anyhow::Result<()> {
+    let nodes = DISPATCHER
+        .read()
+        .get_nodes(&CommandSource::Player(player.clone()));
+    let connection = player.lock().connection.clone();
+    connection.send_packet(&CommandsS2C { nodes }).await
+}
+
+/// Runs a command typed by a player, without its leading slash.
+///
+/// The errors of the command are sent back to the player.
+///
+/// # Parameters
+/// - `last_seen`: The acknowledgements sent with a signed command.
+pub(crate) async fn player_command(
+    connection: &mut Connection,
+    command: &str,
+    last_seen: Option<LastSeenUpdate>,
+) -> anyhow::Result<()> {
+    let player = connection.player.clone().ok_or(anyhow!(
+        "Gameplay: player_command: invalid context: player is undefined"
+    ))?;
+    if let Some(last_seen) = last_seen {
+        let result = player.lock().last_seen.apply_update(&last_seen);
+        if result.is_err() {
+            return connection
+                .disconnect(&TextComponent::text("Chat message validation failure"))
+                .await;
+        }
+    }
+    if !command.chars().all(is_allowed_character) {
+        return connection
+            .disconnect(&TextComponent::text("Illegal characters in chat"))
+            .await;
+    }
+    if is_spamming(connection) {
+        return connection
+            .disconnect(&TextComponent::text("Kicked for spamming"))
+            .await;
+    }
+    let source = CommandSource::Player(player);
+    info!("{} issued server command: /{}", source.get_name(), command);
+    let result = DISPATCHER.read().execute(&source, command);
+    if let Err(err) = result {
+        source.send_error(err.to_string());
+    }
+    Ok(())
+}
+
+/// Answers the request of a client for the completions of the command being typed.
+pub(crate) async fn command_suggestions(
+    connection: &mut Connection,
+    id: i32,
+    text: &str,
+) -> anyhow::Result<()> {
+    let player = match &connection.player {
+        Some(player) => player.clone(),
+        None => return Ok(()),
+    };
+    let command = text.strip_prefix('/').unwrap_or(text);
+    let offset = text.len() - command.len();
+    let (start, matches) = DISPATCHER
+        .read()
+        .suggest(&CommandSource::Player(player), command);
+    // The client counts in UTF-16 code units.
+    let start = text[..offset + start].encode_utf16().count() as i32;
+    connection
+        .send_packet(&CommandSuggestionsResponseS2C {
+            id,
+            start,
+            length: text.encode_utf16().count() as i32 - start,
+            matches,
+        })
+        .await
+}
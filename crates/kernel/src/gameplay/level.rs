@@ -0,0 +1,97 @@
+use crate::gameplay::map_players;
+use crate::network::connection::Connection;
+use crate::network::packet::s2c::game_event::{GameEvent, GameEventS2C};
+use crate::network::packet::s2c::set_default_spawn_position::SetDefaultSpawnPositionS2C;
+use crate::network::packet::s2c::update_time::UpdateTimeS2C;
+use crate::network::packet::Encode;
+use crate::WORLD;
+use std::sync::atomic::Ordering;
+
+/// The ticks between two time updates, the clients advancing the time in between.
+const TIME_UPDATE_INTERVAL: i64 = 20;
+
+/// The rain level above which it rains.
+const RAINING_LEVEL: f32 = 0.2;
+
+/// Advances the time and the weather and sends their changes to the players.
+///
+/// Called every tick after the world's tick.
+pub(crate) async fn tick() {
+    let (previous, levels) = WORLD.tick_level();
+    let changed = WORLD.level_changed.swap(false, Ordering::AcqRel);
+    let (time, spawn) = {
+        let level_data = WORLD.level_data.lock();
+        (
+            UpdateTimeS2C {
+                game_time: level_data.game_time,
+                day_time: level_data.day_time,
+            },
+            SetDefaultSpawnPositionS2C {
+                pos: level_data.spawn,
+                angle: level_data.spawn_angle,
+            },
+        )
+    };
+    if changed || time.game_time % TIME_UPDATE_INTERVAL == 0 {
+        broadcast(&time).await;
+    }
+    if changed {
+        broadcast(&spawn).await;
+    }
+    let (was_raining, raining) = (previous.0 > RAINING_LEVEL, levels.0 > RAINING_LEVEL);
+    if was_raining != raining {
+        let event = if raining {
+            GameEvent::BeginRaining
+        } else {
+            GameEvent::EndRaining
+        };
+        broadcast(&GameEventS2C::empty(event)).await;
+    }
+    if previous.0 != levels.0 {
+        broadcast(&GameEventS2C::new(GameEvent::RainLevelChange, levels.0)).await;
+    }
+    if previous.1 != levels.1 {
+        broadcast(&GameEventS2C::new(GameEvent::ThunderLevelChange, levels.1)).await;
+    }
+}
+
+/// Sends the time, the spawn point and the weather to a player joining the game.
+pub(crate) async fn player_join(connection: &Connection) -> anyhow::Result<()> {
+    let (time, spawn) = {
+        let level_data = WORLD.level_data.lock();
+        (
+            UpdateTimeS2C {
+                game_time: level_data.game_time,
+                day_time: level_data.day_time,
+            },
+            SetDefaultSpawnPositionS2C {
+                pos: level_data.spawn,
+                angle: level_data.spawn_angle,
+            },
+        )
+    };
+    connection.send_packet(&spawn).await?;
+    connection.send_packet(&time).await?;
+    let (rain_level, thunder_level) = *WORLD.weather_levels.lock();
+    if rain_level > RAINING_LEVEL {
+        connection
+            .send_packet(&GameEventS2C::empty(GameEvent::BeginRaining))
+            .await?;
+        connection
+            .send_packet(&GameEventS2C::new(GameEvent::RainLevelChange, rain_level))
+            .await?;
+        connection
+            .send_packet(&GameEventS2C::new(
+                GameEvent::ThunderLevelChange,
+                thunder_level,
+            ))
+            .await?;
+    }
+    Ok(())
+}
+
+async fn broadcast<D: Encode>(packet: &D) {
+    for connection in map_players(|player| player.connection.clone()) {
+        let _ = connection.try_send_packet(packet).await;
+    }
+}
@@ -0,0 +1,126 @@
+use crate::entity::player::Player;
+use crate::gameplay::map_players;
+use crate::network::outbound::ConnectionHandle;
+use crate::network::packet::s2c::player_info_remove::PlayerInfoRemoveS2C;
+use crate::network::packet::s2c::player_info_update::{
+    actions, PlayerInfoEntry, PlayerInfoUpdateS2C,
+};
+use crate::network::packet::s2c::set_tab_list_header_and_footer::SetTabListHeaderAndFooterS2C;
+use crate::network::packet::Encode;
+use crate::util::text::TextComponent;
+use anyhow::Result;
+use parking_lot::{Mutex, RwLock};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// The ticks between two updates of the latency shown in the tab list, like vanilla.
+const LATENCY_UPDATE_INTERVAL: u64 = 600;
+
+/// The header and footer of the tab list, shown to every player.
+static HEADER_AND_FOOTER: RwLock<Option<(TextComponent, TextComponent)>> = RwLock::new(None);
+
+/// Sets the text shown above and below the tab list of every player.
+pub async fn set_header_and_footer(header: TextComponent, footer: TextComponent) {
+    let packet = SetTabListHeaderAndFooterS2C {
+        header: &header,
+        footer: &footer,
+    };
+    broadcast(&packet, None).await;
+    *HEADER_AND_FOOTER.write() = Some((header, footer));
+}
+
+/// Sends an update of a player's entry to every player.
+///
+/// # Parameters
+/// - `entry`: The entry of the player, built while holding its lock.
+/// - `actions`: The bit mask of the [`actions`] to send.
+pub async fn update_player(entry: PlayerInfoEntry, actions: u8) {
+    let packet = PlayerInfoUpdateS2C {
+        actions,
+        entries: vec![entry],
+    };
+    broadcast(&packet, None).await;
+}
+
+/// Adds a joining player to the tab list of every player,
+/// and sends it the entries of every player online, its own included.
+///
+/// Must be called once the player is in [`PLAYERS`](crate::gameplay::PLAYERS),
+/// so of two players joining at once, the last to list the players online sees the other.
+pub(crate) async fn player_join(player: &Arc<Mutex<Player>>) -> Result<()> {
+    let (connection, entry) = {
+        let player = player.lock();
+        (player.connection.clone(), PlayerInfoEntry::new(&player))
+    };
+    let uuid = entry.uuid;
+    broadcast(
+        &PlayerInfoUpdateS2C {
+            actions: actions::ALL,
+            entries: vec![entry],
+        },
+        Some(uuid),
+    )
+    .await;
+    let entries = online_players()
+        .into_iter()
+        .map(|(_, entry)| entry)
+        .collect();
+    connection
+        .send_packet(&PlayerInfoUpdateS2C {
+            actions: actions::ALL,
+            entries,
+        })
+        .await?;
+    let header_and_footer = HEADER_AND_FOOTER.read().clone();
+    if let Some((header, footer)) = header_and_footer {
+        connection
+            .send_packet(&SetTabListHeaderAndFooterS2C {
+                header: &header,
+                footer: &footer,
+            })
+            .await?;
+    }
+    Ok(())
+}
+
+/// Sends the latency of every player to every player, every [`LATENCY_UPDATE_INTERVAL`] ticks.
+///
+/// The latency measured by the keep alives is only stored in between.
+pub(crate) async fn tick(tick: u64) {
+    if !tick.is_multiple_of(LATENCY_UPDATE_INTERVAL) {
+        return;
+    }
+    let (connections, entries): (Vec<ConnectionHandle>, Vec<PlayerInfoEntry>) =
+        online_players().into_iter().unzip();
+    if entries.is_empty() {
+        return;
+    }
+    let packet = PlayerInfoUpdateS2C {
+        actions: actions::UPDATE_LATENCY,
+        entries,
+    };
+    for connection in connections {
+        let _ = connection.try_send_packet(&packet).await;
+    }
+}
+
+/// Removes a player that left the game from the tab list of every player.
+pub(crate) async fn player_quit(uuid: Uuid) {
+    broadcast(&PlayerInfoRemoveS2C { uuids: vec![uuid] }, Some(uuid)).await;
+}
+
+/// Sends a packet to every player online, except the given one.
+///
+/// A client whose outbound queue is full is kicked rather than waited for.
+async fn broadcast<D: Encode>(packet: &D, except: Option<Uuid>) {
+    for (connection, entry) in online_players() {
+        if Some(entry.uuid) != except {
+            let _ = connection.try_send_packet(packet).await;
+        }
+    }
+}
+
+/// Gets the connection and the tab list entry of every player spawned in the world.
+fn online_players() -> Vec<(ConnectionHandle, PlayerInfoEntry)> {
+    map_players(|player| (player.connection.clone(), PlayerInfoEntry::new(player)))
+}
@@ -1,4 +1,5 @@
 pub mod block_items;
+pub mod inventory;
 
 use crate::block::BLOCK_ITEM_BY_ID;
 use crate::registry::protocol_id::get_protocol_id;
@@ -0,0 +1,88 @@
+use simdnbt::owned::{NbtCompound, NbtList, NbtTag};
+use std::collections::BTreeMap;
+
+/// A stack of items, as stored in the saved data.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ItemStack {
+    /// The name of the item, such as `minecraft:stone`.
+    pub item: String,
+    pub count: i32,
+    /// The data components of the stack, kept as they are stored.
+    pub components: Option<NbtCompound>,
+}
+
+impl ItemStack {
+    pub fn new(item: &str, count: i32) -> ItemStack {
+        ItemStack {
+            item: item.to_string(),
+            count,
+            components: None,
+        }
+    }
+}
+
+/// The items of a container, by slot in the numbering of their saved data.
+#[derive(Clone, Default)]
+pub struct Inventory {
+    slots: BTreeMap<i8, ItemStack>,
+}
+
+impl Inventory {
+    pub fn get(&self, slot: i8) -> Option<&ItemStack> {
+        self.slots.get(&slot)
+    }
+
+    /// Puts a stack in a slot, or empties the slot if `None`.
+    ///
+    /// # Returns
+    /// The stack previously in the slot.
+    pub fn set(&mut self, slot: i8, stack: Option<ItemStack>) -> Option<ItemStack> {
+        match stack {
+            Some(stack) => self.slots.insert(slot, stack),
+            None => self.slots.remove(&slot),
+        }
+    }
+
+    /// Iterates over the slots holding a stack, sorted by slot.
+    pub fn iter(&self) -> impl Iterator<Item = (i8, &ItemStack)> {
+        self.slots.iter().map(|(slot, stack)| (*slot, stack))
+    }
+
+    /// Reads the stacks from a list of `{Slot, id, count, components}` compounds.
+    pub fn read(nbt: &NbtList) -> Inventory {
+        let mut inventory = Inventory::default();
+        for item in nbt.compounds().unwrap_or_default() {
+            let (Some(slot), Some(id)) = (item.byte("Slot"), item.string("id")) else {
+                continue;
+            };
+            let stack = ItemStack {
+                item: id.to_string(),
+                count: item.int("count").unwrap_or(1),
+                components: item.compound("components").cloned(),
+            };
+            inventory.slots.insert(slot, stack);
+        }
+        inventory
+    }
+
+    pub fn write(&self) -> NbtList {
+        if self.slots.is_empty() {
+            return NbtList::Empty;
+        }
+        NbtList::Compound(
+            self.iter()
+                .map(|(slot, stack)| {
+                    let mut item = NbtCompound::from_values(vec![
+                        ("Slot".into(), NbtTag::Byte(slot)),
+                        ("id".into(), NbtTag::String(stack.item.as_str().into())),
+                        ("count".into(), NbtTag::Int(stack.count)),
+                    ]);
+                    if let Some(components) = &stack.components {
+                        item.insert("components", NbtTag::Compound(components.clone()));
+                    }
+                    item
+                })
+                .collect(),
+        )
+    }
+}
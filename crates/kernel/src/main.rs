@@ -2,22 +2,30 @@ extern crate core;
 
 pub mod api;
 pub mod block;
+pub mod command;
 pub mod config;
+pub mod console;
 pub mod entity;
 pub mod gameplay;
 pub mod item;
 pub mod network;
 pub mod registry;
+pub mod server;
 mod test;
 pub mod util;
 pub mod world;
 
-use crate::config::PORT;
+use crate::config::{FORWARDING_SECRET, ONLINE_MODE, PORT};
+use crate::console::ConsoleWriter;
+use crate::network::auth::KEY_PAIR;
+use crate::network::chat_session::load_certificate_keys;
+use crate::network::forwarding::{ForwardingMode, FORWARDING_MODE};
 use crate::registry::registries::register_vanilla;
 use crate::registry::{
     BIOMES_INDEX, DAMAGE_TYPES_INDEX, DIMENSION_TYPES_INDEX, PAINTING_VARIANTS_INDEX,
     WOLF_VARIANTS_INDEX,
 };
+use crate::server::TICK_TIMES;
 use mimalloc::MiMalloc;
 use network::connection::read_socket;
 use static_files::Resource;
@@ -25,11 +33,10 @@ use std::collections::HashMap;
 use std::io::Error;
 use std::net::SocketAddr;
 use std::sync::LazyLock;
-use tokio::io::{stdin, AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::net::{TcpListener, TcpStream};
 use tokio::task::block_in_place;
 use tokio::time::MissedTickBehavior::Skip;
-use tracing::{debug, info, instrument, warn};
+use tracing::{debug, error, info, instrument, warn};
 use tracing_subscriber::fmt;
 use tracing_subscriber::fmt::format;
 
@@ -38,6 +45,8 @@ static ALLOCATOR: MiMalloc = MiMalloc;
 
 pub const PROTOCOL_VERSION: i32 = 767;
 pub const MINECRAFT_VERSION: &str = "1.21"; // 1.21 - 1.21.1 ( Protocol 767 )
+/// The data version written to the saved chunks and level data.
+pub const DATA_VERSION: i32 = 3953; // 1.21
 include!(concat!(env!("OUT_DIR"), "/generated.rs"));
 pub static GENERATED: LazyLock<HashMap<&'static str, Resource>> = LazyLock::new(generate);
 pub static WORLD: LazyLock<world::World> = LazyLock::new(world::World::new);
@@ -54,17 +63,17 @@ async fn main() {
                 .with_thread_names(true)
                 .with_source_location(true),
         )
+        .with_writer(ConsoleWriter)
         .init();
-    tokio::spawn(async {
-        loop {
-            let mut reader = BufReader::new(stdin());
-            let mut buf = String::new();
-            reader
-                .read_line(&mut buf)
-                .await
-                .expect("Error in reading commands from stdin.");
-        }
-    });
+    console::start();
+    if matches!(*FORWARDING_MODE, ForwardingMode::Modern) && FORWARDING_SECRET.is_empty() {
+        error!("Velocity modern forwarding is enabled without a forwarding secret, set `forwarding-secret` in config.toml.");
+        std::process::exit(1);
+    }
+    if let Err(err) = load_certificate_keys().await {
+        error!("The `player-certificate-key` in config.toml is not a valid base64 DER public key: {:?}", err);
+        std::process::exit(1);
+    }
     let time = std::time::Instant::now();
     info!("Binding PORT: {:?}.", *PORT);
     let listener = TcpListener::bind(format!("127.0.0.1:{}", *PORT))
@@ -79,13 +88,28 @@ async fn main() {
         PAINTING_VARIANTS_INDEX.len()
     );
     register_vanilla();
+    // Loaded before any player joins, so a world which cannot be read stops the server at startup.
+    LazyLock::force(&WORLD);
+    if matches!(*FORWARDING_MODE, ForwardingMode::None) && *ONLINE_MODE {
+        LazyLock::force(&KEY_PAIR);
+        info!("Generated RSA key pair.");
+    }
+    server::set_saving(true);
     tokio::spawn(async move {
         let mut interval = tokio::time::interval(std::time::Duration::from_millis(50));
         interval.set_missed_tick_behavior(Skip);
+        let mut tick = 0u64;
         loop {
+            let start = std::time::Instant::now();
             block_in_place(|| {
                 WORLD.tick();
             });
+            gameplay::level::tick().await;
+            TICK_TIMES.lock().record(start, start.elapsed());
+            tick += 1;
+            gameplay::tab_list::tick(tick).await;
+            server::autosave(tick);
+            server::save_unloaded_chunks();
             interval.tick().await;
         }
     });
@@ -95,23 +119,19 @@ async fn main() {
         loop {
             accept_connection(listener.accept().await).await;
         }
-    })
-    .await
-    .unwrap();
+    });
+    server::wait_for_stop().await;
+    server::shutdown().await;
 }
 
 #[instrument]
 async fn accept_connection(result: Result<(TcpStream, SocketAddr), Error>) {
     match result {
-        Ok((mut socket, _addr)) => {
+        Ok((socket, _addr)) => {
             tokio::spawn(async move {
                 debug!("New connection accepted");
-                match read_socket(&mut socket).await {
-                    Ok(()) => {}
-                    Err(err) => {
-                        warn!("Error in handling packet: {:?}", err);
-                        let _ = socket.shutdown().await;
-                    }
+                if let Err(err) = read_socket(socket).await {
+                    warn!("Error in handling packet: {:?}", err);
                 }
             });
         }
@@ -1,2 +1,11 @@
+pub mod auth;
+pub mod chat_session;
+pub mod chunk_batch;
+pub mod codec;
+pub mod compression;
 pub mod connection;
+pub mod encryption;
+pub mod forwarding;
+pub mod legacy_ping;
+pub mod outbound;
 pub mod packet;
@@ -0,0 +1,154 @@
+use crate::config::SESSION_SERVER_URL;
+use anyhow::Result;
+use arc_swap::ArcSwap;
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use rsa::pkcs8::EncodePublicKey;
+use rsa::rand_core::OsRng;
+use rsa::{Pkcs1v15Encrypt, RsaPrivateKey};
+use serde_derive::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use std::sync::{Arc, LazyLock};
+use uuid::Uuid;
+
+/// The RSA key pair used for the encryption handshake, generated once per server start.
+pub static KEY_PAIR: LazyLock<KeyPair> = LazyLock::new(KeyPair::generate);
+
+/// The backend used to verify players in online mode.
+///
+/// Defaults to a [`MojangSessionServer`] pointed at the configured `session-server-url`,
+/// and can be replaced to authenticate against a different service.
+pub static SESSION_SERVER: LazyLock<ArcSwap<Box<dyn SessionServer>>> = LazyLock::new(|| {
+    ArcSwap::new(Arc::new(Box::new(MojangSessionServer::new(
+        SESSION_SERVER_URL.to_string(),
+    ))))
+});
+
+pub struct KeyPair {
+    private_key: RsaPrivateKey,
+    public_key_der: Vec<u8>,
+}
+
+impl KeyPair {
+    fn generate() -> KeyPair {
+        let private_key =
+            RsaPrivateKey::new(&mut OsRng, 1024).expect("Error in generating the RSA key pair.");
+        let public_key_der = private_key
+            .to_public_key()
+            .to_public_key_der()
+            .expect("Error in encoding the RSA public key.")
+            .into_vec();
+        KeyPair {
+            private_key,
+            public_key_der,
+        }
+    }
+
+    /// Gets the public key encoded as an ASN.1 `SubjectPublicKeyInfo` structure.
+    pub fn get_public_key_der(&self) -> &[u8] {
+        &self.public_key_der
+    }
+
+    pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
+        Ok(self.private_key.decrypt(Pkcs1v15Encrypt, data)?)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameProfile {
+    pub id: Uuid,
+    pub name: String,
+    #[serde(default)]
+    pub properties: Vec<ProfileProperty>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfileProperty {
+    pub name: String,
+    pub value: String,
+    #[serde(default)]
+    pub signature: Option<String>,
+}
+
+#[async_trait]
+pub trait SessionServer: Send + Sync {
+    /// Checks whether the player has joined the server through the session service.
+    ///
+    /// # Parameters
+    /// - `username`: The username sent in the login start packet.
+    /// - `server_hash`: The server hash computed by [`server_hash`].
+    ///
+    /// # Returns
+    /// - `Ok(Some(GameProfile))`: The profile of the authenticated player.
+    /// - `Ok(None)`: The player could not be verified.
+    /// - `Err(anyhow::Error)`: An error if the session service could not be reached.
+    async fn has_joined(&self, username: &str, server_hash: &str) -> Result<Option<GameProfile>>;
+}
+
+/// A session server speaking the `hasJoined` API of Mojang's session service.
+pub struct MojangSessionServer {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl MojangSessionServer {
+    pub fn new(url: String) -> MojangSessionServer {
+        MojangSessionServer {
+            url: url.trim_end_matches('/').to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl SessionServer for MojangSessionServer {
+    async fn has_joined(&self, username: &str, server_hash: &str) -> Result<Option<GameProfile>> {
+        let response = self
+            .client
+            .get(format!("{}/session/minecraft/hasJoined", self.url))
+            .query(&[("username", username), ("serverId", server_hash)])
+            .send()
+            .await?;
+        if response.status() != StatusCode::OK {
+            return Ok(None);
+        }
+        Ok(Some(response.json().await?))
+    }
+}
+
+/// Computes the server hash sent to the session server.
+///
+/// The hash is the SHA-1 digest of the server id (always empty),
+/// the shared secret and the public key,
+/// formatted the way Java's `BigInteger.toString(16)` does.
+pub fn server_hash(shared_secret: &[u8], public_key_der: &[u8]) -> String {
+    let mut sha = Sha1::new();
+    sha.update(shared_secret);
+    sha.update(public_key_der);
+    hex_digest(&sha.finalize())
+}
+
+/// Formats a digest as a signed, two's complement hexadecimal number without leading zeros.
+pub fn hex_digest(digest: &[u8]) -> String {
+    let negative = digest.first().is_some_and(|byte| byte & 0x80 != 0);
+    let mut bytes = digest.to_vec();
+    if negative {
+        let mut carry = true;
+        for byte in bytes.iter_mut().rev() {
+            *byte = !*byte;
+            if carry {
+                (*byte, carry) = byte.overflowing_add(1);
+            }
+        }
+    }
+    let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+    let hex = match hex.trim_start_matches('0') {
+        "" => "0",
+        hex => hex,
+    };
+    if negative {
+        format!("-{}", hex)
+    } else {
+        hex.to_string()
+    }
+}
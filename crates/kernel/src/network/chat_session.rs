@@ -0,0 +1,330 @@
+use crate::config::{ENFORCE_SECURE_CHAT, ONLINE_MODE, PLAYER_CERTIFICATE_KEY};
+use anyhow::{anyhow, Result};
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use rsa::pkcs1v15::{Signature, VerifyingKey};
+use rsa::pkcs8::DecodePublicKey;
+use rsa::signature::Verifier;
+use rsa::RsaPublicKey;
+use serde_derive::Deserialize;
+use sha1::Sha1;
+use sha2::Sha256;
+use std::collections::VecDeque;
+use std::sync::{LazyLock, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::warn;
+use uuid::Uuid;
+
+/// How many of the last messages a client acknowledges.
+pub const LAST_SEEN_COUNT: usize = 20;
+/// The length of a message signature, made with a 2048 bits RSA key.
+pub const SIGNATURE_LENGTH: usize = 256;
+
+pub type MessageSignature = [u8; SIGNATURE_LENGTH];
+
+/// Where the keys of the Mojang services are listed, when no player certificate key is configured.
+const PUBLIC_KEYS_URL: &str = "https://api.minecraftservices.com/publickeys";
+
+/// The keys the profile keys of the players may be signed with, set by [`load_certificate_keys`].
+static CERTIFICATE_KEYS: OnceLock<Vec<VerifyingKey<Sha1>>> = OnceLock::new();
+
+/// Whether chat messages are signed and verified.
+///
+/// Secure chat needs the online mode to trust the player's uuid,
+/// and the player certificate keys to trust its profile key.
+pub static SECURE_CHAT: LazyLock<bool> =
+    LazyLock::new(|| *ENFORCE_SECURE_CHAT && *ONLINE_MODE && CERTIFICATE_KEYS.get().is_some());
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PublicKeys {
+    player_certificate_keys: Vec<PublicKey>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PublicKey {
+    public_key: String,
+}
+
+/// Loads the player certificate keys if secure chat is enforced in online mode,
+/// from the config, or from the Mojang services when the config leaves it empty.
+///
+/// If the keys cannot be fetched, a warning is logged and secure chat is not enforced.
+///
+/// # Errors
+/// Returns an error if the configured key is not a base64 DER public key.
+pub async fn load_certificate_keys() -> Result<()> {
+    if !*ENFORCE_SECURE_CHAT || !*ONLINE_MODE {
+        return Ok(());
+    }
+    let keys = if PLAYER_CERTIFICATE_KEY.trim().is_empty() {
+        match fetch_certificate_keys().await {
+            Ok(keys) => keys,
+            Err(err) => {
+                warn!("Could not fetch the player certificate keys, secure chat is not enforced: {:#}", err);
+                return Ok(());
+            }
+        }
+    } else {
+        vec![parse_certificate_key(&PLAYER_CERTIFICATE_KEY)?]
+    };
+    let _ = CERTIFICATE_KEYS.set(keys);
+    Ok(())
+}
+
+async fn fetch_certificate_keys() -> Result<Vec<VerifyingKey<Sha1>>> {
+    let keys: PublicKeys = reqwest::get(PUBLIC_KEYS_URL)
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    if keys.player_certificate_keys.is_empty() {
+        return Err(anyhow!("No player certificate key is listed"));
+    }
+    keys.player_certificate_keys
+        .iter()
+        .map(|key| parse_certificate_key(&key.public_key))
+        .collect()
+}
+
+fn parse_certificate_key(key: &str) -> Result<VerifyingKey<Sha1>> {
+    let der = BASE64_STANDARD.decode(key.trim())?;
+    Ok(VerifyingKey::new(RsaPublicKey::from_public_key_der(&der)?))
+}
+
+#[inline]
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis() as i64)
+}
+
+/// The key a player signs its chat messages with, issued by the Mojang services.
+#[derive(Debug, Clone)]
+pub struct ProfilePublicKey {
+    /// The expiry time in milliseconds since the epoch.
+    pub expires_at: i64,
+    /// The key encoded as an ASN.1 `SubjectPublicKeyInfo` structure.
+    pub key: Vec<u8>,
+    /// The signature of the key by the player certificate key.
+    pub signature: Vec<u8>,
+}
+
+impl ProfilePublicKey {
+    pub fn has_expired(&self) -> bool {
+        self.expires_at < now_millis()
+    }
+
+    /// Checks that the key was issued to the player by the player certificate key.
+    ///
+    /// # Returns
+    /// The key to verify the player's messages with.
+    pub fn verify(&self, uuid: Uuid) -> Result<VerifyingKey<Sha256>> {
+        let certificate_keys = CERTIFICATE_KEYS
+            .get()
+            .ok_or(anyhow!("No player certificate key is loaded"))?;
+        let mut payload = Vec::with_capacity(24 + self.key.len());
+        payload.extend_from_slice(uuid.as_bytes());
+        payload.extend_from_slice(&self.expires_at.to_be_bytes());
+        payload.extend_from_slice(&self.key);
+        let signature = Signature::try_from(self.signature.as_slice())?;
+        if !certificate_keys
+            .iter()
+            .any(|key| key.verify(&payload, &signature).is_ok())
+        {
+            return Err(anyhow!("Invalid signature for profile public key"));
+        }
+        Ok(VerifyingKey::new(RsaPublicKey::from_public_key_der(
+            &self.key,
+        )?))
+    }
+}
+
+/// The signed content of a chat message.
+#[derive(Debug, Clone)]
+pub struct MessageBody {
+    pub message: String,
+    /// The time the message was sent, in milliseconds since the epoch.
+    pub timestamp: i64,
+    pub salt: i64,
+    /// The signatures of the messages the sender acknowledged.
+    pub last_seen: Vec<MessageSignature>,
+}
+
+/// The chat session of a player, the chain its signed messages belong to.
+pub struct ChatSession {
+    pub session_id: Uuid,
+    pub public_key: ProfilePublicKey,
+    verifying_key: VerifyingKey<Sha256>,
+    /// The index of the next message of the chain.
+    next_index: i32,
+    last_timestamp: i64,
+}
+
+impl ChatSession {
+    /// Starts the chat session of a player.
+    ///
+    /// # Errors
+    /// Returns the reason to kick the player with if its profile key is expired or not trusted.
+    pub fn new(uuid: Uuid, session_id: Uuid, public_key: ProfilePublicKey) -> Result<ChatSession> {
+        if public_key.has_expired() {
+            return Err(anyhow!("Expired profile public key"));
+        }
+        Ok(ChatSession {
+            session_id,
+            verifying_key: public_key.verify(uuid)?,
+            public_key,
+            next_index: 0,
+            last_timestamp: i64::MIN,
+        })
+    }
+
+    /// Verifies the signature of the next message of the chain.
+    ///
+    /// # Returns
+    /// - `Ok(i32)`: The index of the message in the chain.
+    /// - `Err(anyhow::Error)`: The reason to kick the player with.
+    pub fn verify_message(
+        &mut self,
+        sender: Uuid,
+        body: &MessageBody,
+        signature: &MessageSignature,
+    ) -> Result<i32> {
+        if body.timestamp < self.last_timestamp {
+            return Err(anyhow!(
+                "Out-of-order chat packet received. Did your system time change?"
+            ));
+        }
+        if self.public_key.has_expired() {
+            return Err(anyhow!(
+                "Chat disabled due to expired profile public key. Please try reconnecting."
+            ));
+        }
+        let index = self.next_index;
+        let mut payload =
+            Vec::with_capacity(64 + body.message.len() + body.last_seen.len() * SIGNATURE_LENGTH);
+        payload.extend_from_slice(&1i32.to_be_bytes());
+        payload.extend_from_slice(sender.as_bytes());
+        payload.extend_from_slice(self.session_id.as_bytes());
+        payload.extend_from_slice(&index.to_be_bytes());
+        payload.extend_from_slice(&body.salt.to_be_bytes());
+        payload.extend_from_slice(&(body.timestamp / 1000).to_be_bytes());
+        payload.extend_from_slice(&(body.message.len() as i32).to_be_bytes());
+        payload.extend_from_slice(body.message.as_bytes());
+        payload.extend_from_slice(&(body.last_seen.len() as i32).to_be_bytes());
+        for last_seen in body.last_seen.iter() {
+            payload.extend_from_slice(last_seen);
+        }
+        let signature = Signature::try_from(signature.as_slice())?;
+        self.verifying_key
+            .verify(&payload, &signature)
+            .map_err(|_| anyhow!("Chat message validation failure"))?;
+        self.next_index += 1;
+        self.last_timestamp = body.timestamp;
+        Ok(index)
+    }
+}
+
+/// The acknowledgements sent with a chat message.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LastSeenUpdate {
+    /// How many messages were received since the last acknowledgement.
+    pub offset: i32,
+    /// A bit set of which of the last [`LAST_SEEN_COUNT`] messages were seen.
+    pub acknowledged: [u8; 3],
+}
+
+/// A message sent to the client and not yet dropped from its last seen window.
+#[derive(Clone, Copy)]
+struct TrackedMessage {
+    signature: MessageSignature,
+    /// Whether the client has not acknowledged the message yet.
+    pending: bool,
+}
+
+/// Keeps track of the signed messages sent to a client,
+/// to check the messages it claims to have seen when it signs its own.
+pub struct LastSeenMessages {
+    tracked: VecDeque<Option<TrackedMessage>>,
+    last_pending: Option<MessageSignature>,
+}
+
+impl Default for LastSeenMessages {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LastSeenMessages {
+    pub fn new() -> LastSeenMessages {
+        LastSeenMessages {
+            tracked: VecDeque::from(vec![None; LAST_SEEN_COUNT]),
+            last_pending: None,
+        }
+    }
+
+    /// Records a signed message sent to the client.
+    pub fn add_pending(&mut self, signature: &MessageSignature) {
+        if self.last_pending.as_ref() != Some(signature) {
+            self.tracked.push_back(Some(TrackedMessage {
+                signature: *signature,
+                pending: true,
+            }));
+            self.last_pending = Some(*signature);
+        }
+    }
+
+    /// Drops the oldest messages of the window, as the client did.
+    pub fn apply_offset(&mut self, offset: i32) -> Result<()> {
+        let max = self.tracked.len() - LAST_SEEN_COUNT;
+        if offset < 0 || offset as usize > max {
+            return Err(anyhow!(
+                "Advanced last seen window by {} messages, but expected at most {}",
+                offset,
+                max
+            ));
+        }
+        self.tracked.drain(..offset as usize);
+        Ok(())
+    }
+
+    /// Applies the acknowledgements of a chat message.
+    ///
+    /// # Returns
+    /// The signatures of the seen messages, which are part of the signed message.
+    pub fn apply_update(&mut self, update: &LastSeenUpdate) -> Result<Vec<MessageSignature>> {
+        self.apply_offset(update.offset)?;
+        if update.acknowledged[LAST_SEEN_COUNT / 8] >> (LAST_SEEN_COUNT % 8) != 0 {
+            return Err(anyhow!(
+                "Last seen update acknowledged more than {} messages",
+                LAST_SEEN_COUNT
+            ));
+        }
+        let mut last_seen = Vec::with_capacity(LAST_SEEN_COUNT);
+        for i in 0..LAST_SEEN_COUNT {
+            let acknowledged = update.acknowledged[i / 8] >> (i % 8) & 1 != 0;
+            let tracked = &mut self.tracked[i];
+            match tracked {
+                Some(message) if acknowledged => {
+                    message.pending = false;
+                    last_seen.push(message.signature);
+                }
+                None if acknowledged => {
+                    return Err(anyhow!(
+                        "Last seen update acknowledged unknown or previously ignored message at index {}",
+                        i
+                    ))
+                }
+                Some(message) if !message.pending => {
+                    return Err(anyhow!(
+                        "Last seen update ignored previously acknowledged message at index {}",
+                        i
+                    ))
+                }
+                _ => *tracked = None,
+            }
+        }
+        Ok(last_seen)
+    }
+}
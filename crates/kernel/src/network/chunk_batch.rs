@@ -0,0 +1,103 @@
+use crate::world::chunk::Chunk;
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+/// The lowest rate a client can ask for, so a stalled client still receives chunks eventually.
+const MIN_CHUNKS_PER_TICK: f32 = 0.01;
+const MAX_CHUNKS_PER_TICK: f32 = 64.0;
+const START_CHUNKS_PER_TICK: f32 = 9.0;
+const MAX_UNACKNOWLEDGED_BATCHES: i32 = 10;
+
+/// Paces the chunks sent to a player to the rate requested by the client.
+///
+/// Chunks are queued with [`ChunkSender::push`] and sent in batches by the connection every tick.
+/// After each batch, the client answers with the number of chunks per tick it can handle,
+/// which is used as the budget for the next batches.
+pub struct ChunkSender {
+    pending: VecDeque<Arc<Chunk>>,
+    desired_chunks_per_tick: f32,
+    batch_quota: f32,
+    unacknowledged_batches: i32,
+    max_unacknowledged_batches: i32,
+}
+
+impl ChunkSender {
+    pub fn new() -> ChunkSender {
+        ChunkSender {
+            pending: VecDeque::new(),
+            desired_chunks_per_tick: START_CHUNKS_PER_TICK,
+            batch_quota: 0.0,
+            unacknowledged_batches: 0,
+            // Only one batch is sent until the client reports its rate for the first time.
+            max_unacknowledged_batches: 1,
+        }
+    }
+
+    /// Queues a chunk to be sent, after the chunks already queued.
+    pub fn push(&mut self, chunk: Arc<Chunk>) {
+        self.pending.push_back(chunk);
+    }
+
+    /// Checks whether a chunk is queued and has not been sent yet.
+    pub fn contains(&self, chunk_x: i32, chunk_z: i32) -> bool {
+        self.pending
+            .iter()
+            .any(|chunk| chunk.get_position() == (chunk_x, chunk_z))
+    }
+
+    /// Removes a queued chunk that has not been sent yet.
+    ///
+    /// # Returns
+    /// Whether the chunk was still queued.
+    pub fn remove(&mut self, chunk_x: i32, chunk_z: i32) -> bool {
+        let len = self.pending.len();
+        self.pending
+            .retain(|chunk| chunk.get_position() != (chunk_x, chunk_z));
+        self.pending.len() != len
+    }
+
+    /// Takes the chunks of the next batch, to be called once per tick.
+    ///
+    /// # Returns
+    /// - `Some(Vec<Arc<Chunk>>)`: The chunks to send between a batch start and a batch finished.
+    /// - `None`: Nothing should be sent this tick,
+    ///   either because the queue is empty or the client has not acknowledged enough batches.
+    pub fn next_batch(&mut self) -> Option<Vec<Arc<Chunk>>> {
+        if self.unacknowledged_batches >= self.max_unacknowledged_batches {
+            return None;
+        }
+        self.batch_quota = (self.batch_quota + self.desired_chunks_per_tick)
+            .min(self.desired_chunks_per_tick.max(1.0));
+        if self.batch_quota < 1.0 || self.pending.is_empty() {
+            return None;
+        }
+        let count = (self.batch_quota as usize).min(self.pending.len());
+        let batch: Vec<Arc<Chunk>> = self.pending.drain(..count).collect();
+        self.batch_quota -= count as f32;
+        self.unacknowledged_batches += 1;
+        Some(batch)
+    }
+
+    /// Handles the client's acknowledgement of a batch.
+    ///
+    /// # Parameters
+    /// - `desired_chunks_per_tick`: The rate the client is able to process chunks at.
+    pub fn on_batch_received(&mut self, desired_chunks_per_tick: f32) {
+        self.unacknowledged_batches = (self.unacknowledged_batches - 1).max(0);
+        self.desired_chunks_per_tick = if desired_chunks_per_tick.is_nan() {
+            MIN_CHUNKS_PER_TICK
+        } else {
+            desired_chunks_per_tick.clamp(MIN_CHUNKS_PER_TICK, MAX_CHUNKS_PER_TICK)
+        };
+        if self.unacknowledged_batches == 0 {
+            self.batch_quota = 1.0;
+        }
+        self.max_unacknowledged_batches = MAX_UNACKNOWLEDGED_BATCHES;
+    }
+}
+
+impl Default for ChunkSender {
+    fn default() -> Self {
+        Self::new()
+    }
+}
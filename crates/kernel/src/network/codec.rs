@@ -0,0 +1,116 @@
+use crate::network::compression::decompress_packet;
+use crate::network::connection::Packet;
+use crate::network::encryption::CipherStream;
+use crate::util::io::ReadExt;
+use anyhow::{anyhow, Result};
+use bytes::{Buf, BytesMut};
+use std::io::ErrorKind;
+use tokio::io::AsyncReadExt;
+use tokio::net::tcp::OwnedReadHalf;
+
+/// The largest frame a client may send, the largest length fitting in a 3 bytes VarInt.
+pub const MAX_FRAME_LENGTH: usize = 2097151;
+
+/// Reassembles the length-prefixed frames of the protocol from the bytes read off the socket.
+///
+/// Bytes are appended to [`FrameDecoder::buffer`] as they arrive,
+/// and complete frames are split off with [`FrameDecoder::next_frame`].
+/// A frame may span many reads, and a single read may contain many frames.
+pub struct FrameDecoder {
+    buf: BytesMut,
+}
+
+impl FrameDecoder {
+    pub fn new() -> FrameDecoder {
+        FrameDecoder {
+            buf: BytesMut::with_capacity(4096),
+        }
+    }
+
+    /// Gets the buffer to read the socket into.
+    pub fn buffer(&mut self) -> &mut BytesMut {
+        &mut self.buf
+    }
+
+    /// Splits the next complete frame off the buffer.
+    ///
+    /// # Returns
+    /// - `Ok(Some(BytesMut))`: The body of the frame, without its length.
+    /// - `Ok(None)`: The buffer does not contain a complete frame yet.
+    /// - `Err(anyhow::Error)`: The length of the frame is malformed or exceeds [`MAX_FRAME_LENGTH`].
+    pub fn next_frame(&mut self) -> Result<Option<BytesMut>> {
+        let mut length: i32 = 0;
+        let mut header = 0;
+        loop {
+            let byte = match self.buf.get(header) {
+                Some(byte) => *byte,
+                None => return Ok(None),
+            };
+            length |= (byte as i32 & 0x7F) << (header * 7);
+            header += 1;
+            if byte & 0x80 == 0 {
+                break;
+            }
+            if header == 5 {
+                return Err(anyhow!("Frame length VarInt is longer than 5 bytes"));
+            }
+        }
+        if length <= 0 {
+            return Err(anyhow!("Invalid frame length {}", length));
+        }
+        let length = length as usize;
+        if length > MAX_FRAME_LENGTH {
+            return Err(anyhow!(
+                "Frame of {} bytes exceeds the maximum of {} bytes",
+                length,
+                MAX_FRAME_LENGTH
+            ));
+        }
+        if self.buf.len() < header + length {
+            self.buf.reserve(header + length - self.buf.len());
+            return Ok(None);
+        }
+        self.buf.advance(header);
+        Ok(Some(self.buf.split_to(length)))
+    }
+}
+
+impl Default for FrameDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The reading half of a connection, turning the bytes of the socket into packets.
+pub struct PacketReader {
+    pub stream: CipherStream<OwnedReadHalf>,
+    pub decoder: FrameDecoder,
+}
+
+impl PacketReader {
+    pub fn new(stream: OwnedReadHalf) -> PacketReader {
+        PacketReader {
+            stream: CipherStream::new(stream),
+            decoder: FrameDecoder::new(),
+        }
+    }
+
+    /// Reads the next packet, waiting for more data if no complete frame is buffered.
+    ///
+    /// This is cancel safe, the bytes read so far stay in the frame decoder.
+    pub async fn read_packet(&mut self, compression_threshold: Option<i32>) -> Result<Packet> {
+        loop {
+            if let Some(frame) = self.decoder.next_frame()? {
+                let buf = match compression_threshold {
+                    Some(threshold) => decompress_packet(&frame, threshold).await?,
+                    None => frame.to_vec(),
+                };
+                let id = buf.as_slice().read_var_int().await?;
+                return Ok(Packet { id, data: buf });
+            }
+            if self.stream.read_buf(self.decoder.buffer()).await? == 0 {
+                return Err(std::io::Error::from(ErrorKind::UnexpectedEof).into());
+            }
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use crate::util::io::{ReadExt, WriteExt};
+use anyhow::{anyhow, Result};
+use bytes::Bytes;
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::io::{Read, Write};
+
+/// The maximum size of a decompressed packet accepted from clients, same as vanilla.
+pub const MAX_DECOMPRESSED_LENGTH: i32 = 8388608;
+
+/// A packet frame that has been encoded once and can be written to any connection
+/// using the same compression threshold.
+pub struct CachedFrame {
+    pub packet_id: i32,
+    pub threshold: Option<i32>,
+    pub frame: Bytes,
+}
+
+/// Wraps an encoded packet (packet id followed by its data) into a length-prefixed frame.
+///
+/// # Parameters
+/// - `packet`: The packet id and data.
+/// - `threshold`: The compression threshold of the connection,
+///   `None` if compression is not enabled.
+///
+/// # Returns
+/// The frame ready to be written to the socket.
+/// Packets shorter than the threshold are sent with a data length of 0 and stay uncompressed.
+pub async fn frame_packet(packet: &[u8], threshold: Option<i32>) -> Result<Vec<u8>> {
+    let mut frame = Vec::with_capacity(packet.len() + 10);
+    match threshold {
+        None => {
+            frame.write_var_int(packet.len() as i32).await?;
+            frame.extend_from_slice(packet);
+        }
+        Some(threshold) if packet.len() < threshold as usize => {
+            frame.write_var_int(packet.len() as i32 + 1).await?;
+            frame.write_var_int(0).await?;
+            frame.extend_from_slice(packet);
+        }
+        Some(_) => {
+            let mut encoder =
+                ZlibEncoder::new(Vec::with_capacity(packet.len() / 2), Compression::default());
+            encoder.write_all(packet)?;
+            let compressed = encoder.finish()?;
+            let mut data_length = Vec::with_capacity(5);
+            data_length.write_var_int(packet.len() as i32).await?;
+            frame
+                .write_var_int((data_length.len() + compressed.len()) as i32)
+                .await?;
+            frame.extend_from_slice(&data_length);
+            frame.extend_from_slice(&compressed);
+        }
+    }
+    Ok(frame)
+}
+
+/// Unwraps the body of a compressed frame (everything after the packet length).
+///
+/// # Parameters
+/// - `body`: The data length followed by the (possibly compressed) packet.
+/// - `threshold`: The compression threshold of the connection.
+///
+/// # Returns
+/// The packet id and data.
+///
+/// # Errors
+/// Returns an error if the packet claims to be compressed below the threshold,
+/// is larger than [`MAX_DECOMPRESSED_LENGTH`]
+/// or does not inflate to the announced length.
+pub async fn decompress_packet(body: &[u8], threshold: i32) -> Result<Vec<u8>> {
+    let mut body = body;
+    let data_length = body.read_var_int().await?;
+    if data_length == 0 {
+        return Ok(body.to_vec());
+    }
+    if data_length < threshold {
+        return Err(anyhow!(
+            "Badly compressed packet: size of {} is below the threshold of {}",
+            data_length,
+            threshold
+        ));
+    }
+    if data_length > MAX_DECOMPRESSED_LENGTH {
+        return Err(anyhow!(
+            "Badly compressed packet: size of {} is larger than the protocol maximum of {}",
+            data_length,
+            MAX_DECOMPRESSED_LENGTH
+        ));
+    }
+    let mut packet = Vec::with_capacity(data_length as usize);
+    ZlibDecoder::new(body)
+        .take(data_length as u64 + 1)
+        .read_to_end(&mut packet)?;
+    if packet.len() != data_length as usize {
+        return Err(anyhow!(
+            "Badly compressed packet: expected {} bytes, got {}",
+            data_length,
+            packet.len()
+        ));
+    }
+    Ok(packet)
+}
@@ -1,74 +1,191 @@
 use crate::entity::player::{Player, PlayerUpdate};
-use crate::network::connection::State::{Handshake, Login};
+use crate::entity::tracker::EntityTracker;
+use crate::gameplay::{
+    player_quit, player_update, send_block_updates, send_chunk_batch, track_entities,
+};
+use crate::network::auth::ProfileProperty;
+use crate::network::chunk_batch::ChunkSender;
+use crate::network::codec::PacketReader;
+use crate::network::connection::State::{Handshake, Login, Status};
+use crate::network::forwarding::{parse_legacy_forwarding, ForwardingMode, FORWARDING_MODE};
+use crate::network::legacy_ping::{is_legacy_ping, respond_legacy_ping};
+use crate::network::outbound::{write_loop, ConnectionHandle};
+use crate::network::packet::s2c::disconnect::DisconnectS2C;
+use crate::network::packet::s2c::keep_alive::KeepAliveS2C;
 use crate::network::packet::*;
-use crate::util::io::{ReadExt, WriteExt};
-use crate::PROTOCOL_VERSION;
+use crate::util::io::ReadExt;
+use crate::util::text::TextComponent;
+use crate::{MINECRAFT_VERSION, PROTOCOL_VERSION};
 use anyhow::anyhow;
 use anyhow::Result;
+use bytes::Bytes;
 use parking_lot::Mutex;
+use std::io::ErrorKind;
+use std::net::SocketAddr;
 use std::pin::{pin, Pin};
 use std::sync::Arc;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use std::time::{Duration, Instant};
+use tokio::io::AsyncReadExt;
+use tokio::net::tcp::OwnedReadHalf;
 use tokio::net::TcpStream;
 use tokio::sync::mpsc::UnboundedReceiver;
 use tokio::task::yield_now;
+use tokio::time::MissedTickBehavior::Skip;
+use tracing::debug;
 
-pub(crate) async fn read_socket(socket: &mut TcpStream) -> Result<()> {
+/// How often a keep alive is sent, and how long the client has to answer it.
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+pub(crate) async fn read_socket(mut socket: TcpStream) -> Result<()> {
     socket.set_nodelay(true)?;
-    let mut connection = Connection::new(socket);
+    if is_legacy_ping(&socket).await? {
+        return respond_legacy_ping(&mut socket).await;
+    }
+    let address = socket.peer_addr().ok();
+    let (read, write) = socket.into_split();
+    let (handle, receivers) = ConnectionHandle::new();
+    tokio::spawn(async move {
+        if let Err(err) = write_loop(write, receivers.outbound, receivers.kick).await {
+            debug!("Error in writing to connection: {:?}", err);
+        }
+    });
+    let mut connection = Connection::new(read, address, handle, receivers.updates);
+    let result = match handle_connection(&mut connection).await {
+        // The client closed the connection.
+        Err(err)
+            if err
+                .downcast_ref::<std::io::Error>()
+                .is_some_and(|err| err.kind() == ErrorKind::UnexpectedEof) =>
+        {
+            Ok(())
+        }
+        Err(err) => {
+            // Refused players are disconnected with their reason before getting here,
+            // so an error is a protocol error: the client only gets a generic reason,
+            // and the error is logged with its details by the caller.
+            if !connection.closed {
+                let reason = TextComponent::text("Network Protocol Error");
+                let _ = connection.disconnect(&reason).await;
+            }
+            Err(err)
+        }
+        Ok(()) => Ok(()),
+    };
+    player_quit(&mut connection).await;
+    connection.handle.close();
+    result
+}
+
+async fn handle_connection(connection: &mut Connection) -> Result<()> {
+    let mut interval = tokio::time::interval(Duration::from_millis(50));
+    interval.set_missed_tick_behavior(Skip);
     loop {
-        let packet = connection.read_packet().await?;
+        if connection.closed {
+            return Ok(());
+        }
+        let threshold = connection.handle.compression_threshold();
+        let packet = tokio::select! {
+            packet = connection.reader.read_packet(threshold) => packet?,
+            Some(update) = connection.recv.recv() => {
+                player_update(connection, update).await?;
+                continue;
+            }
+            _ = interval.tick() => {
+                match connection.state {
+                    State::Configuration => connection.keep_alive().await?,
+                    State::Play => {
+                        connection.keep_alive().await?;
+                        connection.chat_spam = (connection.chat_spam - 1).max(0);
+                        send_block_updates(connection).await?;
+                        send_chunk_batch(connection).await?;
+                        track_entities(connection).await?;
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+        };
         match connection.state {
             Handshake => {
                 let mut data: Pin<&mut Box<&[u8]>> = pin!(Box::new(&*packet.data));
                 data.read_var_int().await?;
                 let protocol_version = data.read_var_int().await?;
-                if protocol_version != PROTOCOL_VERSION {
-                    return Err(anyhow!("Invalid protocol version {}", protocol_version));
-                }
-                let _server_addr = data.read_str().await?;
+                let server_addr = data.read_str().await?;
                 let _server_port = data.read_u16().await?;
                 let next_state = data.read_var_int().await?;
-                if next_state != 2 {
+                match next_state {
+                    1 => connection.state = Status,
+                    2 => {
+                        connection.state = Login;
+                        if protocol_version != PROTOCOL_VERSION {
+                            let reason = if protocol_version < PROTOCOL_VERSION {
+                                format!("Outdated client! Please use {}", MINECRAFT_VERSION)
+                            } else {
+                                format!("Outdated server! I'm still on {}", MINECRAFT_VERSION)
+                            };
+                            return connection.disconnect(&TextComponent::text(reason)).await;
+                        }
+                        if *FORWARDING_MODE == ForwardingMode::Legacy {
+                            let Some(forwarded) = parse_legacy_forwarding(&server_addr)? else {
+                                let reason = TextComponent::text("If you wish to use IP forwarding, please enable it in your BungeeCord config as well!");
+                                return connection.disconnect(&reason).await;
+                            };
+                            let port = connection.address.map_or(0, |address| address.port());
+                            connection.address = Some(SocketAddr::new(forwarded.address, port));
+                            connection.uuid = Some(forwarded.uuid.as_u128());
+                            connection.properties = forwarded.properties;
+                        }
+                    }
+                    _ => return Err(anyhow!("Invalid next state {}", next_state)),
+                }
+            }
+            Status => {
+                if let Some(decoder) = STATUS_DECODERS.load().get(&packet.id) {
+                    let mut buf = &*packet.data;
+                    buf.read_var_int().await?;
+                    decoder.decode(connection, buf).await?;
+                }
+                // The ping request is the last packet of a server list ping.
+                if packet.id == 0x01 {
                     return Ok(());
                 }
-                connection.state = Login
             }
             Login => {
                 if let Some(decoder) = LOGIN_DECODERS.load().get(&packet.id) {
                     let mut buf = &*packet.data;
                     buf.read_var_int().await?;
-                    decoder.decode(&mut connection, buf).await?;
+                    decoder.decode(connection, buf).await?;
                 }
             }
             State::Configuration => {
                 if let Some(decoder) = CONFIGURATION_DECODERS.load().get(&packet.id) {
                     let mut buf = &*packet.data;
                     buf.read_var_int().await?;
-                    decoder.decode(&mut connection, buf).await?;
+                    decoder.decode(connection, buf).await?;
                 }
             }
             State::Play => {
                 if let Some(decoder) = PLAY_DECODERS.load().get(&packet.id) {
                     let mut buf = &*packet.data;
                     buf.read_var_int().await?;
-                    decoder.decode(&mut connection, buf).await?;
+                    decoder.decode(connection, buf).await?;
                 }
             }
         }
         yield_now().await;
-        if let Some(recv) = &mut connection.recv {
-            while let Ok(_update) = &recv.try_recv() {
-                // TODO
-            }
-        }
     }
 }
-pub struct Connection<'a> {
-    pub stream: &'a mut TcpStream,
+pub struct Connection {
+    pub reader: PacketReader,
+    /// The handle used to send packets, shared with the player once it joins.
+    pub handle: ConnectionHandle,
     pub state: State,
+    pub address: Option<SocketAddr>,
     pub username: Option<String>,
     pub uuid: Option<u128>,
+    pub properties: Vec<ProfileProperty>,
+    pub verify_token: Option<[u8; 4]>,
+    pub forwarding_message_id: Option<i32>,
     pub locale: Option<String>,
     pub view_distance: Option<i8>,
     pub chat_mode: Option<ChatMode>,
@@ -79,9 +196,21 @@ pub struct Connection<'a> {
     pub allow_server_listings: Option<bool>,
     pub player_eid: Option<i32>,
     pub player: Option<Arc<Mutex<Player>>>,
-    pub recv: Option<UnboundedReceiver<PlayerUpdate>>,
+    pub recv: UnboundedReceiver<PlayerUpdate>,
+    pub chunk_sender: ChunkSender,
+    pub entity_tracker: EntityTracker,
+    /// The id of the keep alive waiting for an answer.
+    pub keep_alive_id: Option<i64>,
+    pub keep_alive_time: Instant,
+    /// The round-trip time in milliseconds, averaged over the keep alives.
+    pub latency: i32,
+    /// Counts the recent chat messages, decreasing every tick.
+    pub chat_spam: i32,
+    /// Whether the connection was closed by the server.
+    pub closed: bool,
 }
 
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum ChatMode {
     Enabled,
     CommandsOnly,
@@ -91,13 +220,23 @@ pub enum MainHand {
     Left,
     Right,
 }
-impl Connection<'_> {
-    pub(crate) fn new(stream: &mut TcpStream) -> Connection<'_> {
+impl Connection {
+    pub(crate) fn new(
+        stream: OwnedReadHalf,
+        address: Option<SocketAddr>,
+        handle: ConnectionHandle,
+        recv: UnboundedReceiver<PlayerUpdate>,
+    ) -> Connection {
         Connection {
-            stream,
+            reader: PacketReader::new(stream),
+            handle,
+            address,
             state: Handshake,
             username: None,
             uuid: None,
+            properties: Vec::new(),
+            verify_token: None,
+            forwarding_message_id: None,
             locale: None,
             view_distance: None,
             chat_mode: None,
@@ -108,24 +247,60 @@ impl Connection<'_> {
             allow_server_listings: None,
             player: None,
             player_eid: None,
-            recv: None,
+            recv,
+            chunk_sender: ChunkSender::new(),
+            entity_tracker: EntityTracker::new(),
+            keep_alive_id: None,
+            keep_alive_time: Instant::now(),
+            latency: 0,
+            chat_spam: 0,
+            closed: false,
         }
     }
-    pub async fn send_packet<D: Encode>(&mut self, data: &D) -> Result<()> {
-        let mut buf = Vec::new();
-        buf.write_var_int(data.get_id()).await?;
-        data.encode(self, &mut buf).await?;
-        self.stream.write_var_int(buf.len() as i32).await?;
-        self.stream.write_all(&buf).await?;
+    /// Queues a packet, waiting if the client is behind on reading.
+    pub async fn send_packet<D: Encode>(&self, data: &D) -> Result<()> {
+        self.handle.send_packet(data).await
+    }
+    /// Kicks the client with a reason and closes the connection.
+    ///
+    /// # Parameters
+    /// - `reason`: The reason shown to the player on the disconnection screen.
+    pub async fn disconnect(&mut self, reason: &TextComponent) -> Result<()> {
+        if let Login | State::Configuration | State::Play = self.state {
+            self.send_packet(&DisconnectS2C {
+                reason,
+                state: self.state,
+            })
+            .await?;
+        }
+        self.handle.close();
+        self.closed = true;
         Ok(())
     }
-    async fn read_packet(&mut self) -> Result<Packet> {
-        self.stream.readable().await?;
-        let length = self.stream.read_var_int().await?;
-        let mut buf = Vec::with_capacity(length as usize);
-        self.stream.read_buf(&mut buf).await?;
-        let id = buf.as_slice().read_var_int().await?;
-        Ok(Packet { id, data: buf })
+    /// Sends a keep alive, or disconnects the client if it did not answer the previous one.
+    ///
+    /// Called every tick in the configuration and play states.
+    async fn keep_alive(&mut self) -> Result<()> {
+        if self.keep_alive_time.elapsed() < KEEP_ALIVE_INTERVAL {
+            return Ok(());
+        }
+        if self.keep_alive_id.is_some() {
+            return self.disconnect(&TextComponent::text("Timed out")).await;
+        }
+        let id = fastrand::i64(..);
+        self.keep_alive_id = Some(id);
+        self.keep_alive_time = Instant::now();
+        self.send_packet(&KeepAliveS2C {
+            id,
+            state: self.state,
+        })
+        .await
+    }
+    /// Queues a frame that has already been built by
+    /// [`frame_packet`](crate::network::compression::frame_packet)
+    /// for this connection's compression threshold.
+    pub async fn send_frame(&self, frame: Bytes) -> Result<()> {
+        self.handle.send_frame(frame).await
     }
 }
 
@@ -133,8 +308,10 @@ pub struct Packet {
     pub id: i32,
     pub data: Vec<u8>,
 }
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum State {
     Handshake,
+    Status,
     Login,
     Configuration,
     Play,
@@ -0,0 +1,129 @@
+use aes::Aes128;
+use anyhow::{anyhow, Result};
+use cfb8::cipher::generic_array::GenericArray;
+use cfb8::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use cfb8::{Decryptor, Encryptor};
+use std::io::Error;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// A stream that transparently applies the protocol's AES/CFB8 encryption once it is enabled.
+///
+/// Before [`CipherStream::enable_encryption`] is called, all data passes through unchanged.
+pub struct CipherStream<S> {
+    inner: S,
+    encryptor: Option<Encryptor<Aes128>>,
+    decryptor: Option<Decryptor<Aes128>>,
+    pending: Vec<u8>,
+}
+
+impl<S> CipherStream<S> {
+    pub fn new(inner: S) -> CipherStream<S> {
+        CipherStream {
+            inner,
+            encryptor: None,
+            decryptor: None,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Enables encryption in both directions.
+    ///
+    /// # Parameters
+    /// - `shared_secret`: The 16 bytes secret sent by the client,
+    ///   used as both the key and the initial vector.
+    ///
+    /// # Errors
+    /// Returns an error if the secret is not 16 bytes long or encryption is already enabled.
+    pub fn enable_encryption(&mut self, shared_secret: &[u8]) -> Result<()> {
+        if self.encryptor.is_some() {
+            return Err(anyhow!("Encryption is already enabled"));
+        }
+        self.encryptor = Some(
+            Encryptor::new_from_slices(shared_secret, shared_secret)
+                .map_err(|_| anyhow!("Invalid shared secret length"))?,
+        );
+        self.decryptor = Some(
+            Decryptor::new_from_slices(shared_secret, shared_secret)
+                .map_err(|_| anyhow!("Invalid shared secret length"))?,
+        );
+        Ok(())
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.encryptor.is_some()
+    }
+
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+}
+
+impl<S: AsyncWrite + Unpin> CipherStream<S> {
+    fn poll_pending(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        while !self.pending.is_empty() {
+            let written = ready!(Pin::new(&mut self.inner).poll_write(cx, &self.pending))?;
+            if written == 0 {
+                return Poll::Ready(Err(Error::from(std::io::ErrorKind::WriteZero)));
+            }
+            self.pending.drain(..written);
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for CipherStream<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        let before = buf.filled().len();
+        ready!(Pin::new(&mut this.inner).poll_read(cx, buf))?;
+        if let Some(decryptor) = &mut this.decryptor {
+            for byte in buf.filled_mut()[before..].chunks_mut(1) {
+                decryptor.decrypt_block_mut(GenericArray::from_mut_slice(byte));
+            }
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for CipherStream<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, Error>> {
+        let this = self.get_mut();
+        if this.encryptor.is_none() {
+            return Pin::new(&mut this.inner).poll_write(cx, buf);
+        }
+        ready!(this.poll_pending(cx))?;
+        let encryptor = this.encryptor.as_mut().unwrap();
+        let start = this.pending.len();
+        this.pending.extend_from_slice(buf);
+        for byte in this.pending[start..].chunks_mut(1) {
+            encryptor.encrypt_block_mut(GenericArray::from_mut_slice(byte));
+        }
+        // The data is accepted even if the socket is busy, the rest is written on the next flush.
+        if let Poll::Ready(Err(err)) = this.poll_pending(cx) {
+            return Poll::Ready(Err(err));
+        }
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        let this = self.get_mut();
+        ready!(this.poll_pending(cx))?;
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        let this = self.get_mut();
+        ready!(this.poll_pending(cx))?;
+        Pin::new(&mut this.inner).poll_shutdown(cx)
+    }
+}
@@ -0,0 +1,126 @@
+use crate::config::{FORWARDING_SECRET, PLAYER_INFO_FORWARDING_MODE};
+use crate::network::auth::ProfileProperty;
+use crate::util::io::ReadExt;
+use anyhow::{anyhow, Result};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::net::IpAddr;
+use std::sync::LazyLock;
+use tokio::io::AsyncReadExt;
+use uuid::Uuid;
+
+/// The login plugin channel used by Velocity to forward player information.
+pub const VELOCITY_CHANNEL: &str = "velocity:player_info";
+/// The version of Velocity's modern forwarding requested from the proxy.
+///
+/// Version 1 carries the address, profile and properties, without chat session keys.
+pub const VELOCITY_FORWARDING_VERSION: u8 = 1;
+
+pub static FORWARDING_MODE: LazyLock<ForwardingMode> =
+    LazyLock::new(|| match *PLAYER_INFO_FORWARDING_MODE {
+        "none" => ForwardingMode::None,
+        "legacy" | "bungeecord" => ForwardingMode::Legacy,
+        "modern" | "velocity" => ForwardingMode::Modern,
+        mode => panic!("Unknown player info forwarding mode: {}", mode),
+    });
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ForwardingMode {
+    /// Players connect directly to the server.
+    None,
+    /// BungeeCord's IP forwarding, appended to the server address of the handshake.
+    Legacy,
+    /// Velocity's modern forwarding, sent through a login plugin message signed with a secret.
+    Modern,
+}
+
+/// Player information forwarded by a proxy.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForwardedPlayer {
+    pub address: IpAddr,
+    pub uuid: Uuid,
+    pub username: Option<String>,
+    pub properties: Vec<ProfileProperty>,
+}
+
+/// Parses the server address of a handshake sent by BungeeCord with IP forwarding enabled.
+///
+/// The address has the format `host\0ip\0uuid\0properties`,
+/// where the properties are an optional JSON array.
+///
+/// # Returns
+/// - `Ok(Some(ForwardedPlayer))`: The forwarded player information.
+/// - `Ok(None)`: The address does not contain forwarded information,
+///   which means the player either bypassed the proxy or IP forwarding is disabled on it.
+/// - `Err(anyhow::Error)`: An error if the forwarded information is malformed.
+pub fn parse_legacy_forwarding(server_addr: &str) -> Result<Option<ForwardedPlayer>> {
+    let parts: Vec<&str> = server_addr.split('\0').collect();
+    if parts.len() < 3 {
+        return Ok(None);
+    }
+    let properties = match parts.get(3) {
+        Some(properties) => serde_json::from_str(properties)?,
+        None => Vec::new(),
+    };
+    Ok(Some(ForwardedPlayer {
+        address: parts[1].parse()?,
+        uuid: Uuid::parse_str(parts[2]).map_err(|_| anyhow!("Invalid forwarded UUID"))?,
+        username: None,
+        properties,
+    }))
+}
+
+/// Verifies and parses the response to Velocity's player info request.
+///
+/// # Parameters
+/// - `data`: The data of the login plugin response,
+///   an HMAC-SHA256 signature followed by the signed player information.
+///
+/// # Returns
+/// - `Ok(Some(ForwardedPlayer))`: The forwarded player information.
+/// - `Ok(None)`: The signature does not match the configured forwarding secret.
+/// - `Err(anyhow::Error)`: An error if the data is malformed.
+pub async fn parse_modern_forwarding(data: &[u8]) -> Result<Option<ForwardedPlayer>> {
+    if data.len() < 32 {
+        return Err(anyhow!("Invalid Velocity forwarding data"));
+    }
+    let (signature, mut data) = data.split_at(32);
+    let mut mac = Hmac::<Sha256>::new_from_slice(FORWARDING_SECRET.as_bytes())?;
+    mac.update(data);
+    if mac.verify_slice(signature).is_err() {
+        return Ok(None);
+    }
+
+    let version = data.read_var_int().await?;
+    if version < VELOCITY_FORWARDING_VERSION as i32 {
+        return Err(anyhow!(
+            "Unsupported Velocity forwarding version {}",
+            version
+        ));
+    }
+    let address = data.read_str().await?.parse()?;
+    let uuid = Uuid::from_u128(data.read_u128().await?);
+    let username = data.read_str().await?;
+    let count = data.read_var_int().await?;
+    let mut properties = Vec::with_capacity(count.max(0) as usize);
+    for _ in 0..count {
+        let name = data.read_str().await?;
+        let value = data.read_str().await?;
+        let signature = if data.read_bool().await? {
+            Some(data.read_str().await?)
+        } else {
+            None
+        };
+        properties.push(ProfileProperty {
+            name,
+            value,
+            signature,
+        });
+    }
+    Ok(Some(ForwardedPlayer {
+        address,
+        uuid,
+        username: Some(username),
+        properties,
+    }))
+}
@@ -0,0 +1,58 @@
+use crate::config::{MAX_PLAYERS, MOTD};
+use crate::{MINECRAFT_VERSION, PROTOCOL_VERSION, WORLD};
+use anyhow::Result;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use tokio::time::{sleep, Duration, Instant};
+
+/// How long to wait for the first three bytes of a connection starting like a legacy ping.
+const PEEK_TIMEOUT: Duration = Duration::from_millis(500);
+/// How long to wait between two peeks of the first bytes.
+const PEEK_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Checks whether the client opened the connection with a legacy (pre-Netty) server list ping.
+///
+/// Legacy pings start with `0xFE 0x01`, followed by `0xFA` since 1.6.
+/// A modern handshake only starts with the same bytes if its frame is exactly 254 bytes long,
+/// in which case the third byte is the handshake packet id `0x00`.
+///
+/// The bytes may arrive apart, so the stream is peeked until the three are there,
+/// or for [`PEEK_TIMEOUT`], as the oldest clients only send `0xFE`.
+pub(crate) async fn is_legacy_ping(stream: &TcpStream) -> Result<bool> {
+    let mut head = [0u8; 3];
+    let deadline = Instant::now() + PEEK_TIMEOUT;
+    loop {
+        let len = stream.peek(&mut head).await?;
+        let legacy = matches!(&head[..len], [0xFE] | [0xFE, 0x01] | [0xFE, 0x01, 0xFA]);
+        if !legacy || len == head.len() || Instant::now() >= deadline {
+            return Ok(legacy);
+        }
+        // A peek returns the bytes already received without waiting for more.
+        sleep(PEEK_INTERVAL).await;
+    }
+}
+
+/// Answers a legacy server list ping with a kick packet carrying the server status.
+///
+/// The response uses the `§1` format understood by 1.4 - 1.6 clients and most monitoring tools:
+/// protocol version, game version, MOTD, online players and max players separated by `\0`.
+pub(crate) async fn respond_legacy_ping(stream: &mut TcpStream) -> Result<()> {
+    let response = format!(
+        "§1\0{}\0{}\0{}\0{}\0{}",
+        PROTOCOL_VERSION,
+        MINECRAFT_VERSION,
+        *MOTD,
+        WORLD.online_players(),
+        *MAX_PLAYERS
+    );
+    let chars: Vec<u16> = response.encode_utf16().collect();
+    let mut buf = Vec::with_capacity(3 + chars.len() * 2);
+    buf.push(0xFF);
+    buf.extend_from_slice(&(chars.len() as u16).to_be_bytes());
+    for c in chars {
+        buf.extend_from_slice(&c.to_be_bytes());
+    }
+    stream.write_all(&buf).await?;
+    stream.flush().await?;
+    Ok(())
+}
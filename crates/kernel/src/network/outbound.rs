@@ -0,0 +1,238 @@
+use crate::entity::player::PlayerUpdate;
+use crate::network::compression::frame_packet;
+use crate::network::encryption::CipherStream;
+use crate::network::packet::Encode;
+use crate::util::io::WriteExt;
+use anyhow::{anyhow, Result};
+use bytes::Bytes;
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncWrite, AsyncWriteExt, BufWriter};
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::mpsc::{
+    channel, unbounded_channel, Receiver, Sender, UnboundedReceiver, UnboundedSender,
+};
+use tokio::sync::Notify;
+use tokio::time::timeout;
+
+/// How many frames may wait for the writer before the client is considered too slow.
+pub const OUTBOUND_QUEUE_CAPACITY: usize = 4096;
+/// How long the connection's own task waits for room in a full queue.
+const SEND_TIMEOUT: Duration = Duration::from_secs(30);
+/// How long the writer waits for the client to read the frames queued at once.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// An item of the outbound queue, written to the socket in order.
+pub enum Outbound {
+    /// A frame built by [`frame_packet`] for the connection's compression threshold.
+    Frame(Bytes),
+    /// Enables encryption for all the frames after this one.
+    EnableEncryption(Vec<u8>),
+    /// Writes the remaining frames and closes the socket.
+    Close,
+}
+
+/// A cheap, cloneable handle to send packets to a connection from any task.
+///
+/// Frames are queued and written by the connection's writer task,
+/// which flushes everything queued at once.
+/// The queue is bounded: [`ConnectionHandle::send_frame`] waits for room,
+/// while [`ConnectionHandle::try_send_frame`] kicks a client that fell too far behind.
+#[derive(Clone)]
+pub struct ConnectionHandle {
+    sender: Sender<Outbound>,
+    updates: UnboundedSender<PlayerUpdate>,
+    kick: Arc<Notify>,
+    compression_threshold: Arc<AtomicI32>,
+}
+
+/// The receiving ends of a [`ConnectionHandle`].
+pub struct ConnectionReceivers {
+    pub outbound: Receiver<Outbound>,
+    pub updates: UnboundedReceiver<PlayerUpdate>,
+    pub kick: Arc<Notify>,
+}
+
+impl ConnectionHandle {
+    pub fn new() -> (ConnectionHandle, ConnectionReceivers) {
+        let (sender, outbound) = channel(OUTBOUND_QUEUE_CAPACITY);
+        let (updates, updates_receiver) = unbounded_channel();
+        let kick = Arc::new(Notify::new());
+        (
+            ConnectionHandle {
+                sender,
+                updates,
+                kick: kick.clone(),
+                compression_threshold: Arc::new(AtomicI32::new(-1)),
+            },
+            ConnectionReceivers {
+                outbound,
+                updates: updates_receiver,
+                kick,
+            },
+        )
+    }
+
+    pub fn compression_threshold(&self) -> Option<i32> {
+        match self.compression_threshold.load(Ordering::Acquire) {
+            threshold if threshold < 0 => None,
+            threshold => Some(threshold),
+        }
+    }
+
+    pub fn set_compression_threshold(&self, threshold: Option<i32>) {
+        self.compression_threshold
+            .store(threshold.unwrap_or(-1), Ordering::Release);
+    }
+
+    /// Encodes a packet into a frame for the connection's compression threshold.
+    pub async fn encode<D: Encode>(&self, packet: &D) -> Result<Bytes> {
+        let mut buf = Vec::new();
+        buf.write_var_int(packet.get_id()).await?;
+        packet.encode(&mut buf).await?;
+        Ok(Bytes::from(
+            frame_packet(&buf, self.compression_threshold()).await?,
+        ))
+    }
+
+    /// Queues a packet, waiting for room if the queue is full.
+    pub async fn send_packet<D: Encode>(&self, packet: &D) -> Result<()> {
+        let frame = self.encode(packet).await?;
+        self.send_frame(frame).await
+    }
+
+    /// Queues a packet without waiting, kicking the client if its queue is full.
+    pub async fn try_send_packet<D: Encode>(&self, packet: &D) -> Result<()> {
+        let frame = self.encode(packet).await?;
+        self.try_send_frame(frame)
+    }
+
+    /// Queues a frame, waiting for room if the queue is full.
+    ///
+    /// # Errors
+    /// Returns an error if the connection is closed
+    /// or the client did not read anything for [`SEND_TIMEOUT`].
+    pub async fn send_frame(&self, frame: Bytes) -> Result<()> {
+        match timeout(SEND_TIMEOUT, self.sender.send(Outbound::Frame(frame))).await {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(_)) => Err(anyhow!("Connection is closed")),
+            Err(_) => {
+                self.kick.notify_one();
+                Err(anyhow!("Timed out waiting for the outbound queue"))
+            }
+        }
+    }
+
+    /// Queues a frame without waiting.
+    ///
+    /// # Errors
+    /// Returns an error if the connection is closed or its queue is full,
+    /// in which case the connection is closed without writing the queued frames.
+    pub fn try_send_frame(&self, frame: Bytes) -> Result<()> {
+        match self.sender.try_send(Outbound::Frame(frame)) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(_)) => {
+                self.kick.notify_one();
+                Err(anyhow!("Outbound queue of the connection is full"))
+            }
+            Err(TrySendError::Closed(_)) => Err(anyhow!("Connection is closed")),
+        }
+    }
+
+    /// Sends an update to be handled by the task reading the player's connection.
+    pub fn send_update(&self, update: PlayerUpdate) -> Result<()> {
+        self.updates
+            .send(update)
+            .map_err(|_| anyhow!("Connection is closed"))
+    }
+
+    /// Enables encryption after the frames queued so far.
+    pub async fn enable_encryption(&self, shared_secret: &[u8]) -> Result<()> {
+        self.sender
+            .send(Outbound::EnableEncryption(shared_secret.to_vec()))
+            .await
+            .map_err(|_| anyhow!("Connection is closed"))
+    }
+
+    /// Closes the connection once the frames queued so far are written.
+    pub fn close(&self) {
+        if self.sender.try_send(Outbound::Close).is_err() {
+            self.kick.notify_one();
+        }
+    }
+}
+
+/// Writes the frames queued for a connection to its socket until it is closed.
+///
+/// Frames are buffered and flushed once the queue is empty,
+/// so packets sent together leave in as few writes as possible.
+/// Every write is raced against a kick, which drops the socket without flushing,
+/// and fails if the client does not read the frames within [`WRITE_TIMEOUT`].
+pub(crate) async fn write_loop<S: AsyncWrite + Unpin>(
+    stream: S,
+    mut outbound: Receiver<Outbound>,
+    kick: Arc<Notify>,
+) -> Result<()> {
+    let mut stream = BufWriter::new(CipherStream::new(stream));
+    loop {
+        let item = tokio::select! {
+            biased;
+            // The client fell behind, the queued frames are dropped.
+            _ = kick.notified() => return Ok(()),
+            item = outbound.recv() => match item {
+                Some(item) => item,
+                // Every handle was dropped.
+                None => break,
+            },
+        };
+        let closed = tokio::select! {
+            biased;
+            _ = kick.notified() => return Ok(()),
+            result = timeout(WRITE_TIMEOUT, write_batch(&mut stream, &mut outbound, item)) => {
+                result.map_err(|_| anyhow!("Timed out writing to the connection"))??
+            }
+        };
+        if closed {
+            return Ok(());
+        }
+    }
+    tokio::select! {
+        biased;
+        _ = kick.notified() => Ok(()),
+        result = timeout(WRITE_TIMEOUT, stream.shutdown()) => {
+            Ok(result.map_err(|_| anyhow!("Timed out closing the connection"))??)
+        }
+    }
+}
+
+/// Writes an item and the ones queued after it, then flushes them.
+///
+/// # Returns
+/// Whether the connection was closed.
+async fn write_batch<S: AsyncWrite + Unpin>(
+    stream: &mut BufWriter<CipherStream<S>>,
+    outbound: &mut Receiver<Outbound>,
+    mut item: Outbound,
+) -> Result<bool> {
+    loop {
+        match item {
+            Outbound::Frame(frame) => stream.write_all(&frame).await?,
+            Outbound::EnableEncryption(shared_secret) => {
+                stream.flush().await?;
+                stream.get_mut().enable_encryption(&shared_secret)?;
+            }
+            Outbound::Close => {
+                stream.flush().await?;
+                stream.shutdown().await?;
+                return Ok(true);
+            }
+        }
+        item = match outbound.try_recv() {
+            Ok(item) => item,
+            Err(_) => break,
+        };
+    }
+    stream.flush().await?;
+    Ok(false)
+}
@@ -11,22 +11,30 @@ pub mod c2s;
 pub mod s2c;
 
 pub trait Encode {
-    fn encode<W: AsyncWrite + Unpin>(
-        &self,
-        connection: &mut Connection<'_>,
-        buf: &mut W,
-    ) -> impl Future<Output = Result<()>>;
+    fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> impl Future<Output = Result<()>>;
     fn get_id(&self) -> i32;
 }
 
 #[async_trait]
 pub trait Decode: Send + Sync {
-    async fn decode(&self, connection: &mut Connection<'_>, data: &[u8]) -> Result<()>;
+    async fn decode(&self, connection: &mut Connection, data: &[u8]) -> Result<()>;
 }
 
+pub static STATUS_DECODERS: LazyLock<ArcSwap<HashMap<i32, Box<dyn Decode>>>> =
+    LazyLock::new(|| {
+        let mut map: HashMap<i32, Box<dyn Decode>> = HashMap::new();
+        map.insert(0x00, Box::new(c2s::status_request::StatusRequest));
+        map.insert(0x01, Box::new(c2s::ping_request::PingRequest));
+        ArcSwap::new(Arc::new(map))
+    });
 pub static LOGIN_DECODERS: LazyLock<ArcSwap<HashMap<i32, Box<dyn Decode>>>> = LazyLock::new(|| {
     let mut map: HashMap<i32, Box<dyn Decode>> = HashMap::new();
     map.insert(0x00, Box::new(c2s::login_start::LoginStart));
+    map.insert(0x01, Box::new(c2s::encryption_response::EncryptionResponse));
+    map.insert(
+        0x02,
+        Box::new(c2s::login_plugin_response::LoginPluginResponse),
+    );
     map.insert(0x03, Box::new(c2s::login_acknowledged::LoginAcknowledged));
     ArcSwap::new(Arc::new(map))
 });
@@ -38,6 +46,7 @@ pub static CONFIGURATION_DECODERS: LazyLock<ArcSwap<HashMap<i32, Box<dyn Decode>
             0x03,
             Box::new(c2s::acknowledge_finish_configuration::AcknowledgeFinishConfiguration),
         );
+        map.insert(0x04, Box::new(c2s::keep_alive::KeepAlive));
         map.insert(0x07, Box::new(c2s::known_packs_c2s::ServerBoundKnownPacks));
         ArcSwap::new(Arc::new(map))
     });
@@ -47,5 +56,30 @@ pub static PLAY_DECODERS: LazyLock<ArcSwap<HashMap<i32, Box<dyn Decode>>>> = Laz
         0x00,
         Box::new(c2s::confirm_teleportation::ConfirmTeleportation),
     );
+    map.insert(
+        0x03,
+        Box::new(c2s::message_acknowledgement::MessageAcknowledgement),
+    );
+    map.insert(0x04, Box::new(c2s::chat_command::ChatCommand));
+    map.insert(0x05, Box::new(c2s::signed_chat_command::SignedChatCommand));
+    map.insert(0x06, Box::new(c2s::chat_message::ChatMessage));
+    map.insert(0x07, Box::new(c2s::player_session::PlayerSession));
+    map.insert(
+        0x08,
+        Box::new(c2s::chunk_batch_received::ChunkBatchReceived),
+    );
+    map.insert(0x0A, Box::new(c2s::client_info::ClientInformation));
+    map.insert(
+        0x0B,
+        Box::new(c2s::command_suggestions_request::CommandSuggestionsRequest),
+    );
+    map.insert(0x18, Box::new(c2s::keep_alive::KeepAlive));
+    map.insert(0x1A, Box::new(c2s::set_player_position::SetPlayerPosition));
+    map.insert(
+        0x1B,
+        Box::new(c2s::set_player_position_and_rotation::SetPlayerPositionAndRotation),
+    );
+    map.insert(0x1C, Box::new(c2s::set_player_rotation::SetPlayerRotation));
+    map.insert(0x1D, Box::new(c2s::set_player_on_ground::SetPlayerOnGround));
     ArcSwap::new(Arc::new(map))
 });
@@ -1,6 +1,22 @@
 pub(crate) mod acknowledge_finish_configuration;
+pub(crate) mod chat_command;
+pub(crate) mod chat_message;
+pub(crate) mod chunk_batch_received;
 pub(crate) mod client_info;
+pub(crate) mod command_suggestions_request;
 pub(crate) mod confirm_teleportation;
+pub(crate) mod encryption_response;
+pub(crate) mod keep_alive;
 pub(crate) mod known_packs_c2s;
 pub(crate) mod login_acknowledged;
+pub(crate) mod login_plugin_response;
 pub(crate) mod login_start;
+pub(crate) mod message_acknowledgement;
+pub(crate) mod ping_request;
+pub(crate) mod player_session;
+pub(crate) mod set_player_on_ground;
+pub(crate) mod set_player_position;
+pub(crate) mod set_player_position_and_rotation;
+pub(crate) mod set_player_rotation;
+pub(crate) mod signed_chat_command;
+pub(crate) mod status_request;
@@ -7,7 +7,7 @@ use async_trait::async_trait;
 pub struct AcknowledgeFinishConfiguration;
 #[async_trait]
 impl crate::network::packet::Decode for AcknowledgeFinishConfiguration {
-    async fn decode(&self, connection: &mut Connection<'_>, _data: &[u8]) -> Result<()> {
+    async fn decode(&self, connection: &mut Connection, _data: &[u8]) -> Result<()> {
         connection.state = Play;
         player_join(connection).await
     }
@@ -0,0 +1,16 @@
+use crate::gameplay::command::player_command;
+use crate::network::connection::Connection;
+use crate::network::packet::Decode;
+use crate::util::io::ReadExt;
+use anyhow::Result;
+use async_trait::async_trait;
+
+pub struct ChatCommand;
+
+#[async_trait]
+impl Decode for ChatCommand {
+    async fn decode(&self, connection: &mut Connection, mut data: &[u8]) -> Result<()> {
+        let command = data.read_str().await?;
+        player_command(connection, &command, None).await
+    }
+}
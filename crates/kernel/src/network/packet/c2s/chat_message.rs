@@ -0,0 +1,43 @@
+use crate::gameplay::chat::{player_chat, PlayerMessage};
+use crate::network::chat_session::{LastSeenUpdate, MessageSignature};
+use crate::network::connection::Connection;
+use crate::network::packet::Decode;
+use crate::util::io::ReadExt;
+use anyhow::Result;
+use async_trait::async_trait;
+use tokio::io::AsyncReadExt;
+
+pub struct ChatMessage;
+
+#[async_trait]
+impl Decode for ChatMessage {
+    async fn decode(&self, connection: &mut Connection, mut data: &[u8]) -> Result<()> {
+        let message = data.read_str().await?;
+        let timestamp = data.read_i64().await?;
+        let salt = data.read_i64().await?;
+        let signature = if data.read_bool().await? {
+            let mut signature: MessageSignature = [0; 256];
+            data.read_exact(&mut signature).await?;
+            Some(signature)
+        } else {
+            None
+        };
+        let offset = data.read_var_int().await?;
+        let mut acknowledged = [0; 3];
+        data.read_exact(&mut acknowledged).await?;
+        player_chat(
+            connection,
+            PlayerMessage {
+                message,
+                timestamp,
+                salt,
+                signature,
+                last_seen: LastSeenUpdate {
+                    offset,
+                    acknowledged,
+                },
+            },
+        )
+        .await
+    }
+}
@@ -0,0 +1,16 @@
+use crate::network::connection::Connection;
+use crate::network::packet::Decode;
+use anyhow::Result;
+use async_trait::async_trait;
+use tokio::io::AsyncReadExt;
+
+pub struct ChunkBatchReceived;
+
+#[async_trait]
+impl Decode for ChunkBatchReceived {
+    async fn decode(&self, connection: &mut Connection, mut data: &[u8]) -> Result<()> {
+        let chunks_per_tick = data.read_f32().await?;
+        connection.chunk_sender.on_batch_received(chunks_per_tick);
+        Ok(())
+    }
+}
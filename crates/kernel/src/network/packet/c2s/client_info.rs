@@ -6,9 +6,17 @@ use async_trait::async_trait;
 use tokio::io::AsyncReadExt;
 pub struct ClientInformation;
 
+/// Gets the main hand as sent in the entity metadata, the right hand by default.
+pub(crate) fn main_hand_id(connection: &Connection) -> u8 {
+    match connection.main_hand {
+        Some(MainHand::Left) => 0,
+        _ => 1,
+    }
+}
+
 #[async_trait]
 impl Decode for ClientInformation {
-    async fn decode(&self, connection: &mut Connection<'_>, mut data: &[u8]) -> Result<()> {
+    async fn decode(&self, connection: &mut Connection, mut data: &[u8]) -> Result<()> {
         connection.locale = Some(data.read_str().await?);
         connection.view_distance = Some(data.read_i8().await?);
         connection.chat_mode = Some(match data.read_var_int().await? {
@@ -24,6 +32,12 @@ impl Decode for ClientInformation {
         });
         connection.enable_text_filtering = Some(data.read_bool().await?);
         connection.allow_server_listings = Some(data.read_bool().await?);
+        // The settings can change while playing.
+        if let Some(p) = &connection.player {
+            let mut player = p.lock();
+            player.chat_mode = connection.chat_mode.unwrap();
+            player.set_client_settings(connection.skin_parts.unwrap(), main_hand_id(connection));
+        }
         Ok(())
     }
 }
@@ -0,0 +1,17 @@
+use crate::gameplay::command::command_suggestions;
+use crate::network::connection::Connection;
+use crate::network::packet::Decode;
+use crate::util::io::ReadExt;
+use anyhow::Result;
+use async_trait::async_trait;
+
+pub struct CommandSuggestionsRequest;
+
+#[async_trait]
+impl Decode for CommandSuggestionsRequest {
+    async fn decode(&self, connection: &mut Connection, mut data: &[u8]) -> Result<()> {
+        let id = data.read_var_int().await?;
+        let text = data.read_str().await?;
+        command_suggestions(connection, id, &text).await
+    }
+}
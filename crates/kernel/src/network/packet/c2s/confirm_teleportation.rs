@@ -9,7 +9,7 @@ pub struct ConfirmTeleportation;
 
 #[async_trait]
 impl Decode for ConfirmTeleportation {
-    async fn decode(&self, connection: &mut Connection<'_>, mut data: &[u8]) -> Result<()> {
+    async fn decode(&self, connection: &mut Connection, mut data: &[u8]) -> Result<()> {
         let read_teleport_id = data.read_var_int().await?;
         let p = connection.player.clone().ok_or(anyhow!(
             "PacketC2S: ConfirmTeleportation: invalid context: player is undefined"
@@ -0,0 +1,60 @@
+use crate::network::auth::{server_hash, KEY_PAIR, SESSION_SERVER};
+use crate::network::connection::Connection;
+use crate::network::packet::c2s::login_start::finish_login;
+use crate::network::packet::Decode;
+use crate::util::io::ReadExt;
+use crate::util::text::TextComponent;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use tracing::warn;
+
+/// The length of a block encrypted with the 1024-bit key of the server.
+const MAX_ENCRYPTED_LENGTH: usize = 128;
+
+pub struct EncryptionResponse;
+
+#[async_trait]
+impl Decode for EncryptionResponse {
+    async fn decode(&self, connection: &mut Connection, mut data: &[u8]) -> Result<()> {
+        let shared_secret = data.read_byte_array(MAX_ENCRYPTED_LENGTH).await?;
+        let verify_token = data.read_byte_array(MAX_ENCRYPTED_LENGTH).await?;
+        let expected_token = connection.verify_token.take().ok_or(anyhow!(
+            "PacketC2S: EncryptionResponse: invalid context: encryption is not requested"
+        ))?;
+        if KEY_PAIR.decrypt(&verify_token)? != expected_token {
+            return connection
+                .disconnect(&TextComponent::text("Invalid verify token"))
+                .await;
+        }
+        let shared_secret = KEY_PAIR.decrypt(&shared_secret)?;
+        // Anything already buffered was read before the cipher could decrypt it.
+        if !connection.reader.decoder.buffer().is_empty() {
+            return Err(anyhow!("Unexpected data before enabling encryption"));
+        }
+        connection.reader.stream.enable_encryption(&shared_secret)?;
+        connection.handle.enable_encryption(&shared_secret).await?;
+
+        let username = connection.username.clone().ok_or(anyhow!(
+            "PacketC2S: EncryptionResponse: invalid context: username is undefined"
+        ))?;
+        let hash = server_hash(&shared_secret, KEY_PAIR.get_public_key_der());
+        let profile = match SESSION_SERVER.load().has_joined(&username, &hash).await {
+            Ok(Some(profile)) => profile,
+            Ok(None) => {
+                return connection
+                    .disconnect(&TextComponent::text("Failed to verify username!"))
+                    .await
+            }
+            Err(err) => {
+                warn!("Could not verify the username of {}: {:?}", username, err);
+                return connection
+                    .disconnect(&TextComponent::text("Failed to verify username!"))
+                    .await;
+            }
+        };
+        connection.username = Some(profile.name);
+        connection.uuid = Some(profile.id.as_u128());
+        connection.properties = profile.properties;
+        finish_login(connection).await
+    }
+}
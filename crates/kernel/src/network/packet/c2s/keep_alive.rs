@@ -0,0 +1,28 @@
+use crate::network::connection::Connection;
+use crate::network::packet::Decode;
+use crate::util::text::TextComponent;
+use anyhow::Result;
+use async_trait::async_trait;
+use tokio::io::AsyncReadExt;
+
+pub struct KeepAlive;
+
+#[async_trait]
+impl Decode for KeepAlive {
+    async fn decode(&self, connection: &mut Connection, mut data: &[u8]) -> Result<()> {
+        let id = data.read_i64().await?;
+        if connection.keep_alive_id != Some(id) {
+            return connection
+                .disconnect(&TextComponent::text("Timed out"))
+                .await;
+        }
+        connection.keep_alive_id = None;
+        let elapsed = connection.keep_alive_time.elapsed().as_millis() as i32;
+        connection.latency = (connection.latency * 3 + elapsed) / 4;
+        // Sent to the players with the others by the tab list's timer.
+        if let Some(player) = &connection.player {
+            player.lock().latency = connection.latency;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+use crate::network::connection::Connection;
+use crate::network::forwarding::parse_modern_forwarding;
+use crate::network::packet::c2s::login_start::finish_login;
+use crate::network::packet::Decode;
+use crate::util::io::ReadExt;
+use crate::util::text::TextComponent;
+use anyhow::Result;
+use async_trait::async_trait;
+use std::net::SocketAddr;
+
+pub struct LoginPluginResponse;
+
+#[async_trait]
+impl Decode for LoginPluginResponse {
+    async fn decode(&self, connection: &mut Connection, mut data: &[u8]) -> Result<()> {
+        let message_id = data.read_var_int().await?;
+        let successful = data.read_bool().await?;
+        if connection.forwarding_message_id != Some(message_id) {
+            return Ok(());
+        }
+        connection.forwarding_message_id = None;
+        if !successful {
+            let reason = TextComponent::text("This server requires you to connect with Velocity.");
+            return connection.disconnect(&reason).await;
+        }
+        let Some(forwarded) = parse_modern_forwarding(data).await? else {
+            let reason = TextComponent::text("Unable to verify player details");
+            return connection.disconnect(&reason).await;
+        };
+        let port = connection.address.map_or(0, |address| address.port());
+        connection.address = Some(SocketAddr::new(forwarded.address, port));
+        connection.uuid = Some(forwarded.uuid.as_u128());
+        connection.username = forwarded.username;
+        connection.properties = forwarded.properties;
+        finish_login(connection).await
+    }
+}
@@ -1,8 +1,15 @@
+use crate::config::{NETWORK_COMPRESSION_THRESHOLD, ONLINE_MODE};
 use crate::network::connection::Connection;
+use crate::network::forwarding::{
+    ForwardingMode, FORWARDING_MODE, VELOCITY_CHANNEL, VELOCITY_FORWARDING_VERSION,
+};
+use crate::network::packet::s2c::encryption_request::EncryptionRequestS2C;
+use crate::network::packet::s2c::login_plugin_request::LoginPluginRequestS2C;
 use crate::network::packet::s2c::login_success::LoginSuccessS2C;
+use crate::network::packet::s2c::set_compression::SetCompressionS2C;
 use crate::network::packet::Decode;
 use crate::util::io::ReadExt;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use tokio::io::AsyncReadExt;
 
@@ -10,10 +17,58 @@ pub struct LoginStart;
 
 #[async_trait]
 impl Decode for LoginStart {
-    async fn decode(&self, connection: &mut Connection<'_>, mut data: &[u8]) -> Result<()> {
+    async fn decode(&self, connection: &mut Connection, mut data: &[u8]) -> Result<()> {
         connection.username = Some(data.read_str().await?);
-        connection.uuid = Some(data.read_u128().await?);
-        connection.send_packet(&LoginSuccessS2C).await?;
-        Ok(())
+        let uuid = data.read_u128().await?;
+        match *FORWARDING_MODE {
+            // The profile has been forwarded in the handshake.
+            ForwardingMode::Legacy => return finish_login(connection).await,
+            ForwardingMode::Modern => {
+                let message_id = fastrand::i32(..);
+                connection.forwarding_message_id = Some(message_id);
+                connection
+                    .send_packet(&LoginPluginRequestS2C {
+                        message_id,
+                        channel: VELOCITY_CHANNEL,
+                        data: &[VELOCITY_FORWARDING_VERSION],
+                    })
+                    .await?;
+                return Ok(());
+            }
+            ForwardingMode::None => {}
+        }
+        connection.uuid = Some(uuid);
+        if *ONLINE_MODE {
+            let verify_token = fastrand::u32(..).to_be_bytes();
+            connection.verify_token = Some(verify_token);
+            connection
+                .send_packet(&EncryptionRequestS2C { verify_token })
+                .await?;
+            return Ok(());
+        }
+        finish_login(connection).await
     }
 }
+
+/// Enables compression and accepts the login once the player's profile is known.
+pub(crate) async fn finish_login(connection: &mut Connection) -> Result<()> {
+    let threshold = *NETWORK_COMPRESSION_THRESHOLD;
+    if threshold >= 0 {
+        connection
+            .send_packet(&SetCompressionS2C { threshold })
+            .await?;
+        connection.handle.set_compression_threshold(Some(threshold));
+    }
+    let (uuid, username) = match (connection.uuid, &connection.username) {
+        (Some(uuid), Some(username)) => (uuid, username.clone()),
+        _ => return Err(anyhow!("Login start packet is not accepted yet.")),
+    };
+    connection
+        .send_packet(&LoginSuccessS2C {
+            uuid,
+            username: &username,
+            properties: &connection.properties,
+        })
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,25 @@
+use crate::network::connection::Connection;
+use crate::network::packet::Decode;
+use crate::util::io::ReadExt;
+use crate::util::text::TextComponent;
+use anyhow::Result;
+use async_trait::async_trait;
+
+pub struct MessageAcknowledgement;
+
+#[async_trait]
+impl Decode for MessageAcknowledgement {
+    async fn decode(&self, connection: &mut Connection, mut data: &[u8]) -> Result<()> {
+        let offset = data.read_var_int().await?;
+        let result = match &connection.player {
+            Some(player) => player.lock().last_seen.apply_offset(offset),
+            None => return Ok(()),
+        };
+        if result.is_err() {
+            return connection
+                .disconnect(&TextComponent::text("Chat message validation failure"))
+                .await;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,17 @@
+use crate::network::connection::Connection;
+use crate::network::packet::s2c::pong_response::PongResponseS2C;
+use crate::network::packet::Decode;
+use anyhow::Result;
+use async_trait::async_trait;
+use tokio::io::AsyncReadExt;
+
+pub struct PingRequest;
+
+#[async_trait]
+impl Decode for PingRequest {
+    async fn decode(&self, connection: &mut Connection, mut data: &[u8]) -> Result<()> {
+        let payload = data.read_i64().await?;
+        connection.send_packet(&PongResponseS2C { payload }).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+use crate::gameplay::chat::player_session;
+use crate::network::chat_session::ProfilePublicKey;
+use crate::network::connection::Connection;
+use crate::network::packet::Decode;
+use crate::util::io::ReadExt;
+use anyhow::Result;
+use async_trait::async_trait;
+use tokio::io::AsyncReadExt;
+use uuid::Uuid;
+
+/// The maximum length of the encoded profile public key.
+const MAX_KEY_LENGTH: usize = 512;
+/// The maximum length of the signature of the profile public key.
+const MAX_SIGNATURE_LENGTH: usize = 4096;
+
+pub struct PlayerSession;
+
+#[async_trait]
+impl Decode for PlayerSession {
+    async fn decode(&self, connection: &mut Connection, mut data: &[u8]) -> Result<()> {
+        let session_id = Uuid::from_u128(data.read_u128().await?);
+        let expires_at = data.read_i64().await?;
+        let key = data.read_byte_array(MAX_KEY_LENGTH).await?;
+        let signature = data.read_byte_array(MAX_SIGNATURE_LENGTH).await?;
+        player_session(
+            connection,
+            session_id,
+            ProfilePublicKey {
+                expires_at,
+                key,
+                signature,
+            },
+        )
+        .await
+    }
+}
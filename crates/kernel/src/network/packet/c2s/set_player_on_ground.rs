@@ -0,0 +1,21 @@
+use crate::entity::Entity;
+use crate::network::connection::Connection;
+use crate::network::packet::Decode;
+use crate::util::io::ReadExt;
+use anyhow::anyhow;
+use anyhow::Result;
+use async_trait::async_trait;
+
+pub struct SetPlayerOnGround;
+
+#[async_trait]
+impl Decode for SetPlayerOnGround {
+    async fn decode(&self, connection: &mut Connection, mut data: &[u8]) -> Result<()> {
+        let on_ground = data.read_bool().await?;
+        let p = connection.player.clone().ok_or(anyhow!(
+            "PacketC2S: SetPlayerOnGround: invalid context: player is undefined"
+        ))?;
+        p.lock().get_data_mut().on_ground = on_ground;
+        Ok(())
+    }
+}
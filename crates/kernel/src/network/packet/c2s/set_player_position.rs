@@ -0,0 +1,37 @@
+use crate::entity::Entity;
+use crate::gameplay::player_move;
+use crate::network::connection::Connection;
+use crate::network::packet::Decode;
+use crate::util::io::ReadExt;
+use anyhow::anyhow;
+use anyhow::Result;
+use async_trait::async_trait;
+use tokio::io::AsyncReadExt;
+
+pub struct SetPlayerPosition;
+
+#[async_trait]
+impl Decode for SetPlayerPosition {
+    async fn decode(&self, connection: &mut Connection, mut data: &[u8]) -> Result<()> {
+        let x = data.read_f64().await?;
+        let y = data.read_f64().await?;
+        let z = data.read_f64().await?;
+        let on_ground = data.read_bool().await?;
+        if !(x.is_finite() && y.is_finite() && z.is_finite()) {
+            return Err(anyhow!("PacketC2S: SetPlayerPosition: invalid position"));
+        }
+        let p = connection.player.clone().ok_or(anyhow!(
+            "PacketC2S: SetPlayerPosition: invalid context: player is undefined"
+        ))?;
+        {
+            let mut player = p.lock();
+            // Movements sent before the client accepted a teleport are outdated.
+            if player.teleport_id.is_some() {
+                return Ok(());
+            }
+            player.set_position(x, y, z);
+            player.get_data_mut().on_ground = on_ground;
+        }
+        player_move(connection).await
+    }
+}
@@ -0,0 +1,46 @@
+use crate::entity::Entity;
+use crate::gameplay::player_move;
+use crate::network::connection::Connection;
+use crate::network::packet::Decode;
+use crate::util::io::ReadExt;
+use anyhow::anyhow;
+use anyhow::Result;
+use async_trait::async_trait;
+use tokio::io::AsyncReadExt;
+
+pub struct SetPlayerPositionAndRotation;
+
+#[async_trait]
+impl Decode for SetPlayerPositionAndRotation {
+    async fn decode(&self, connection: &mut Connection, mut data: &[u8]) -> Result<()> {
+        let x = data.read_f64().await?;
+        let y = data.read_f64().await?;
+        let z = data.read_f64().await?;
+        let yaw = data.read_f32().await?;
+        let pitch = data.read_f32().await?;
+        let on_ground = data.read_bool().await?;
+        if !(x.is_finite() && y.is_finite() && z.is_finite()) {
+            return Err(anyhow!(
+                "PacketC2S: SetPlayerPositionAndRotation: invalid position"
+            ));
+        }
+        if !(yaw.is_finite() && pitch.is_finite()) {
+            return Err(anyhow!(
+                "PacketC2S: SetPlayerPositionAndRotation: invalid rotation"
+            ));
+        }
+        let p = connection.player.clone().ok_or(anyhow!(
+            "PacketC2S: SetPlayerPositionAndRotation: invalid context: player is undefined"
+        ))?;
+        {
+            let mut player = p.lock();
+            if player.teleport_id.is_some() {
+                return Ok(());
+            }
+            player.set_position(x, y, z);
+            player.set_rotation(yaw, pitch, on_ground);
+            player.set_head_yaw(yaw);
+        }
+        player_move(connection).await
+    }
+}
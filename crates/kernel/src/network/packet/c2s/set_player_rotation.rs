@@ -0,0 +1,29 @@
+use crate::entity::Entity;
+use crate::network::connection::Connection;
+use crate::network::packet::Decode;
+use crate::util::io::ReadExt;
+use anyhow::anyhow;
+use anyhow::Result;
+use async_trait::async_trait;
+use tokio::io::AsyncReadExt;
+
+pub struct SetPlayerRotation;
+
+#[async_trait]
+impl Decode for SetPlayerRotation {
+    async fn decode(&self, connection: &mut Connection, mut data: &[u8]) -> Result<()> {
+        let yaw = data.read_f32().await?;
+        let pitch = data.read_f32().await?;
+        let on_ground = data.read_bool().await?;
+        if !(yaw.is_finite() && pitch.is_finite()) {
+            return Err(anyhow!("PacketC2S: SetPlayerRotation: invalid rotation"));
+        }
+        let p = connection.player.clone().ok_or(anyhow!(
+            "PacketC2S: SetPlayerRotation: invalid context: player is undefined"
+        ))?;
+        let mut player = p.lock();
+        player.set_rotation(yaw, pitch, on_ground);
+        player.set_head_yaw(yaw);
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+use crate::gameplay::command::player_command;
+use crate::network::chat_session::LastSeenUpdate;
+use crate::network::connection::Connection;
+use crate::network::packet::Decode;
+use crate::util::io::ReadExt;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use tokio::io::AsyncReadExt;
+
+pub struct SignedChatCommand;
+
+#[async_trait]
+impl Decode for SignedChatCommand {
+    async fn decode(&self, connection: &mut Connection, mut data: &[u8]) -> Result<()> {
+        let command = data.read_str().await?;
+        let _timestamp = data.read_i64().await?;
+        let _salt = data.read_i64().await?;
+        // The signed arguments are not broadcast, so their signatures are not checked.
+        let count = data.read_var_int().await?;
+        if !(0..=8).contains(&count) {
+            return Err(anyhow!(
+                "PacketC2S: SignedChatCommand: too many argument signatures"
+            ));
+        }
+        for _ in 0..count {
+            data.read_str().await?;
+            let mut signature = [0; 256];
+            data.read_exact(&mut signature).await?;
+        }
+        let offset = data.read_var_int().await?;
+        let mut acknowledged = [0; 3];
+        data.read_exact(&mut acknowledged).await?;
+        player_command(
+            connection,
+            &command,
+            Some(LastSeenUpdate {
+                offset,
+                acknowledged,
+            }),
+        )
+        .await
+    }
+}
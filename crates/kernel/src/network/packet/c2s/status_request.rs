@@ -0,0 +1,15 @@
+use crate::network::connection::Connection;
+use crate::network::packet::s2c::status_response::StatusResponseS2C;
+use crate::network::packet::Decode;
+use anyhow::Result;
+use async_trait::async_trait;
+
+pub struct StatusRequest;
+
+#[async_trait]
+impl Decode for StatusRequest {
+    async fn decode(&self, connection: &mut Connection, _data: &[u8]) -> Result<()> {
+        connection.send_packet(&StatusResponseS2C).await?;
+        Ok(())
+    }
+}
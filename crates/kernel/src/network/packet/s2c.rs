@@ -1,8 +1,39 @@
+pub mod block_update;
+pub mod chunk_batch_finished;
+pub mod chunk_batch_start;
+pub mod chunk_data_and_update_light;
+pub mod command_suggestions_response;
+pub mod commands;
+pub mod disconnect;
+pub mod encryption_request;
+pub mod entity_event;
 pub mod finish_configuration;
 pub mod game_event;
+pub mod keep_alive;
 pub mod known_packs_s2c;
+pub mod login_plugin_request;
 pub mod login_success;
 pub mod play_login;
+pub mod player_chat_message;
+pub mod player_info_remove;
+pub mod player_info_update;
+pub mod pong_response;
 pub mod registry_data;
+pub mod remove_entities;
 pub mod set_center_chunk;
+pub mod set_compression;
+pub mod set_default_spawn_position;
+pub mod set_entity_metadata;
+pub mod set_head_rotation;
+pub mod set_tab_list_header_and_footer;
+pub mod spawn_entity;
+pub mod status_response;
 pub mod synchronize_player_position;
+pub mod system_chat_message;
+pub mod teleport_entity;
+pub mod unload_chunk;
+pub mod update_entity_position;
+pub mod update_entity_position_and_rotation;
+pub mod update_entity_rotation;
+pub mod update_section_blocks;
+pub mod update_time;
@@ -0,0 +1,25 @@
+use crate::network::packet::Encode;
+use crate::util::encode_position;
+use crate::util::io::WriteExt;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// Changes a single block, at absolute block coordinates.
+pub struct BlockUpdateS2C {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    pub block_state: u32,
+}
+
+impl Encode for BlockUpdateS2C {
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> anyhow::Result<()> {
+        buf.write_u64(encode_position(self.x, self.y, self.z))
+            .await?;
+        buf.write_var_int(self.block_state as i32).await?;
+        Ok(())
+    }
+
+    fn get_id(&self) -> i32 {
+        0x09
+    }
+}
@@ -0,0 +1,19 @@
+use crate::network::packet::Encode;
+use crate::util::io::WriteExt;
+use anyhow::Result;
+use tokio::io::AsyncWrite;
+
+pub struct ChunkBatchFinishedS2C {
+    pub batch_size: i32,
+}
+
+impl Encode for ChunkBatchFinishedS2C {
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> Result<()> {
+        buf.write_var_int(self.batch_size).await?;
+        Ok(())
+    }
+
+    fn get_id(&self) -> i32 {
+        0x0C
+    }
+}
@@ -0,0 +1,15 @@
+use crate::network::packet::Encode;
+use anyhow::Result;
+use tokio::io::AsyncWrite;
+
+pub struct ChunkBatchStartS2C;
+
+impl Encode for ChunkBatchStartS2C {
+    async fn encode<W: AsyncWrite + Unpin>(&self, _buf: &mut W) -> Result<()> {
+        Ok(())
+    }
+
+    fn get_id(&self) -> i32 {
+        0x0D
+    }
+}
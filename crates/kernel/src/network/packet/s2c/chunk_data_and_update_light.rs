@@ -0,0 +1,27 @@
+use crate::network::packet::Encode;
+use crate::world::chunk::Chunk;
+use anyhow::Result;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// Sends a whole chunk, including its heightmaps, block entities and light.
+///
+/// The body is the chunk's cached serialization,
+/// so players joining the same area usually go through [`Chunk::get_frame`] instead.
+pub struct ChunkDataAndUpdateLightS2C<'a> {
+    pub chunk: &'a Chunk,
+}
+
+impl ChunkDataAndUpdateLightS2C<'_> {
+    pub const ID: i32 = 0x27;
+}
+
+impl Encode for ChunkDataAndUpdateLightS2C<'_> {
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> Result<()> {
+        buf.write_all(&self.chunk.get_serialized().await?).await?;
+        Ok(())
+    }
+
+    fn get_id(&self) -> i32 {
+        Self::ID
+    }
+}
@@ -0,0 +1,31 @@
+use crate::network::packet::Encode;
+use crate::util::io::WriteExt;
+use tokio::io::AsyncWrite;
+
+pub struct CommandSuggestionsResponseS2C {
+    /// The id of the request this answers.
+    pub id: i32,
+    /// The position in the typed text the suggestions replace.
+    pub start: i32,
+    pub length: i32,
+    pub matches: Vec<String>,
+}
+
+impl Encode for CommandSuggestionsResponseS2C {
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> anyhow::Result<()> {
+        buf.write_var_int(self.id).await?;
+        buf.write_var_int(self.start).await?;
+        buf.write_var_int(self.length).await?;
+        buf.write_var_int(self.matches.len() as i32).await?;
+        for suggestion in self.matches.iter() {
+            buf.write_str(suggestion).await?;
+            // No tooltip.
+            buf.write_bool(false).await?;
+        }
+        Ok(())
+    }
+
+    fn get_id(&self) -> i32 {
+        0x10
+    }
+}
@@ -0,0 +1,67 @@
+use crate::command::argument::ArgumentType;
+use crate::network::packet::Encode;
+use crate::util::io::WriteExt;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// A node of the command graph, as sent to a client.
+pub struct CommandNodeData {
+    /// 0 for the root, 1 for a literal, 2 for an argument.
+    pub node_type: u8,
+    pub executable: bool,
+    pub children: Vec<i32>,
+    pub redirect: Option<i32>,
+    pub name: Option<String>,
+    pub argument_type: Option<ArgumentType>,
+    /// Whether the client asks the server for the suggestions of the argument.
+    pub ask_server: bool,
+}
+
+/// The commands a player may use, for the client to parse and complete them.
+///
+/// The root is the first node.
+pub struct CommandsS2C {
+    pub nodes: Vec<CommandNodeData>,
+}
+
+impl Encode for CommandsS2C {
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> anyhow::Result<()> {
+        buf.write_var_int(self.nodes.len() as i32).await?;
+        for node in self.nodes.iter() {
+            let mut flags = node.node_type;
+            if node.executable {
+                flags |= 0x04;
+            }
+            if node.redirect.is_some() {
+                flags |= 0x08;
+            }
+            if node.ask_server {
+                flags |= 0x10;
+            }
+            buf.write_u8(flags).await?;
+            buf.write_var_int(node.children.len() as i32).await?;
+            for child in node.children.iter() {
+                buf.write_var_int(*child).await?;
+            }
+            if let Some(redirect) = node.redirect {
+                buf.write_var_int(redirect).await?;
+            }
+            if let Some(name) = &node.name {
+                buf.write_str(name).await?;
+            }
+            if let Some(argument_type) = &node.argument_type {
+                buf.write_var_int(argument_type.get_parser_id()).await?;
+                argument_type.encode_properties(buf).await?;
+            }
+            if node.ask_server {
+                buf.write_str("minecraft:ask_server").await?;
+            }
+        }
+        // The root node.
+        buf.write_var_int(0).await?;
+        Ok(())
+    }
+
+    fn get_id(&self) -> i32 {
+        0x11
+    }
+}
@@ -0,0 +1,31 @@
+use crate::network::connection::State;
+use crate::network::packet::Encode;
+use crate::util::io::WriteExt;
+use crate::util::text::TextComponent;
+use anyhow::Result;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// Kicks the client, showing the reason on the disconnection screen.
+pub struct DisconnectS2C<'a> {
+    pub reason: &'a TextComponent,
+    /// The state the connection is in, the packet has a different id in each state.
+    pub state: State,
+}
+
+impl Encode for DisconnectS2C<'_> {
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> Result<()> {
+        match self.state {
+            State::Login => buf.write_str(&self.reason.to_json()).await?,
+            _ => buf.write_all(&self.reason.to_network_nbt()).await?,
+        }
+        Ok(())
+    }
+
+    fn get_id(&self) -> i32 {
+        match self.state {
+            State::Login => 0x00,
+            State::Configuration => 0x02,
+            _ => 0x1D,
+        }
+    }
+}
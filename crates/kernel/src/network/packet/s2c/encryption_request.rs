@@ -0,0 +1,23 @@
+use crate::network::auth::KEY_PAIR;
+use crate::network::packet::Encode;
+use crate::util::io::WriteExt;
+use anyhow::Result;
+use tokio::io::AsyncWrite;
+
+pub struct EncryptionRequestS2C {
+    pub verify_token: [u8; 4],
+}
+
+impl Encode for EncryptionRequestS2C {
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> Result<()> {
+        buf.write_str("").await?;
+        buf.write_byte_array(KEY_PAIR.get_public_key_der()).await?;
+        buf.write_byte_array(&self.verify_token).await?;
+        buf.write_bool(true).await?;
+        Ok(())
+    }
+
+    fn get_id(&self) -> i32 {
+        0x01
+    }
+}
@@ -0,0 +1,34 @@
+use crate::network::packet::Encode;
+use anyhow::Result;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// The status telling a player its permission level is this plus the level.
+const PERMISSION_LEVEL_STATUS: u8 = 24;
+
+/// Plays an effect on an entity, or tells a player about its own state.
+pub struct EntityEventS2C {
+    pub entity_id: i32,
+    pub status: u8,
+}
+
+impl EntityEventS2C {
+    /// Tells a player its permission level, which unlocks the features of the operators on its client.
+    pub fn permission_level(entity_id: i32, level: u8) -> EntityEventS2C {
+        EntityEventS2C {
+            entity_id,
+            status: PERMISSION_LEVEL_STATUS + level.min(4),
+        }
+    }
+}
+
+impl Encode for EntityEventS2C {
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> Result<()> {
+        buf.write_i32(self.entity_id).await?;
+        buf.write_u8(self.status).await?;
+        Ok(())
+    }
+
+    fn get_id(&self) -> i32 {
+        0x1F
+    }
+}
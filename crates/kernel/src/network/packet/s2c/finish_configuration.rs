@@ -1,4 +1,3 @@
-use crate::network::connection::Connection;
 use crate::network::packet::Encode;
 use anyhow::Result;
 use tokio::io::AsyncWrite;
@@ -6,11 +5,7 @@ use tokio::io::AsyncWrite;
 pub struct FinishConfigurationS2C;
 
 impl Encode for FinishConfigurationS2C {
-    async fn encode<W: AsyncWrite + Unpin>(
-        &self,
-        _connection: &mut Connection<'_>,
-        _buf: &mut W,
-    ) -> Result<()> {
+    async fn encode<W: AsyncWrite + Unpin>(&self, _buf: &mut W) -> Result<()> {
         Ok(())
     }
 
@@ -1,4 +1,3 @@
-use crate::network::connection::Connection;
 use crate::network::packet::Encode;
 use tokio::io::{AsyncWrite, AsyncWriteExt};
 
@@ -8,11 +7,7 @@ pub struct GameEventS2C {
 }
 
 impl Encode for GameEventS2C {
-    async fn encode<W: AsyncWrite + Unpin>(
-        &self,
-        _connection: &mut Connection<'_>,
-        buf: &mut W,
-    ) -> anyhow::Result<()> {
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> anyhow::Result<()> {
         buf.write_u8(self.event as u8).await?;
         buf.write_f32(self.value).await?;
         Ok(())
@@ -0,0 +1,24 @@
+use crate::network::connection::State;
+use crate::network::packet::Encode;
+use anyhow::Result;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+pub struct KeepAliveS2C {
+    pub id: i64,
+    /// The state the connection is in, the packet has a different id in each state.
+    pub state: State,
+}
+
+impl Encode for KeepAliveS2C {
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> Result<()> {
+        buf.write_i64(self.id).await?;
+        Ok(())
+    }
+
+    fn get_id(&self) -> i32 {
+        match self.state {
+            State::Configuration => 0x04,
+            _ => 0x26,
+        }
+    }
+}
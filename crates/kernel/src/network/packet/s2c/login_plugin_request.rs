@@ -0,0 +1,23 @@
+use crate::network::packet::Encode;
+use crate::util::io::WriteExt;
+use anyhow::Result;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+pub struct LoginPluginRequestS2C<'a> {
+    pub message_id: i32,
+    pub channel: &'a str,
+    pub data: &'a [u8],
+}
+
+impl Encode for LoginPluginRequestS2C<'_> {
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> Result<()> {
+        buf.write_var_int(self.message_id).await?;
+        buf.write_str(self.channel).await?;
+        buf.write_all(self.data).await?;
+        Ok(())
+    }
+
+    fn get_id(&self) -> i32 {
+        0x04
+    }
+}
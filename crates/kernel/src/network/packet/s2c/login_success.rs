@@ -1,24 +1,32 @@
-use crate::network::connection::Connection;
+use crate::network::auth::ProfileProperty;
 use crate::network::packet::Encode;
 use crate::util::io::WriteExt;
 use crate::util::{write_str, write_var_int};
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use tokio::io::{AsyncWrite, AsyncWriteExt};
 
-pub struct LoginSuccessS2C;
+pub struct LoginSuccessS2C<'a> {
+    pub uuid: u128,
+    pub username: &'a str,
+    pub properties: &'a [ProfileProperty],
+}
 
-impl Encode for LoginSuccessS2C {
-    async fn encode<W: AsyncWrite + Unpin>(
-        &self,
-        connection: &mut Connection<'_>,
-        buf: &mut W,
-    ) -> Result<()> {
-        if connection.username.is_none() || connection.uuid.is_none() {
-            return Err(anyhow!("Login start packet is not accepted yet.",));
+impl Encode for LoginSuccessS2C<'_> {
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> Result<()> {
+        buf.write_u128(self.uuid).await?;
+        write_str(buf, self.username).await?;
+        write_var_int(buf, self.properties.len() as i32).await?;
+        for property in self.properties.iter() {
+            write_str(buf, &property.name).await?;
+            write_str(buf, &property.value).await?;
+            match &property.signature {
+                Some(signature) => {
+                    buf.write_bool(true).await?;
+                    write_str(buf, signature).await?;
+                }
+                None => buf.write_bool(false).await?,
+            }
         }
-        buf.write_u128(connection.uuid.unwrap()).await?;
-        write_str(buf, &connection.username.clone().unwrap()).await?;
-        write_var_int(buf, 0).await?;
         buf.write_bool(true).await?;
         Ok(())
     }
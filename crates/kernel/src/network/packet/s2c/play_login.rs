@@ -1,43 +1,45 @@
-use crate::config::{HASHED_SEED, MAX_PLAYERS, SIMULATION_DISTANCE, VIEW_DISTANCE};
-use crate::network::connection::Connection;
+use crate::config::{MAX_PLAYERS, SIMULATION_DISTANCE, VIEW_DISTANCE};
+use crate::entity::player::Player;
+use crate::network::chat_session::SECURE_CHAT;
 use crate::network::packet::Encode;
 use crate::registry::dimension_type::DIMENSION_TYPES;
 use crate::registry::DIMENSION_TYPES_INDEX;
 use crate::util::encode_position;
 use crate::util::io::WriteExt;
+use crate::WORLD;
 use anyhow::anyhow;
 use anyhow::Result;
 use tokio::io::{AsyncWrite, AsyncWriteExt};
 
-pub struct PlayLoginS2C;
+pub struct PlayLoginS2C {
+    pub entity_id: i32,
+    pub dimension: usize,
+    pub game_mode: u8,
+    pub previous_game_mode: i8,
+    pub death_location: Option<(String, i32, i32, i32)>,
+    pub portal_cooldown: i32,
+    /// Whether the client must sign its chat messages.
+    pub enforce_secure_chat: bool,
+}
+
+impl PlayLoginS2C {
+    pub fn new(player: &Player) -> PlayLoginS2C {
+        PlayLoginS2C {
+            entity_id: player.entity.entity_id,
+            dimension: player.entity.dimension,
+            game_mode: player.game_mode,
+            previous_game_mode: player.previous_game_mode,
+            death_location: player.death_location.clone(),
+            portal_cooldown: player.entity.portal_cooldown,
+            enforce_secure_chat: *SECURE_CHAT,
+        }
+    }
+}
 
 impl Encode for PlayLoginS2C {
-    async fn encode<W: AsyncWrite + Unpin>(
-        &self,
-        connection: &mut Connection<'_>,
-        buf: &mut W,
-    ) -> Result<()> {
-        let (eid, dim, game_mode, previous_game_mode, death_location, portal_cooldown) = {
-            let p = connection.player.clone().ok_or(anyhow!(
-                "PacketS2C: Login(play): invalid context: player is undefined"
-            ))?;
-            let player = p.lock();
-            let eid = player.entity.entity_id;
-            let dim = player.entity.dimension;
-            let game_mode = player.game_mode;
-            let previous_game_mode = player.previous_game_mode;
-            let death_location = player.death_location.clone();
-            let portal_cooldown = player.entity.portal_cooldown;
-            (
-                eid,
-                dim,
-                game_mode,
-                previous_game_mode,
-                death_location,
-                portal_cooldown,
-            )
-        };
-        buf.write_i32(eid).await?;
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> Result<()> {
+        let dim = self.dimension;
+        buf.write_i32(self.entity_id).await?;
         buf.write_bool(false).await?;
         buf.write_var_int(DIMENSION_TYPES.len() as i32).await?;
         for dimension_type in DIMENSION_TYPES_INDEX.iter() {
@@ -55,12 +57,13 @@ impl Encode for PlayLoginS2C {
             None => return Err(anyhow!("Dimension type not found")),
         })
         .await?;
-        buf.write_i64(*HASHED_SEED).await?;
-        buf.write_u8(game_mode).await?;
-        buf.write_i8(previous_game_mode).await?;
+        let hashed_seed = WORLD.level_data.lock().get_hashed_seed();
+        buf.write_i64(hashed_seed).await?;
+        buf.write_u8(self.game_mode).await?;
+        buf.write_i8(self.previous_game_mode).await?;
         buf.write_bool(false).await?;
         buf.write_bool(false).await?;
-        match &death_location {
+        match &self.death_location {
             Some(death_location) => {
                 buf.write_bool(true).await?;
                 buf.write_str(&death_location.0).await?;
@@ -75,8 +78,8 @@ impl Encode for PlayLoginS2C {
                 buf.write_bool(false).await?;
             }
         }
-        buf.write_var_int(portal_cooldown).await?;
-        buf.write_bool(false).await?;
+        buf.write_var_int(self.portal_cooldown).await?;
+        buf.write_bool(self.enforce_secure_chat).await?;
         Ok(())
     }
 
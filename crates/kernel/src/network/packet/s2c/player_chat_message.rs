@@ -0,0 +1,66 @@
+use crate::network::chat_session::{MessageBody, MessageSignature};
+use crate::network::packet::Encode;
+use crate::util::io::WriteExt;
+use crate::util::text::TextComponent;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use uuid::Uuid;
+
+/// An empty style, the network NBT of an empty compound.
+const EMPTY_STYLE: [u8; 2] = [0x0A, 0x00];
+
+/// A signed chat message of a player, shown as `<sender> message`.
+///
+/// The chat type is sent inline, so the chat type registry is not needed.
+/// The last seen messages are sent as full signatures rather than ids of a signature cache.
+pub struct PlayerChatMessageS2C<'a> {
+    pub sender: Uuid,
+    /// The index of the message in the sender's chain.
+    pub index: i32,
+    pub signature: Option<&'a MessageSignature>,
+    pub body: &'a MessageBody,
+    pub sender_name: &'a TextComponent,
+}
+
+impl Encode for PlayerChatMessageS2C<'_> {
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> anyhow::Result<()> {
+        buf.write_u128(self.sender.as_u128()).await?;
+        buf.write_var_int(self.index).await?;
+        match self.signature {
+            Some(signature) => {
+                buf.write_bool(true).await?;
+                buf.write_all(signature).await?;
+            }
+            None => buf.write_bool(false).await?,
+        }
+        buf.write_str(&self.body.message).await?;
+        buf.write_i64(self.body.timestamp).await?;
+        buf.write_i64(self.body.salt).await?;
+        buf.write_var_int(self.body.last_seen.len() as i32).await?;
+        for signature in self.body.last_seen.iter() {
+            // 0 for a full signature instead of an id.
+            buf.write_var_int(0).await?;
+            buf.write_all(signature).await?;
+        }
+        // No unsigned content.
+        buf.write_bool(false).await?;
+        // Not filtered.
+        buf.write_var_int(0).await?;
+        // The `minecraft:chat` chat type, inline.
+        buf.write_var_int(0).await?;
+        for translation_key in ["chat.type.text", "chat.type.text.narrate"] {
+            buf.write_str(translation_key).await?;
+            // The sender and the content parameters.
+            buf.write_var_int(2).await?;
+            buf.write_var_int(0).await?;
+            buf.write_var_int(2).await?;
+            buf.write_all(&EMPTY_STYLE).await?;
+        }
+        buf.write_all(&self.sender_name.to_network_nbt()).await?;
+        buf.write_bool(false).await?;
+        Ok(())
+    }
+
+    fn get_id(&self) -> i32 {
+        0x39
+    }
+}
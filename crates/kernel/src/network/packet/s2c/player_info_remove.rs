@@ -0,0 +1,23 @@
+use crate::network::packet::Encode;
+use crate::util::io::WriteExt;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use uuid::Uuid;
+
+/// Removes players from the tab list.
+pub struct PlayerInfoRemoveS2C {
+    pub uuids: Vec<Uuid>,
+}
+
+impl Encode for PlayerInfoRemoveS2C {
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> anyhow::Result<()> {
+        buf.write_var_int(self.uuids.len() as i32).await?;
+        for uuid in self.uuids.iter() {
+            buf.write_u128(uuid.as_u128()).await?;
+        }
+        Ok(())
+    }
+
+    fn get_id(&self) -> i32 {
+        0x3D
+    }
+}
@@ -0,0 +1,127 @@
+use crate::entity::player::Player;
+use crate::entity::Entity;
+use crate::network::auth::ProfileProperty;
+use crate::network::chat_session::ProfilePublicKey;
+use crate::network::packet::Encode;
+use crate::util::io::WriteExt;
+use crate::util::text::TextComponent;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use uuid::Uuid;
+
+/// The actions of a [`PlayerInfoUpdateS2C`], combined as a bit mask.
+pub mod actions {
+    pub const ADD_PLAYER: u8 = 0x01;
+    pub const INITIALIZE_CHAT: u8 = 0x02;
+    pub const UPDATE_GAME_MODE: u8 = 0x04;
+    pub const UPDATE_LISTED: u8 = 0x08;
+    pub const UPDATE_LATENCY: u8 = 0x10;
+    pub const UPDATE_DISPLAY_NAME: u8 = 0x20;
+    /// All the actions, sent when a player is added to the list.
+    pub const ALL: u8 = ADD_PLAYER
+        | INITIALIZE_CHAT
+        | UPDATE_GAME_MODE
+        | UPDATE_LISTED
+        | UPDATE_LATENCY
+        | UPDATE_DISPLAY_NAME;
+}
+
+/// A player of the tab list.
+pub struct PlayerInfoEntry {
+    pub uuid: Uuid,
+    pub username: String,
+    pub properties: Vec<ProfileProperty>,
+    /// The id and the key of the player's chat session, to verify its messages.
+    pub chat_session: Option<(Uuid, ProfilePublicKey)>,
+    pub game_mode: u8,
+    pub listed: bool,
+    /// The latency in milliseconds, shown as bars.
+    pub latency: i32,
+    /// The name shown instead of the username.
+    pub display_name: Option<TextComponent>,
+}
+
+impl PlayerInfoEntry {
+    pub fn new(player: &Player) -> PlayerInfoEntry {
+        PlayerInfoEntry {
+            uuid: player.get_data().uuid,
+            username: player.username.clone(),
+            properties: player.properties.clone(),
+            chat_session: player
+                .chat_session
+                .as_ref()
+                .map(|session| (session.session_id, session.public_key.clone())),
+            game_mode: player.game_mode,
+            listed: true,
+            latency: player.latency,
+            display_name: player.display_name.clone(),
+        }
+    }
+}
+
+/// Adds players to the tab list or updates them.
+///
+/// Only the fields of the given actions are sent, for every entry.
+pub struct PlayerInfoUpdateS2C {
+    pub actions: u8,
+    pub entries: Vec<PlayerInfoEntry>,
+}
+
+impl Encode for PlayerInfoUpdateS2C {
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> anyhow::Result<()> {
+        buf.write_u8(self.actions).await?;
+        buf.write_var_int(self.entries.len() as i32).await?;
+        for entry in self.entries.iter() {
+            buf.write_u128(entry.uuid.as_u128()).await?;
+            if self.actions & actions::ADD_PLAYER != 0 {
+                buf.write_str(&entry.username).await?;
+                buf.write_var_int(entry.properties.len() as i32).await?;
+                for property in entry.properties.iter() {
+                    buf.write_str(&property.name).await?;
+                    buf.write_str(&property.value).await?;
+                    match &property.signature {
+                        Some(signature) => {
+                            buf.write_bool(true).await?;
+                            buf.write_str(signature).await?;
+                        }
+                        None => buf.write_bool(false).await?,
+                    }
+                }
+            }
+            if self.actions & actions::INITIALIZE_CHAT != 0 {
+                match &entry.chat_session {
+                    Some((session_id, public_key)) => {
+                        buf.write_bool(true).await?;
+                        buf.write_u128(session_id.as_u128()).await?;
+                        buf.write_i64(public_key.expires_at).await?;
+                        buf.write_byte_array(&public_key.key).await?;
+                        buf.write_byte_array(&public_key.signature).await?;
+                    }
+                    None => buf.write_bool(false).await?,
+                }
+            }
+            if self.actions & actions::UPDATE_GAME_MODE != 0 {
+                buf.write_var_int(entry.game_mode as i32).await?;
+            }
+            if self.actions & actions::UPDATE_LISTED != 0 {
+                buf.write_bool(entry.listed).await?;
+            }
+            if self.actions & actions::UPDATE_LATENCY != 0 {
+                buf.write_var_int(entry.latency).await?;
+            }
+            if self.actions & actions::UPDATE_DISPLAY_NAME != 0 {
+                match &entry.display_name {
+                    Some(display_name) => {
+                        buf.write_bool(true).await?;
+                        buf.write_all(&display_name.to_network_nbt()).await?;
+                    }
+                    None => buf.write_bool(false).await?,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn get_id(&self) -> i32 {
+        0x3E
+    }
+}
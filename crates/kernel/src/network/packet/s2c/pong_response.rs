@@ -0,0 +1,18 @@
+use crate::network::packet::Encode;
+use anyhow::Result;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+pub struct PongResponseS2C {
+    pub payload: i64,
+}
+
+impl Encode for PongResponseS2C {
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> Result<()> {
+        buf.write_i64(self.payload).await?;
+        Ok(())
+    }
+
+    fn get_id(&self) -> i32 {
+        0x01
+    }
+}
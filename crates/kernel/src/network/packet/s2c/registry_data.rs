@@ -1,4 +1,3 @@
-use crate::network::connection::Connection;
 use crate::network::packet::Encode;
 use crate::registry;
 use crate::registry::get_cache;
@@ -18,11 +17,7 @@ pub struct RegistryDataS2C<'a, T: Serialize> {
 }
 
 impl<'a, T: Serialize + registry::NbtSerializable> Encode for RegistryDataS2C<'a, T> {
-    async fn encode<W: AsyncWrite + Unpin>(
-        &self,
-        _connection: &mut Connection<'_>,
-        buf: &mut W,
-    ) -> Result<()> {
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> Result<()> {
         write_str(buf, self.id).await?;
         write_var_int(buf, self.map.len() as i32).await?;
         for key in self.index {
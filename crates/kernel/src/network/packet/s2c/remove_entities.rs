@@ -0,0 +1,21 @@
+use crate::network::packet::Encode;
+use crate::util::io::WriteExt;
+use tokio::io::AsyncWrite;
+
+pub struct RemoveEntitiesS2C {
+    pub entity_ids: Vec<i32>,
+}
+
+impl Encode for RemoveEntitiesS2C {
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> anyhow::Result<()> {
+        buf.write_var_int(self.entity_ids.len() as i32).await?;
+        for entity_id in self.entity_ids.iter() {
+            buf.write_var_int(*entity_id).await?;
+        }
+        Ok(())
+    }
+
+    fn get_id(&self) -> i32 {
+        0x42
+    }
+}
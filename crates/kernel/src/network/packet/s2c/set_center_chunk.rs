@@ -1,4 +1,3 @@
-use crate::network::connection::Connection;
 use crate::network::packet::Encode;
 use crate::util::io::WriteExt;
 use tokio::io::AsyncWrite;
@@ -9,11 +8,7 @@ pub struct SetCenterChunkS2C {
 }
 
 impl Encode for SetCenterChunkS2C {
-    async fn encode<W: AsyncWrite + Unpin>(
-        &self,
-        _connection: &mut Connection<'_>,
-        buf: &mut W,
-    ) -> anyhow::Result<()> {
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> anyhow::Result<()> {
         buf.write_var_int(self.chunk_x).await?;
         buf.write_var_int(self.chunk_z).await?;
         Ok(())
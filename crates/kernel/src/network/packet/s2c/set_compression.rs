@@ -0,0 +1,19 @@
+use crate::network::packet::Encode;
+use crate::util::io::WriteExt;
+use anyhow::Result;
+use tokio::io::AsyncWrite;
+
+pub struct SetCompressionS2C {
+    pub threshold: i32,
+}
+
+impl Encode for SetCompressionS2C {
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> Result<()> {
+        buf.write_var_int(self.threshold).await?;
+        Ok(())
+    }
+
+    fn get_id(&self) -> i32 {
+        0x03
+    }
+}
@@ -0,0 +1,22 @@
+use crate::network::packet::Encode;
+use crate::util::encode_position;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// Sets where the compass points to and where the client respawns while loading.
+pub struct SetDefaultSpawnPositionS2C {
+    pub pos: (i32, i32, i32),
+    pub angle: f32,
+}
+
+impl Encode for SetDefaultSpawnPositionS2C {
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> anyhow::Result<()> {
+        let (x, y, z) = self.pos;
+        buf.write_u64(encode_position(x, y, z)).await?;
+        buf.write_f32(self.angle).await?;
+        Ok(())
+    }
+
+    fn get_id(&self) -> i32 {
+        0x56
+    }
+}
@@ -0,0 +1,26 @@
+use crate::entity::metadata::MetadataValue;
+use crate::network::packet::Encode;
+use crate::util::io::WriteExt;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// Updates fields of an entity's metadata.
+pub struct SetEntityMetadataS2C {
+    pub entity_id: i32,
+    pub metadata: Vec<(u8, MetadataValue)>,
+}
+
+impl Encode for SetEntityMetadataS2C {
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> anyhow::Result<()> {
+        buf.write_var_int(self.entity_id).await?;
+        for (index, value) in self.metadata.iter() {
+            buf.write_u8(*index).await?;
+            value.encode(buf).await?;
+        }
+        buf.write_u8(0xFF).await?;
+        Ok(())
+    }
+
+    fn get_id(&self) -> i32 {
+        0x58
+    }
+}
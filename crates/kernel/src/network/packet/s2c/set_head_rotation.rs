@@ -0,0 +1,20 @@
+use crate::network::packet::Encode;
+use crate::util::io::WriteExt;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+pub struct SetHeadRotationS2C {
+    pub entity_id: i32,
+    pub head_yaw: u8,
+}
+
+impl Encode for SetHeadRotationS2C {
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> anyhow::Result<()> {
+        buf.write_var_int(self.entity_id).await?;
+        buf.write_u8(self.head_yaw).await?;
+        Ok(())
+    }
+
+    fn get_id(&self) -> i32 {
+        0x48
+    }
+}
@@ -0,0 +1,20 @@
+use crate::network::packet::Encode;
+use crate::util::text::TextComponent;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+pub struct SetTabListHeaderAndFooterS2C<'a> {
+    pub header: &'a TextComponent,
+    pub footer: &'a TextComponent,
+}
+
+impl Encode for SetTabListHeaderAndFooterS2C<'_> {
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> anyhow::Result<()> {
+        buf.write_all(&self.header.to_network_nbt()).await?;
+        buf.write_all(&self.footer.to_network_nbt()).await?;
+        Ok(())
+    }
+
+    fn get_id(&self) -> i32 {
+        0x6D
+    }
+}
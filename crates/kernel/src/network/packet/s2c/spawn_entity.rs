@@ -0,0 +1,44 @@
+use crate::network::packet::Encode;
+use crate::util::io::WriteExt;
+use crate::util::to_angle;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use uuid::Uuid;
+
+/// Spawns an entity for the client, players included.
+pub struct SpawnEntityS2C {
+    pub entity_id: i32,
+    pub uuid: Uuid,
+    pub entity_type: u32,
+    pub pos: (f64, f64, f64),
+    pub yaw: f32,
+    pub pitch: f32,
+    pub head_yaw: f32,
+    /// Depends on the type of the entity, like the direction of an item frame.
+    pub data: i32,
+    /// The velocity in blocks per tick.
+    pub velocity: (f32, f32, f32),
+}
+
+impl Encode for SpawnEntityS2C {
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> anyhow::Result<()> {
+        buf.write_var_int(self.entity_id).await?;
+        buf.write_u128(self.uuid.as_u128()).await?;
+        buf.write_var_int(self.entity_type as i32).await?;
+        buf.write_f64(self.pos.0).await?;
+        buf.write_f64(self.pos.1).await?;
+        buf.write_f64(self.pos.2).await?;
+        buf.write_u8(to_angle(self.pitch)).await?;
+        buf.write_u8(to_angle(self.yaw)).await?;
+        buf.write_u8(to_angle(self.head_yaw)).await?;
+        buf.write_var_int(self.data).await?;
+        for velocity in [self.velocity.0, self.velocity.1, self.velocity.2] {
+            buf.write_i16((velocity.clamp(-3.9, 3.9) * 8000.0) as i16)
+                .await?;
+        }
+        Ok(())
+    }
+
+    fn get_id(&self) -> i32 {
+        0x01
+    }
+}
@@ -0,0 +1,55 @@
+use crate::config::{MAX_PLAYERS, MOTD, SERVER_ICON};
+use crate::network::chat_session::SECURE_CHAT;
+use crate::network::packet::Encode;
+use crate::util::io::WriteExt;
+use crate::{MINECRAFT_VERSION, PROTOCOL_VERSION, WORLD};
+use anyhow::Result;
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use serde_json::json;
+use std::sync::LazyLock;
+use tokio::io::AsyncWrite;
+
+static FAVICON: LazyLock<Option<String>> = LazyLock::new(|| {
+    let icon = std::fs::read(*SERVER_ICON).ok()?;
+    Some(format!(
+        "data:image/png;base64,{}",
+        BASE64_STANDARD.encode(icon)
+    ))
+});
+
+pub struct StatusResponseS2C;
+
+impl StatusResponseS2C {
+    pub fn to_json() -> String {
+        let mut status = json!({
+            "version": {
+                "name": MINECRAFT_VERSION,
+                "protocol": PROTOCOL_VERSION,
+            },
+            "players": {
+                "max": *MAX_PLAYERS,
+                "online": WORLD.online_players(),
+            },
+            "description": {
+                "text": *MOTD,
+            },
+            "enforcesSecureChat": *SECURE_CHAT,
+        });
+        if let Some(favicon) = &*FAVICON {
+            status["favicon"] = json!(favicon);
+        }
+        status.to_string()
+    }
+}
+
+impl Encode for StatusResponseS2C {
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> Result<()> {
+        buf.write_str(&Self::to_json()).await?;
+        Ok(())
+    }
+
+    fn get_id(&self) -> i32 {
+        0x00
+    }
+}
@@ -1,36 +1,41 @@
-use crate::network::connection::Connection;
+use crate::entity::player::Player;
 use crate::network::packet::Encode;
 use crate::util::io::WriteExt;
-use anyhow::anyhow;
 use tokio::io::{AsyncWrite, AsyncWriteExt};
 
-pub struct SynchronizePlayerPositionS2C;
+/// Teleports the player, who has to confirm the teleportation with the same id.
+pub struct SynchronizePlayerPositionS2C {
+    pub pos: (f64, f64, f64),
+    pub yaw: f32,
+    pub pitch: f32,
+    pub teleport_id: i32,
+}
 
-impl Encode for SynchronizePlayerPositionS2C {
-    async fn encode<W: AsyncWrite + Unpin>(
-        &self,
-        connection: &mut Connection<'_>,
-        buf: &mut W,
-    ) -> anyhow::Result<()> {
-        let ((x, y, z), yaw, pitch);
-        let teleport_id;
-        {
-            teleport_id = fastrand::i32(i32::MIN..i32::MAX);
-            let p = connection.player.clone().ok_or(anyhow!(
-                "PacketS2C: SynchronizePlayerPosition: invalid context: player is undefined"
-            ))?;
-            let mut player = p.lock();
-            player.teleport_id = Some(teleport_id);
-            ((x, y, z), yaw, pitch) = (player.entity.pos, player.entity.yaw, player.entity.pitch);
+impl SynchronizePlayerPositionS2C {
+    /// Creates the packet for the player's current position
+    /// and records its teleport id as awaiting confirmation.
+    pub fn new(player: &mut Player) -> SynchronizePlayerPositionS2C {
+        let teleport_id = fastrand::i32(i32::MIN..i32::MAX);
+        player.teleport_id = Some(teleport_id);
+        SynchronizePlayerPositionS2C {
+            pos: player.entity.pos,
+            yaw: player.entity.yaw,
+            pitch: player.entity.pitch,
+            teleport_id,
         }
+    }
+}
 
+impl Encode for SynchronizePlayerPositionS2C {
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> anyhow::Result<()> {
+        let (x, y, z) = self.pos;
         buf.write_f64(x).await?;
         buf.write_f64(y).await?;
         buf.write_f64(z).await?;
-        buf.write_f32(yaw).await?;
-        buf.write_f32(pitch).await?;
+        buf.write_f32(self.yaw).await?;
+        buf.write_f32(self.pitch).await?;
         buf.write_i8(0).await?;
-        buf.write_var_int(teleport_id).await?;
+        buf.write_var_int(self.teleport_id).await?;
         Ok(())
     }
 
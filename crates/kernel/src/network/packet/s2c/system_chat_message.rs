@@ -0,0 +1,22 @@
+use crate::network::packet::Encode;
+use crate::util::io::WriteExt;
+use crate::util::text::TextComponent;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// A message shown in the chat, or above the hotbar if `overlay` is set.
+pub struct SystemChatMessageS2C<'a> {
+    pub content: &'a TextComponent,
+    pub overlay: bool,
+}
+
+impl Encode for SystemChatMessageS2C<'_> {
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> anyhow::Result<()> {
+        buf.write_all(&self.content.to_network_nbt()).await?;
+        buf.write_bool(self.overlay).await?;
+        Ok(())
+    }
+
+    fn get_id(&self) -> i32 {
+        0x6C
+    }
+}
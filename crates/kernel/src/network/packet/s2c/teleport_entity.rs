@@ -0,0 +1,29 @@
+use crate::network::packet::Encode;
+use crate::util::io::WriteExt;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// Moves an entity to an absolute position, used when it moved too far for a relative move.
+pub struct TeleportEntityS2C {
+    pub entity_id: i32,
+    pub pos: (f64, f64, f64),
+    pub yaw: u8,
+    pub pitch: u8,
+    pub on_ground: bool,
+}
+
+impl Encode for TeleportEntityS2C {
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> anyhow::Result<()> {
+        buf.write_var_int(self.entity_id).await?;
+        buf.write_f64(self.pos.0).await?;
+        buf.write_f64(self.pos.1).await?;
+        buf.write_f64(self.pos.2).await?;
+        buf.write_u8(self.yaw).await?;
+        buf.write_u8(self.pitch).await?;
+        buf.write_bool(self.on_ground).await?;
+        Ok(())
+    }
+
+    fn get_id(&self) -> i32 {
+        0x70
+    }
+}
@@ -0,0 +1,21 @@
+use crate::network::packet::Encode;
+use anyhow::Result;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+pub struct UnloadChunkS2C {
+    pub chunk_x: i32,
+    pub chunk_z: i32,
+}
+
+impl Encode for UnloadChunkS2C {
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> Result<()> {
+        // The Z coordinate comes first.
+        buf.write_i32(self.chunk_z).await?;
+        buf.write_i32(self.chunk_x).await?;
+        Ok(())
+    }
+
+    fn get_id(&self) -> i32 {
+        0x21
+    }
+}
@@ -0,0 +1,30 @@
+use crate::network::packet::Encode;
+use crate::util::io::WriteExt;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// Moves and rotates an entity, moving it by less than 8 blocks on each axis.
+pub struct UpdateEntityPositionAndRotationS2C {
+    pub entity_id: i32,
+    /// The movement in 1/4096 of a block.
+    pub delta: (i16, i16, i16),
+    pub yaw: u8,
+    pub pitch: u8,
+    pub on_ground: bool,
+}
+
+impl Encode for UpdateEntityPositionAndRotationS2C {
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> anyhow::Result<()> {
+        buf.write_var_int(self.entity_id).await?;
+        buf.write_i16(self.delta.0).await?;
+        buf.write_i16(self.delta.1).await?;
+        buf.write_i16(self.delta.2).await?;
+        buf.write_u8(self.yaw).await?;
+        buf.write_u8(self.pitch).await?;
+        buf.write_bool(self.on_ground).await?;
+        Ok(())
+    }
+
+    fn get_id(&self) -> i32 {
+        0x2F
+    }
+}
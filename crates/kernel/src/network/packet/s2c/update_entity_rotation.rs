@@ -0,0 +1,24 @@
+use crate::network::packet::Encode;
+use crate::util::io::WriteExt;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+pub struct UpdateEntityRotationS2C {
+    pub entity_id: i32,
+    pub yaw: u8,
+    pub pitch: u8,
+    pub on_ground: bool,
+}
+
+impl Encode for UpdateEntityRotationS2C {
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> anyhow::Result<()> {
+        buf.write_var_int(self.entity_id).await?;
+        buf.write_u8(self.yaw).await?;
+        buf.write_u8(self.pitch).await?;
+        buf.write_bool(self.on_ground).await?;
+        Ok(())
+    }
+
+    fn get_id(&self) -> i32 {
+        0x30
+    }
+}
@@ -0,0 +1,34 @@
+use crate::network::packet::Encode;
+use crate::util::io::WriteExt;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// Changes several blocks of the same chunk section at once.
+pub struct UpdateSectionBlocksS2C {
+    pub section_x: i32,
+    pub section_y: i32,
+    pub section_z: i32,
+    /// The changed blocks, as their position in the section packed as `x << 8 | z << 4 | y`
+    /// and their new block state.
+    pub blocks: Vec<(u16, u32)>,
+}
+
+impl Encode for UpdateSectionBlocksS2C {
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> anyhow::Result<()> {
+        buf.write_u64(
+            ((self.section_x as u64 & 0x3FFFFF) << 42)
+                | ((self.section_z as u64 & 0x3FFFFF) << 20)
+                | (self.section_y as u64 & 0xFFFFF),
+        )
+        .await?;
+        buf.write_var_int(self.blocks.len() as i32).await?;
+        for (pos, block_state) in self.blocks.iter() {
+            buf.write_var_long((*block_state as i64) << 12 | *pos as i64)
+                .await?;
+        }
+        Ok(())
+    }
+
+    fn get_id(&self) -> i32 {
+        0x49
+    }
+}
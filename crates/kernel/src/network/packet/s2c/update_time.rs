@@ -0,0 +1,21 @@
+use crate::network::packet::Encode;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// Synchronizes the time of the client, sent every second.
+pub struct UpdateTimeS2C {
+    pub game_time: i64,
+    /// The time of day, negative to stop the client from advancing it.
+    pub day_time: i64,
+}
+
+impl Encode for UpdateTimeS2C {
+    async fn encode<W: AsyncWrite + Unpin>(&self, buf: &mut W) -> anyhow::Result<()> {
+        buf.write_i64(self.game_time).await?;
+        buf.write_i64(self.day_time).await?;
+        Ok(())
+    }
+
+    fn get_id(&self) -> i32 {
+        0x64
+    }
+}
@@ -78,7 +78,7 @@ pub(crate) fn get_cache<T: NbtSerializable>(
     })
 }
 
-pub(crate) async fn send_registry_data<'a>(connection: &mut Connection<'a>) -> Result<()> {
+pub(crate) async fn send_registry_data(connection: &mut Connection) -> Result<()> {
     connection
         .send_packet(&RegistryDataS2C {
             id: "minecraft:worldgen/biome",
@@ -9,6 +9,54 @@ use std::sync::{Arc, LazyLock};
 
 pub static REGISTRY: LazyLock<Value> = LazyLock::new(|| get_registry("registries.json"));
 pub static BLOCK_STATES: LazyLock<Value> = LazyLock::new(|| get_registry("blocks.json"));
+/// The block name and the sorted properties of a block state.
+type BlockStateProperties = (String, Vec<(String, String)>);
+/// The block name and the sorted properties of every block state, by id.
+static BLOCK_STATE_PROPERTIES: LazyLock<HashMap<u32, BlockStateProperties>> = LazyLock::new(|| {
+    let mut properties = HashMap::with_capacity(32768);
+    for (name, block) in BLOCK_STATES.as_object().unwrap() {
+        for state in block["states"].as_array().unwrap() {
+            let mut values: Vec<(String, String)> = state
+                .get("properties")
+                .and_then(Value::as_object)
+                .map(|values| {
+                    values
+                        .iter()
+                        .map(|(key, value)| match value {
+                            Value::String(value) => (key.clone(), value.clone()),
+                            value => (key.clone(), value.to_string()),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            values.sort_unstable();
+            let id = state["id"].as_u64().unwrap() as u32;
+            properties.insert(id, (name.clone(), values));
+        }
+    }
+    properties
+});
+/// Block state ids keyed by `name[key=value,...]` with sorted properties.
+/// The bare name of a block maps to its default state.
+static BLOCK_STATE_IDS: LazyLock<HashMap<String, u32>> = LazyLock::new(|| {
+    let mut ids = HashMap::with_capacity(32768);
+    for (id, (name, properties)) in BLOCK_STATE_PROPERTIES.iter() {
+        let properties = properties
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()));
+        ids.insert(block_state_key(name, properties), *id);
+    }
+    for (name, block) in BLOCK_STATES.as_object().unwrap() {
+        let states = block["states"].as_array().unwrap();
+        if let Some(state) = states
+            .iter()
+            .find(|state| state["default"].as_bool().unwrap_or(false))
+        {
+            ids.insert(name.clone(), state["id"].as_u64().unwrap() as u32);
+        }
+    }
+    ids
+});
 
 fn get_registry(file: &str) -> Value {
     let json = GENERATED.get(file).unwrap();
@@ -34,6 +82,43 @@ pub fn get_protocol_id(registry_type: &str, name: &str) -> Option<u32> {
     )
 }
 
+/// Looks up the id of a block state by its block name and properties.
+///
+/// Properties that do not match any state of the block resolve to its default state.
+///
+/// # Returns
+/// - `Some(u32)`: The block state id.
+/// - `None`: If the block is unknown.
+pub fn get_block_state_id<'a>(
+    name: &str,
+    properties: impl Iterator<Item = (&'a str, &'a str)>,
+) -> Option<u32> {
+    BLOCK_STATE_IDS
+        .get(&block_state_key(name, properties))
+        .or_else(|| BLOCK_STATE_IDS.get(name))
+        .copied()
+}
+
+/// Gets the block name and the sorted properties of a block state.
+pub fn get_block_state_properties(id: u32) -> Option<(&'static str, &'static [(String, String)])> {
+    BLOCK_STATE_PROPERTIES
+        .get(&id)
+        .map(|(name, properties)| (name.as_str(), properties.as_slice()))
+}
+
+fn block_state_key<'a>(name: &str, properties: impl Iterator<Item = (&'a str, &'a str)>) -> String {
+    let mut properties: Vec<_> = properties.collect();
+    if properties.is_empty() {
+        return name.to_string();
+    }
+    properties.sort_unstable();
+    let properties: Vec<String> = properties
+        .into_iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect();
+    format!("{}[{}]", name, properties.join(","))
+}
+
 pub fn get_block_states<T: 'static + DeserializeOwned + BlockState>(
     identifier: &str,
 ) -> (HashMap<u32, Arc<(dyn BlockState)>>, u32) {
@@ -70,4 +155,20 @@ mod tests {
             Some(128)
         );
     }
+    #[test]
+    fn test_get_block_state_id() {
+        let stone = get_block_state_id("minecraft:stone", std::iter::empty()).unwrap();
+        assert_eq!(stone, 1);
+        let axis_x = get_block_state_id("minecraft:oak_log", [("axis", "x")].into_iter());
+        let axis_y = get_block_state_id("minecraft:oak_log", [("axis", "y")].into_iter());
+        assert_ne!(axis_x, axis_y);
+        assert_eq!(
+            get_block_state_id("minecraft:oak_log", std::iter::empty()),
+            axis_y
+        );
+        assert_eq!(
+            get_block_state_id("minecraft:unknown", std::iter::empty()),
+            None
+        );
+    }
 }
@@ -0,0 +1,176 @@
+pub mod ops;
+
+use crate::config::AUTOSAVE_INTERVAL;
+use crate::entity::player::PlayerUpdate;
+use crate::gameplay::{map_players, save_players, PLAYERS};
+use crate::util::text::TextComponent;
+use crate::WORLD;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+use tokio::time::sleep;
+use tracing::{info, warn};
+
+/// The ticks per second the server runs at.
+pub const TICKS_PER_SECOND: f64 = 20.0;
+
+/// The ticks the tick rate is measured over.
+const TICK_SAMPLES: usize = 100;
+
+/// How long the players have to leave before the world is saved on shutdown.
+const KICK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Notified once the server is asked to stop.
+static STOP: Notify = Notify::const_new();
+static STOPPING: AtomicBool = AtomicBool::new(false);
+/// Whether the chunks are saved automatically and after they are unloaded,
+/// and the players when they leave.
+///
+/// Turned on once the server started, and toggled by `save-on` and `save-off`.
+static SAVING: AtomicBool = AtomicBool::new(false);
+/// Whether the chunks unloaded with unsaved changes are being saved in the background.
+static SAVING_UNLOADED: AtomicBool = AtomicBool::new(false);
+
+/// The times of the recent ticks.
+pub static TICK_TIMES: Mutex<TickTimes> = Mutex::new(TickTimes::new());
+
+/// The start and the duration of the last ticks.
+pub struct TickTimes {
+    starts: VecDeque<Instant>,
+    durations: VecDeque<Duration>,
+}
+
+impl TickTimes {
+    const fn new() -> TickTimes {
+        TickTimes {
+            starts: VecDeque::new(),
+            durations: VecDeque::new(),
+        }
+    }
+
+    pub fn record(&mut self, start: Instant, duration: Duration) {
+        if self.starts.len() == TICK_SAMPLES {
+            self.starts.pop_front();
+            self.durations.pop_front();
+        }
+        self.starts.push_back(start);
+        self.durations.push_back(duration);
+    }
+
+    /// Gets the ticks run per second, at most [`TICKS_PER_SECOND`].
+    pub fn get_tps(&self) -> f64 {
+        match (self.starts.front(), self.starts.back()) {
+            (Some(first), Some(last)) if first != last => {
+                let elapsed = last.duration_since(*first).as_secs_f64();
+                ((self.starts.len() - 1) as f64 / elapsed).min(TICKS_PER_SECOND)
+            }
+            _ => TICKS_PER_SECOND,
+        }
+    }
+
+    /// Gets the average duration of a tick, in milliseconds.
+    pub fn get_mspt(&self) -> f64 {
+        if self.durations.is_empty() {
+            return 0.0;
+        }
+        let total: Duration = self.durations.iter().sum();
+        total.as_secs_f64() * 1000.0 / self.durations.len() as f64
+    }
+}
+
+/// Asks the server to stop.
+///
+/// The players are kicked and the world is saved before the process exits.
+pub fn stop() {
+    STOPPING.store(true, Ordering::Release);
+    STOP.notify_one();
+}
+
+/// Whether the server was asked to stop.
+pub fn is_stopping() -> bool {
+    STOPPING.load(Ordering::Acquire)
+}
+
+/// Waits until the server is asked to stop.
+pub(crate) async fn wait_for_stop() {
+    STOP.notified().await;
+}
+
+/// Saves the players in the game and the world.
+///
+/// The world is saved even if the automatic saving is turned off.
+pub fn save_all() -> anyhow::Result<()> {
+    let players = save_players();
+    WORLD.save()?;
+    players
+}
+
+/// Turns the automatic saving on or off.
+///
+/// # Returns
+/// Whether the automatic saving was on before.
+pub fn set_saving(saving: bool) -> bool {
+    SAVING.swap(saving, Ordering::AcqRel)
+}
+
+/// Whether the chunks are saved automatically and when unloaded,
+/// and the players when they leave.
+pub fn is_saving() -> bool {
+    SAVING.load(Ordering::Acquire)
+}
+
+/// Saves the world in the background every [`AUTOSAVE_INTERVAL`] ticks.
+pub(crate) fn autosave(tick: u64) {
+    let interval = *AUTOSAVE_INTERVAL;
+    if interval == 0 || !tick.is_multiple_of(interval) || !is_saving() {
+        return;
+    }
+    tokio::task::spawn_blocking(|| {
+        if let Err(err) = save_all() {
+            warn!("Error in saving the world: {:?}", err);
+        }
+    });
+}
+
+/// Saves the chunks unloaded with unsaved changes in the background, called every tick.
+///
+/// Does nothing while the chunks unloaded earlier are still being saved,
+/// or while saving is off, the chunks then waiting until it is turned on or the world is saved.
+pub(crate) fn save_unloaded_chunks() {
+    if !is_saving()
+        || !WORLD
+            .dimensions
+            .iter()
+            .any(|dimension| dimension.storage.has_unloaded())
+        || SAVING_UNLOADED.swap(true, Ordering::AcqRel)
+    {
+        return;
+    }
+    tokio::task::spawn_blocking(|| {
+        for dimension in WORLD.dimensions.iter() {
+            // Each failure is logged with its chunk.
+            let _ = dimension.storage.save_unloaded();
+        }
+        SAVING_UNLOADED.store(false, Ordering::Release);
+    });
+}
+
+/// Kicks every player and saves the world, before the process exits.
+pub(crate) async fn shutdown() {
+    info!("Stopping the server");
+    map_players(|player| {
+        let _ = player
+            .connection
+            .send_update(PlayerUpdate::Kick(TextComponent::text("Server closed")));
+    });
+    let start = Instant::now();
+    while !PLAYERS.is_empty() && start.elapsed() < KICK_TIMEOUT {
+        sleep(Duration::from_millis(50)).await;
+    }
+    info!("Saving the world");
+    if let Err(err) = save_all() {
+        warn!("Error in saving the world: {:?}", err);
+    }
+}
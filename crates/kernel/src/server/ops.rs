@@ -0,0 +1,109 @@
+use crate::config::OP_PERMISSION_LEVEL;
+use parking_lot::Mutex;
+use serde_derive::{Deserialize, Serialize};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use tracing::warn;
+use uuid::Uuid;
+
+/// The operators of the server, kept in `ops.json` next to `config.toml`.
+pub static OPS: LazyLock<Ops> = LazyLock::new(|| Ops::load(Path::new("./ops.json")));
+
+/// An operator, as written in `ops.json` by vanilla.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OpEntry {
+    pub uuid: Uuid,
+    pub name: String,
+    pub level: u8,
+    #[serde(default)]
+    pub bypasses_player_limit: bool,
+}
+
+/// The list of operators, saved to its file whenever it changes.
+pub struct Ops {
+    path: PathBuf,
+    entries: Mutex<Vec<OpEntry>>,
+}
+
+impl Ops {
+    /// Reads the operators from a file.
+    ///
+    /// # Returns
+    /// No operators if the file does not exist or cannot be read.
+    pub fn load(path: &Path) -> Ops {
+        let entries = match std::fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|err| {
+                warn!("Failed to read the operators from {:?}: {:?}", path, err);
+                Vec::new()
+            }),
+            Err(err) if err.kind() == ErrorKind::NotFound => Vec::new(),
+            Err(err) => {
+                warn!("Failed to read the operators from {:?}: {:?}", path, err);
+                Vec::new()
+            }
+        };
+        Ops {
+            path: path.to_path_buf(),
+            entries: Mutex::new(entries),
+        }
+    }
+
+    /// Gets the permission level of a player, `None` if it is not an operator.
+    pub fn get_level(&self, uuid: Uuid) -> Option<u8> {
+        self.entries
+            .lock()
+            .iter()
+            .find(|entry| entry.uuid == uuid)
+            .map(|entry| entry.level)
+    }
+
+    /// Finds an operator by its name, ignoring the case.
+    pub fn find(&self, name: &str) -> Option<OpEntry> {
+        self.entries
+            .lock()
+            .iter()
+            .find(|entry| entry.name.eq_ignore_ascii_case(name))
+            .cloned()
+    }
+
+    /// Makes a player an operator with the `op-permission-level` of the config.
+    ///
+    /// # Returns
+    /// Whether the player was not already an operator.
+    pub fn add(&self, uuid: Uuid, name: &str) -> anyhow::Result<bool> {
+        let mut entries = self.entries.lock();
+        if entries.iter().any(|entry| entry.uuid == uuid) {
+            return Ok(false);
+        }
+        entries.push(OpEntry {
+            uuid,
+            name: name.to_string(),
+            level: *OP_PERMISSION_LEVEL,
+            bypasses_player_limit: false,
+        });
+        self.save(&entries)?;
+        Ok(true)
+    }
+
+    /// Makes a player no longer an operator.
+    ///
+    /// # Returns
+    /// Whether the player was an operator.
+    pub fn remove(&self, uuid: Uuid) -> anyhow::Result<bool> {
+        let mut entries = self.entries.lock();
+        let len = entries.len();
+        entries.retain(|entry| entry.uuid != uuid);
+        if entries.len() == len {
+            return Ok(false);
+        }
+        self.save(&entries)?;
+        Ok(true)
+    }
+
+    fn save(&self, entries: &[OpEntry]) -> anyhow::Result<()> {
+        std::fs::write(&self.path, serde_json::to_string_pretty(entries)?)?;
+        Ok(())
+    }
+}
@@ -1 +1,3 @@
+mod network_test;
+mod server_test;
 mod world_test;
@@ -0,0 +1,698 @@
+mod compression {
+    #[tokio::test]
+    async fn frame_round_trip() {
+        use crate::network::compression::{decompress_packet, frame_packet};
+        use crate::util::io::ReadExt;
+
+        let small = vec![0x01, 0x02, 0x03];
+        let frame = frame_packet(&small, Some(256)).await.unwrap();
+        let mut body = frame.as_slice();
+        assert_eq!(body.read_var_int().await.unwrap() as usize, body.len());
+        assert_eq!(decompress_packet(body, 256).await.unwrap(), small);
+
+        let large = vec![0x2A; 4096];
+        let frame = frame_packet(&large, Some(256)).await.unwrap();
+        assert!(frame.len() < large.len());
+        let mut body = frame.as_slice();
+        assert_eq!(body.read_var_int().await.unwrap() as usize, body.len());
+        assert_eq!(decompress_packet(body, 256).await.unwrap(), large);
+    }
+
+    #[tokio::test]
+    async fn reject_below_threshold() {
+        use crate::network::compression::{decompress_packet, frame_packet};
+        use crate::util::io::ReadExt;
+
+        let frame = frame_packet(&[0x2A; 64], Some(16)).await.unwrap();
+        let mut body = frame.as_slice();
+        body.read_var_int().await.unwrap();
+        assert!(decompress_packet(body, 256).await.is_err());
+    }
+}
+mod auth {
+    #[test]
+    fn hex_digest() {
+        use crate::network::auth::hex_digest;
+        use sha1::{Digest, Sha1};
+
+        let digest = |name: &str| hex_digest(&Sha1::digest(name.as_bytes()));
+        assert_eq!(digest("Notch"), "4ed1f46bbe04bc756bcb17c0c7ce3e4632f06a48");
+        assert_eq!(digest("jeb_"), "-7c9d5b0044c130109a5d7b5fb5c317c02b4e28c1");
+        assert_eq!(digest("simon"), "88e16a1019277b15d58faf0541e11910eb756f6");
+    }
+
+    #[tokio::test]
+    async fn mock_session_server() {
+        use crate::network::auth::{MojangSessionServer, SessionServer};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = vec![0; 1024];
+            let len = socket.read(&mut request).await.unwrap();
+            let request = String::from_utf8_lossy(&request[..len]).to_string();
+            assert!(request
+                .starts_with("GET /session/minecraft/hasJoined?username=Notch&serverId=-1234 "));
+            let body = r#"{"id":"069a79f444e94726a5befca90e38aaf5","name":"Notch","properties":[{"name":"textures","value":"e30=","signature":"c2ln"}]}"#;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+        });
+        let profile = MojangSessionServer::new(url)
+            .has_joined("Notch", "-1234")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(profile.name, "Notch");
+        assert_eq!(profile.id.as_u128(), 0x069a79f444e94726a5befca90e38aaf5);
+        assert_eq!(profile.properties[0].name, "textures");
+        assert_eq!(profile.properties[0].signature.as_deref(), Some("c2ln"));
+    }
+}
+mod forwarding {
+    #[test]
+    fn legacy_forwarding() {
+        use crate::network::forwarding::parse_legacy_forwarding;
+
+        let forwarded = parse_legacy_forwarding(
+            "play.example.com\u{0}203.0.113.7\u{0}069a79f444e94726a5befca90e38aaf5\u{0}[{\"name\":\"textures\",\"value\":\"e30=\"}]",
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(forwarded.address.to_string(), "203.0.113.7");
+        assert_eq!(forwarded.uuid.as_u128(), 0x069a79f444e94726a5befca90e38aaf5);
+        assert_eq!(forwarded.properties[0].name, "textures");
+        assert!(parse_legacy_forwarding("play.example.com")
+            .unwrap()
+            .is_none());
+        assert!(parse_legacy_forwarding("play.example.com\u{0}203.0.113.7\u{0}invalid").is_err());
+    }
+
+    #[tokio::test]
+    async fn modern_forwarding() {
+        use crate::config::FORWARDING_SECRET;
+        use crate::network::forwarding::parse_modern_forwarding;
+        use crate::util::io::WriteExt;
+        use hmac::{Hmac, Mac};
+        use sha2::Sha256;
+        use tokio::io::AsyncWriteExt;
+
+        let mut info = Vec::new();
+        info.write_var_int(1).await.unwrap();
+        info.write_str("203.0.113.7").await.unwrap();
+        info.write_u128(0x069a79f444e94726a5befca90e38aaf5)
+            .await
+            .unwrap();
+        info.write_str("Notch").await.unwrap();
+        info.write_var_int(0).await.unwrap();
+        let mut mac = Hmac::<Sha256>::new_from_slice(FORWARDING_SECRET.as_bytes()).unwrap();
+        mac.update(&info);
+        let mut data = mac.finalize().into_bytes().to_vec();
+        data.extend_from_slice(&info);
+
+        let forwarded = parse_modern_forwarding(&data).await.unwrap().unwrap();
+        assert_eq!(forwarded.address.to_string(), "203.0.113.7");
+        assert_eq!(forwarded.username.as_deref(), Some("Notch"));
+
+        data[0] ^= 1;
+        assert!(parse_modern_forwarding(&data).await.unwrap().is_none());
+    }
+}
+mod chunk_batch {
+    #[test]
+    fn pacing() {
+        use crate::network::chunk_batch::ChunkSender;
+        use crate::util::to_dim_xz;
+        use crate::world::chunk::Chunk;
+        use crate::world::dimension::Dimension;
+        use std::sync::Arc;
+
+        let dimension = Dimension::new(
+            crate::registry::dimension_type::DIMENSION_TYPES
+                .get("minecraft:overworld")
+                .unwrap()
+                .clone(),
+            "overworld".to_string(),
+            0,
+        );
+        let mut sender = ChunkSender::new();
+        for x in 0..20 {
+            sender.push(Arc::new(Chunk::new(&dimension, to_dim_xz(x, 0))));
+        }
+        // Only one batch is sent before the client reports its rate.
+        assert_eq!(sender.next_batch().unwrap().len(), 9);
+        assert!(sender.next_batch().is_none());
+
+        sender.on_batch_received(2.5);
+        assert_eq!(sender.next_batch().unwrap().len(), 2);
+        assert_eq!(sender.next_batch().unwrap().len(), 2);
+
+        // A client asking for nothing still gets a chunk every hundred ticks.
+        sender.on_batch_received(0.0);
+        let mut ticks = 0;
+        while sender.next_batch().is_none() {
+            ticks += 1;
+        }
+        assert!(ticks < 100);
+    }
+}
+mod text {
+    #[test]
+    fn network_nbt() {
+        use crate::util::text::TextComponent;
+        assert_eq!(
+            TextComponent::text("Hi").to_network_nbt(),
+            vec![0x0A, 0x08, 0, 4, b't', b'e', b'x', b't', 0, 2, b'H', b'i', 0]
+        );
+    }
+}
+mod disconnect {
+    #[tokio::test]
+    async fn player_quit() {
+        use crate::entity::player::Player;
+        use crate::entity::Entity;
+        use crate::gameplay::player_quit;
+        use crate::network::connection::Connection;
+        use crate::network::outbound::ConnectionHandle;
+        use crate::WORLD;
+        use parking_lot::Mutex;
+        use std::sync::Arc;
+        use tokio::net::{TcpListener, TcpStream};
+        use uuid::Uuid;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let _client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (socket, address) = listener.accept().await.unwrap();
+        let (read, _write) = socket.into_split();
+        let (handle, receivers) = ConnectionHandle::new();
+        let mut connection =
+            Connection::new(read, Some(address), handle.clone(), receivers.updates);
+
+        let eid = WORLD.entities.generate_eid();
+        let player = Arc::new(Mutex::new(Player::new(
+            eid,
+            0,
+            handle,
+            (0.0, 0.0, 0.0),
+            Uuid::nil(),
+        )));
+        let chunk = WORLD.dimensions[0].get_chunk(0, 0);
+        {
+            let mut player = player.lock();
+            chunk.player_enter(&mut player);
+            player.chunks.push(chunk.clone());
+        }
+        WORLD
+            .entities
+            .spawn(&(player.clone() as Arc<Mutex<dyn Entity>>));
+        connection.player = Some(player.clone());
+
+        player_quit(&mut connection).await;
+        assert!(connection.player.is_none());
+        assert!(WORLD.entities.get_mut(eid).is_none());
+        assert!(player.lock().chunks.is_empty());
+        assert!(player.lock().recv.remove(0).is_none());
+    }
+}
+mod codec {
+    #[test]
+    fn reassemble_frames() {
+        use crate::network::codec::FrameDecoder;
+        let mut decoder = FrameDecoder::new();
+        decoder.buffer().extend_from_slice(&[3, 0x00, 1]);
+        assert!(decoder.next_frame().unwrap().is_none());
+        decoder.buffer().extend_from_slice(&[2, 1, 0x05]);
+        assert_eq!(&decoder.next_frame().unwrap().unwrap()[..], &[0x00, 1, 2]);
+        assert_eq!(&decoder.next_frame().unwrap().unwrap()[..], &[0x05]);
+        assert!(decoder.next_frame().unwrap().is_none());
+    }
+
+    #[test]
+    fn reject_invalid_lengths() {
+        use crate::network::codec::FrameDecoder;
+        let mut decoder = FrameDecoder::new();
+        // 2097152, one more than the maximum.
+        decoder
+            .buffer()
+            .extend_from_slice(&[0x80, 0x80, 0x80, 0x01]);
+        assert!(decoder.next_frame().is_err());
+
+        let mut decoder = FrameDecoder::new();
+        decoder
+            .buffer()
+            .extend_from_slice(&[0xFF, 0xFF, 0xFF, 0xFF, 0x0F]);
+        assert!(decoder.next_frame().is_err());
+
+        let mut decoder = FrameDecoder::new();
+        decoder
+            .buffer()
+            .extend_from_slice(&[0x80, 0x80, 0x80, 0x80, 0x80, 0x01]);
+        assert!(decoder.next_frame().is_err());
+    }
+
+    #[tokio::test]
+    async fn read_str_limits() {
+        use crate::util::io::{ReadExt, WriteExt};
+        let mut buf = Vec::new();
+        buf.write_str("Spot").await.unwrap();
+        assert_eq!(buf.as_slice().read_str().await.unwrap(), "Spot");
+        // Truncated string.
+        assert!((&buf[..3]).read_str().await.is_err());
+
+        let mut buf = Vec::new();
+        buf.write_str(&"a".repeat(32768)).await.unwrap();
+        assert!(buf.as_slice().read_str().await.is_err());
+
+        let mut buf = Vec::new();
+        buf.write_var_int(-1).await.unwrap();
+        assert!(buf.as_slice().read_str().await.is_err());
+    }
+}
+mod outbound {
+    #[tokio::test]
+    async fn write_in_order() {
+        use crate::network::outbound::{write_loop, ConnectionHandle};
+        use bytes::Bytes;
+        use tokio::io::AsyncReadExt;
+
+        let (handle, receivers) = ConnectionHandle::new();
+        for id in 1..=3 {
+            handle.try_send_frame(Bytes::from(vec![1, id])).unwrap();
+        }
+        handle.close();
+        let (mut client, server) = tokio::io::duplex(64);
+        write_loop(server, receivers.outbound, receivers.kick)
+            .await
+            .unwrap();
+        let mut written = Vec::new();
+        client.read_to_end(&mut written).await.unwrap();
+        assert_eq!(written, [1, 1, 1, 2, 1, 3]);
+        assert!(handle.try_send_frame(Bytes::new()).is_err());
+    }
+
+    #[tokio::test]
+    async fn kick_when_full() {
+        use crate::network::outbound::{write_loop, ConnectionHandle, OUTBOUND_QUEUE_CAPACITY};
+        use bytes::Bytes;
+        use tokio::io::AsyncReadExt;
+
+        let (handle, receivers) = ConnectionHandle::new();
+        let frame = Bytes::from_static(&[1, 0]);
+        for _ in 0..OUTBOUND_QUEUE_CAPACITY {
+            handle.try_send_frame(frame.clone()).unwrap();
+        }
+        assert!(handle.try_send_frame(frame).is_err());
+        let (mut client, server) = tokio::io::duplex(64);
+        write_loop(server, receivers.outbound, receivers.kick)
+            .await
+            .unwrap();
+        let mut written = Vec::new();
+        client.read_to_end(&mut written).await.unwrap();
+        assert!(written.is_empty());
+    }
+
+    #[tokio::test]
+    async fn kick_when_not_reading() {
+        use crate::network::outbound::{write_loop, ConnectionHandle};
+        use bytes::Bytes;
+        use std::time::Duration;
+
+        let (handle, receivers) = ConnectionHandle::new();
+        handle.try_send_frame(Bytes::from(vec![0; 1024])).unwrap();
+        handle.close();
+        // The client never reads, so the writer is stuck once the pipe is full.
+        let (_client, server) = tokio::io::duplex(64);
+        let kick = receivers.kick.clone();
+        let writer = tokio::spawn(write_loop(server, receivers.outbound, receivers.kick));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!writer.is_finished());
+        kick.notify_one();
+        tokio::time::timeout(Duration::from_secs(1), writer)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+    }
+}
+mod block_update {
+    #[tokio::test]
+    async fn merge_section_changes() {
+        use crate::entity::player::Player;
+        use crate::gameplay::send_block_updates;
+        use crate::network::connection::Connection;
+        use crate::network::outbound::{ConnectionHandle, Outbound};
+        use crate::util::io::ReadExt;
+        use crate::WORLD;
+        use parking_lot::Mutex;
+        use std::sync::Arc;
+        use tokio::net::{TcpListener, TcpStream};
+        use uuid::Uuid;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let _client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (socket, address) = listener.accept().await.unwrap();
+        let (read, _write) = socket.into_split();
+        let (handle, mut receivers) = ConnectionHandle::new();
+        let mut connection =
+            Connection::new(read, Some(address), handle.clone(), receivers.updates);
+        let mut player = Player::new(0, 0, handle, (1600.0, 0.0, 1600.0), Uuid::nil());
+        let chunk = WORLD.dimensions[0].get_chunk(100, 100);
+        chunk.player_enter(&mut player);
+        player.chunks.push(chunk.clone());
+        connection.player = Some(Arc::new(Mutex::new(player)));
+
+        chunk.set_block(0, 64, 0, 9).unwrap();
+        chunk.set_block(1, 65, 0, 9).unwrap();
+        chunk.set_block(1, 65, 0, 10).unwrap();
+        chunk.set_block(0, 96, 0, 9).unwrap();
+        send_block_updates(&mut connection).await.unwrap();
+
+        let mut ids = Vec::new();
+        while let Ok(Outbound::Frame(frame)) = receivers.outbound.try_recv() {
+            let mut frame = &frame[1..];
+            ids.push(frame.read_var_int().await.unwrap());
+        }
+        ids.sort();
+        assert_eq!(ids, vec![0x09, 0x49]);
+        chunk.player_exit(&mut connection.player.unwrap().lock());
+    }
+
+    #[tokio::test]
+    async fn encode_section_blocks() {
+        use crate::network::packet::s2c::update_section_blocks::UpdateSectionBlocksS2C;
+        use crate::network::packet::Encode;
+
+        let mut buf = Vec::new();
+        UpdateSectionBlocksS2C {
+            section_x: -1,
+            section_y: -4,
+            section_z: 2,
+            blocks: vec![(0x0F3, 9)],
+        }
+        .encode(&mut buf)
+        .await
+        .unwrap();
+        assert_eq!(&buf[..8], &0xFFFFFC00002FFFFCu64.to_be_bytes());
+        assert_eq!(&buf[8..], &[1, 0xF3, 0xA1, 0x02]);
+    }
+}
+mod tracker {
+    #[tokio::test]
+    async fn track_entity() {
+        use crate::entity::metadata::flags;
+        use crate::entity::player::Player;
+        use crate::entity::tracker::EntityTracker;
+        use crate::entity::Entity;
+        use crate::network::outbound::{ConnectionHandle, Outbound};
+        use crate::util::io::ReadExt;
+        use crate::WORLD;
+        use parking_lot::Mutex;
+        use std::sync::Arc;
+        use uuid::Uuid;
+
+        let (handle, mut receivers) = ConnectionHandle::new();
+        let mut ids = async || {
+            let mut ids = Vec::new();
+            while let Ok(Outbound::Frame(frame)) = receivers.outbound.try_recv() {
+                let mut frame = &frame[1..];
+                ids.push(frame.read_var_int().await.unwrap());
+            }
+            ids
+        };
+        let pos = (-20000.0, 0.0, -20000.0);
+        let eid = WORLD.entities.generate_eid();
+        let entity = Arc::new(Mutex::new(Player::new(
+            eid,
+            0,
+            handle.clone(),
+            (pos.0 + 4.0, pos.1, pos.2),
+            Uuid::new_v4(),
+        )));
+        WORLD
+            .entities
+            .spawn(&(entity.clone() as Arc<Mutex<dyn Entity>>));
+        let mut tracker = EntityTracker::new();
+
+        tracker.update(&handle, 0, 0, pos).await.unwrap();
+        assert_eq!(ids().await, vec![0x01, 0x58, 0x48]);
+        tracker.update(&handle, 0, 0, pos).await.unwrap();
+        assert!(ids().await.is_empty());
+
+        entity.lock().set_position(pos.0 + 5.0, pos.1, pos.2);
+        tracker.update(&handle, 0, 0, pos).await.unwrap();
+        assert_eq!(ids().await, vec![0x2E]);
+
+        entity.lock().set_position(pos.0 - 5.0, pos.1, pos.2);
+        tracker.update(&handle, 0, 0, pos).await.unwrap();
+        assert_eq!(ids().await, vec![0x70]);
+
+        entity.lock().set_flag(flags::CROUCHING, true);
+        entity.lock().set_flag(flags::CROUCHING, true);
+        tracker.update(&handle, 0, 0, pos).await.unwrap();
+        assert_eq!(ids().await, vec![0x58]);
+
+        // The range is a square on x and z, whatever the height.
+        entity
+            .lock()
+            .set_position(pos.0 + 40.0, pos.1 + 100.0, pos.2 + 40.0);
+        tracker.update(&handle, 0, 0, pos).await.unwrap();
+        assert_eq!(ids().await, vec![0x70]);
+
+        entity.lock().set_position(pos.0 + 50.0, pos.1, pos.2);
+        tracker.update(&handle, 0, 0, pos).await.unwrap();
+        assert_eq!(ids().await, vec![0x42]);
+
+        WORLD.entities.remove(eid);
+        tracker.update(&handle, 0, 0, pos).await.unwrap();
+        assert!(ids().await.is_empty());
+    }
+}
+mod metadata {
+    #[tokio::test]
+    async fn dirty_fields() {
+        use crate::entity::metadata::{EntityMetadata, Pose, HEALTH, POSE};
+        use crate::network::packet::s2c::set_entity_metadata::SetEntityMetadataS2C;
+        use crate::network::packet::Encode;
+
+        let mut metadata = EntityMetadata::new();
+        metadata.set(HEALTH, 20.0);
+        metadata.set(POSE, Pose::Sneaking);
+        let version = metadata.get_version();
+        metadata.set(HEALTH, 20.0);
+        assert!(metadata.changes_since(version).is_empty());
+        metadata.set(POSE, Pose::Standing);
+        assert_eq!(metadata.get(POSE), Some(Pose::Standing));
+
+        let mut buf = Vec::new();
+        SetEntityMetadataS2C {
+            entity_id: 1,
+            metadata: metadata.changes_since(version),
+        }
+        .encode(&mut buf)
+        .await
+        .unwrap();
+        assert_eq!(buf, [1, 6, 21, 0, 0xFF]);
+    }
+}
+mod player_info {
+    #[tokio::test]
+    async fn encode_actions() {
+        use crate::network::packet::s2c::player_info_update::{
+            actions, PlayerInfoEntry, PlayerInfoUpdateS2C,
+        };
+        use crate::network::packet::Encode;
+        use uuid::Uuid;
+
+        let entry = || PlayerInfoEntry {
+            uuid: Uuid::from_u128(1),
+            username: "Steve".to_string(),
+            properties: Vec::new(),
+            chat_session: None,
+            game_mode: 1,
+            listed: true,
+            latency: 300,
+            display_name: None,
+        };
+        let mut buf = Vec::new();
+        PlayerInfoUpdateS2C {
+            actions: actions::ALL,
+            entries: vec![entry()],
+        }
+        .encode(&mut buf)
+        .await
+        .unwrap();
+        assert_eq!(&buf[..2], &[0x3F, 1]);
+        assert_eq!(&buf[2..18], &1u128.to_be_bytes());
+        assert_eq!(
+            &buf[18..],
+            &[5, b'S', b't', b'e', b'v', b'e', 0, 0, 1, 1, 0xAC, 0x02, 0]
+        );
+
+        let mut buf = Vec::new();
+        PlayerInfoUpdateS2C {
+            actions: actions::UPDATE_LATENCY,
+            entries: vec![entry()],
+        }
+        .encode(&mut buf)
+        .await
+        .unwrap();
+        assert_eq!(&buf[18..], &[0xAC, 0x02]);
+    }
+}
+mod chat {
+    #[tokio::test]
+    async fn kick_spam_and_illegal_characters() {
+        use crate::entity::player::Player;
+        use crate::gameplay::chat::{player_chat, PlayerMessage};
+        use crate::network::chat_session::LastSeenUpdate;
+        use crate::network::connection::Connection;
+        use crate::network::outbound::ConnectionHandle;
+        use parking_lot::Mutex;
+        use std::sync::Arc;
+        use tokio::net::{TcpListener, TcpStream};
+        use uuid::Uuid;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let _client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (socket, address) = listener.accept().await.unwrap();
+        let (read, _write) = socket.into_split();
+        let (handle, receivers) = ConnectionHandle::new();
+        let mut connection =
+            Connection::new(read, Some(address), handle.clone(), receivers.updates);
+        connection.player = Some(Arc::new(Mutex::new(Player::new(
+            0,
+            0,
+            handle,
+            (0.0, 0.0, 0.0),
+            Uuid::nil(),
+        ))));
+
+        let message = |message: &str| PlayerMessage {
+            message: message.to_string(),
+            timestamp: 0,
+            salt: 0,
+            signature: None,
+            last_seen: LastSeenUpdate::default(),
+        };
+        for _ in 0..10 {
+            player_chat(&mut connection, message("Hi")).await.unwrap();
+        }
+        assert!(!connection.closed);
+        player_chat(&mut connection, message("Hi")).await.unwrap();
+        assert!(connection.closed);
+
+        connection.closed = false;
+        connection.chat_spam = 0;
+        player_chat(&mut connection, message("§cHi")).await.unwrap();
+        assert!(connection.closed);
+    }
+}
+mod chat_session {
+    #[test]
+    fn last_seen_window() {
+        use crate::network::chat_session::{LastSeenMessages, LastSeenUpdate};
+
+        let mut last_seen = LastSeenMessages::new();
+        for i in 0..3 {
+            last_seen.add_pending(&[i; 256]);
+        }
+        // The window can only advance past the messages that were sent.
+        assert!(last_seen.apply_offset(4).is_err());
+        assert!(last_seen.apply_offset(3).is_ok());
+
+        let seen = last_seen
+            .apply_update(&LastSeenUpdate {
+                offset: 0,
+                acknowledged: [0x00, 0x00, 0x0C],
+            })
+            .unwrap();
+        assert_eq!(seen, vec![[1; 256], [2; 256]]);
+        // A message acknowledged once can't be ignored later.
+        assert!(last_seen
+            .apply_update(&LastSeenUpdate {
+                offset: 0,
+                acknowledged: [0x00, 0x00, 0x04],
+            })
+            .is_err());
+    }
+}
+
+mod command {
+    #[test]
+    fn dispatch() {
+        use crate::command::argument::ArgumentType;
+        use crate::command::dispatcher::{argument, literal, CommandDispatcher};
+        use crate::command::CommandSource;
+
+        let mut dispatcher = CommandDispatcher::new();
+        dispatcher.register(
+            literal("give").then(
+                argument(
+                    "count",
+                    ArgumentType::Integer {
+                        min: Some(1),
+                        max: Some(64),
+                    },
+                )
+                .executes(|context| context.get_integer("count")),
+            ),
+        );
+        dispatcher.register(literal("op").requires(|_| false));
+        dispatcher.register_alias("g", "give").unwrap();
+        let source = CommandSource::Console;
+
+        assert_eq!(dispatcher.execute(&source, "give 5"), Ok(5));
+        assert_eq!(dispatcher.execute(&source, "g 7"), Ok(7));
+        let err = dispatcher.execute(&source, "give 65").unwrap_err();
+        assert_eq!(err.context, Some(("give 65".to_string(), 5)));
+        assert!(dispatcher.execute(&source, "give").is_err());
+        assert!(dispatcher.execute(&source, "take 5").is_err());
+        assert!(dispatcher.execute(&source, "op").is_err());
+
+        assert_eq!(
+            dispatcher.suggest(&source, "gi"),
+            (0, vec!["give".to_string()])
+        );
+        assert_eq!(dispatcher.suggest(&source, "").1.len(), 2);
+        // The root, give, <count> and g.
+        assert_eq!(dispatcher.get_nodes(&source).len(), 4);
+    }
+}
+mod legacy_ping {
+    #[tokio::test]
+    async fn split_legacy_ping() {
+        use crate::network::legacy_ping::is_legacy_ping;
+        use std::time::Duration;
+        use tokio::io::AsyncWriteExt;
+        use tokio::net::{TcpListener, TcpStream};
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let head: [&[u8]; 3] = [&[0xFE, 0x01, 0xFA], &[0xFE, 0x01, 0x00], &[0x10, 0x00]];
+        for (bytes, legacy) in head.into_iter().zip([true, false, false]) {
+            let mut client = TcpStream::connect(address).await.unwrap();
+            let (server, _) = listener.accept().await.unwrap();
+            // The first byte arrives alone, like from a slow connection.
+            client.write_all(&bytes[..1]).await.unwrap();
+            let check = tokio::spawn(async move {
+                let legacy = is_legacy_ping(&server).await.unwrap();
+                (legacy, server)
+            });
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            client.write_all(&bytes[1..]).await.unwrap();
+            assert_eq!(check.await.unwrap().0, legacy);
+        }
+    }
+}
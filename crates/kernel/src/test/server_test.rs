@@ -0,0 +1,23 @@
+mod ops {
+    #[test]
+    fn ops_json() {
+        use crate::config::OP_PERMISSION_LEVEL;
+        use crate::server::ops::Ops;
+        use uuid::Uuid;
+
+        let path = std::env::temp_dir().join(format!("spot-ops-{}.json", std::process::id()));
+        let uuid = Uuid::from_u128(1);
+        let ops = Ops::load(&path);
+        assert_eq!(ops.get_level(uuid), None);
+        assert!(ops.add(uuid, "Steve").unwrap());
+        assert!(!ops.add(uuid, "Steve").unwrap());
+
+        let ops = Ops::load(&path);
+        assert_eq!(ops.get_level(uuid), Some(*OP_PERMISSION_LEVEL));
+        assert_eq!(ops.find("steve").unwrap().uuid, uuid);
+        assert!(ops.remove(uuid).unwrap());
+        assert!(!ops.remove(uuid).unwrap());
+        assert_eq!(Ops::load(&path).get_level(uuid), None);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
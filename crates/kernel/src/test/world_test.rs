@@ -22,6 +22,24 @@ mod chunk {
         assert_eq!(chunk.get_block(15, 383, 15), Some(9));
         assert_eq!(chunk.get_block(0, 1, 0), Some(0));
     }
+    #[tokio::test]
+    async fn section_serialize() {
+        use crate::world::chunk::Section;
+        let section = Section::new();
+        let mut buf = Vec::new();
+        section.serialize(&mut buf).await.unwrap();
+        // Block count, single valued air, then the single valued biome.
+        assert_eq!(buf.len(), 8);
+        assert_eq!(&buf[..6], &[0, 0, 0, 0, 0, 0]);
+
+        section.set_state(1, 0, 0, 9);
+        buf.clear();
+        section.serialize(&mut buf).await.unwrap();
+        // Block count, 4 bits per entry, palette [0, 9], 256 longs.
+        assert_eq!(&buf[..8], &[0, 1, 4, 2, 0, 9, 0x80, 0x02]);
+        assert_eq!(&buf[8..16], &[0, 0, 0, 0, 0, 0, 0, 0x10]);
+        assert_eq!(buf.len(), 8 + 256 * 8 + 3);
+    }
 }
 mod gen {
     #[test]
@@ -55,6 +73,30 @@ mod gen {
         assert_eq!(chunk.get_block(0, 3, 0), Some(0));
         assert_eq!(chunk.get_block(15, 0, 15), Some(bedrock));
     }
+
+    #[tokio::test]
+    async fn frame_cache() {
+        use crate::util::to_dim_xz;
+        use crate::world::chunk::Chunk;
+        use crate::world::dimension::Dimension;
+
+        let dimension = Dimension::new(
+            crate::registry::dimension_type::DIMENSION_TYPES
+                .get("minecraft:overworld")
+                .unwrap()
+                .clone(),
+            "overworld".to_string(),
+            0,
+        );
+        let chunk = Chunk::new(&dimension, to_dim_xz(0, 0));
+        let frame = chunk.get_frame(0x27, None).await.unwrap();
+        assert_eq!(chunk.get_frame(0x27, None).await.unwrap(), frame);
+        // The cache is keyed by the packet id, along with the threshold.
+        let other = chunk.get_frame(0x28, None).await.unwrap();
+        assert_ne!(other, frame);
+        chunk.set_block(0, 0, 0, 1).unwrap();
+        assert_ne!(chunk.get_frame(0x27, None).await.unwrap(), frame);
+    }
 }
 mod dimension {
     #[test]
@@ -76,4 +118,511 @@ mod dimension {
         assert_eq!(dimension.get_block(1145, 14, 1919), Some(9));
         assert_eq!(dimension.get_block(0, -64, 0), Some(9));
     }
+
+    #[test]
+    fn reload_unloading_chunk() {
+        use crate::world::dimension::Dimension;
+        use std::sync::Arc;
+
+        let dimension = Dimension::new(
+            crate::registry::dimension_type::DIMENSION_TYPES
+                .get("minecraft:overworld")
+                .unwrap()
+                .clone(),
+            "spot:unloading".to_string(),
+            0,
+        );
+        let chunk = dimension.get_chunk(3, 4);
+        chunk.set_block(1, 100, 1, 9).unwrap();
+        let unloaded = Arc::downgrade(&chunk);
+        drop(chunk);
+        assert!(unloaded.upgrade().is_none());
+        assert!(dimension.storage.has_unloaded());
+        // Loaded again before it is saved, with its changes instead of the stored data.
+        let chunk = dimension.get_chunk(3, 4);
+        assert_eq!(chunk.get_block(1, 100, 1), Some(9));
+        chunk.set_block(2, 100, 2, 9).unwrap();
+        assert_eq!(dimension.chunks.len(), 1);
+        drop(chunk);
+
+        dimension.storage.save_unloaded().unwrap();
+        assert!(!dimension.storage.has_unloaded());
+        assert!(dimension.chunks.is_empty());
+        let chunk = dimension.get_chunk(3, 4);
+        assert!(!chunk.is_dirty());
+        assert_eq!(chunk.get_block(1, 100, 1), Some(9));
+        assert_eq!(chunk.get_block(2, 100, 2), Some(9));
+        drop(chunk);
+        let directory = crate::world::anvil::get_dimension_directory(
+            std::path::Path::new(*crate::config::LEVEL_NAME),
+            "spot:unloading",
+        );
+        std::fs::remove_dir_all(&directory).unwrap();
+        // The parent directories are only removed if nothing else was saved there.
+        for directory in directory.ancestors().skip(1) {
+            let _ = std::fs::remove_dir(directory);
+        }
+    }
+
+    #[test]
+    fn tickets() {
+        use crate::util::to_dim_xz;
+        use crate::world::dimension::Dimension;
+        use crate::world::ticket::{
+            self, LoadLevel, TicketType, MAX_LOADS_PER_TICK, PORTAL_TICKET_LIFETIME,
+        };
+
+        let dimension = Dimension::new(
+            crate::registry::dimension_type::DIMENSION_TYPES
+                .get("minecraft:overworld")
+                .unwrap()
+                .clone(),
+            "tickets".to_string(),
+            0,
+        );
+        let plugin = TicketType::Plugin("test".to_string());
+        dimension.add_ticket(10, -10, plugin.clone(), 2);
+        assert_eq!(dimension.get_load_level(10, -10), LoadLevel::Inaccessible);
+        dimension.tick_chunks();
+        assert_eq!(dimension.get_load_level(10, -10), LoadLevel::EntityTicking);
+        assert_eq!(dimension.get_load_level(11, -9), LoadLevel::Ticking);
+        assert_eq!(dimension.get_load_level(8, -12), LoadLevel::Border);
+        assert_eq!(dimension.get_load_level(7, -10), LoadLevel::Inaccessible);
+        // The chunks closest to the ticket are loaded first, the others in the next tick.
+        assert_eq!(dimension.chunks.len(), MAX_LOADS_PER_TICK);
+        assert!(dimension.chunks.contains_key(&to_dim_xz(10, -10)));
+        dimension.tick_chunks();
+        assert_eq!(dimension.chunks.len(), 25);
+
+        assert!(dimension.set_chunk_forced(7, -10, true));
+        assert!(!dimension.set_chunk_forced(7, -10, true));
+        assert!(dimension.remove_ticket(10, -10, &plugin));
+        assert!(!dimension.remove_ticket(10, -10, &plugin));
+        dimension.tick_chunks();
+        assert_eq!(dimension.get_load_level(8, -10), LoadLevel::Ticking);
+        assert_eq!(dimension.get_load_level(9, -10), LoadLevel::Border);
+        assert!(dimension.storage.has_unloaded());
+        assert_eq!(dimension.get_forced_chunks(), vec![(7, -10)]);
+        assert!(dimension.set_chunk_forced(7, -10, false));
+        dimension.tick_chunks();
+
+        dimension.add_ticket(0, 0, TicketType::Portal, 3);
+        for _ in 1..PORTAL_TICKET_LIFETIME {
+            dimension.tick_chunks();
+        }
+        assert_eq!(dimension.get_load_level(0, 0), LoadLevel::EntityTicking);
+        dimension.tick_chunks();
+        assert_eq!(dimension.get_load_level(0, 0), LoadLevel::Inaccessible);
+
+        let directory = std::env::temp_dir().join(format!("spot-forced-{}", std::process::id()));
+        let forced = vec![(-1, 2), (3, -4), (i32::MIN, i32::MAX)];
+        ticket::save_forced(&directory, &forced).unwrap();
+        assert_eq!(ticket::load_forced(&directory).unwrap(), forced);
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
+mod player {
+    #[test]
+    fn drop_far_chunks() {
+        use crate::config::VIEW_DISTANCE;
+        use crate::entity::player::Player;
+        use crate::entity::Entity;
+        use crate::network::outbound::ConnectionHandle;
+        use crate::world::dimension::Dimension;
+        use uuid::Uuid;
+
+        let dimension = Dimension::new(
+            crate::registry::dimension_type::DIMENSION_TYPES
+                .get("minecraft:overworld")
+                .unwrap()
+                .clone(),
+            "overworld".to_string(),
+            0,
+        );
+        let (handle, _receivers) = ConnectionHandle::new();
+        let mut player = Player::new(0, 0, handle, (-0.5, 0.0, 0.5), Uuid::nil());
+        assert_eq!(player.center_chunk, (-1, 0));
+        for x in -1..=1 {
+            let chunk = dimension.get_chunk(x * *VIEW_DISTANCE, 0);
+            chunk.player_enter(&mut player);
+            player.chunks.push(chunk);
+        }
+        player.set_position(16.0, 0.0, 0.0);
+        assert_eq!(player.update(), vec![(-*VIEW_DISTANCE, 0)]);
+        assert_eq!(player.center_chunk, (1, 0));
+        assert_eq!(player.chunks.len(), 2);
+    }
+}
+
+mod level {
+    #[test]
+    fn weather_cycle() {
+        use crate::world::level::LevelData;
+
+        let mut level_data = LevelData::default();
+        level_data.set_weather(true, true, Some(10));
+        for _ in 0..9 {
+            level_data.tick();
+        }
+        assert!(level_data.raining && level_data.thundering);
+        level_data.tick();
+        assert!(!level_data.raining && !level_data.thundering);
+        assert_eq!((level_data.game_time, level_data.day_time), (10, 10));
+
+        level_data.set_weather(false, false, Some(100));
+        for _ in 0..100 {
+            level_data.tick();
+            assert!(!level_data.raining);
+        }
+        assert_eq!(level_data.clear_weather_time, 0);
+    }
+
+    #[test]
+    fn level_dat() {
+        use crate::world::level::LevelData;
+
+        let mut level_data = LevelData::new("test", -1234);
+        level_data.spawn = (1, -60, -1);
+        level_data.spawn_angle = 90.0;
+        level_data.day_time = 6000;
+        level_data.set_weather(true, false, Some(500));
+        level_data.game_rules.set("doDaylightCycle", false);
+        level_data.game_rules.set("randomTickSpeed", 5);
+        let loaded = LevelData::deserialize(&level_data.serialize().unwrap()).unwrap();
+        assert_eq!(loaded.level_name, "test");
+        assert_eq!(loaded.world_gen_settings.seed, -1234);
+        assert_eq!(loaded.spawn, (1, -60, -1));
+        assert_eq!(loaded.spawn_angle, 90.0);
+        assert!(loaded.raining && !loaded.thundering);
+        assert_eq!(loaded.rain_time, 500);
+        assert_eq!(loaded.game_rules.get_int("randomTickSpeed"), 5);
+        assert!(loaded.game_rules.get_bool("doWeatherCycle"));
+        assert_eq!(loaded.get_hashed_seed(), level_data.get_hashed_seed());
+
+        let mut loaded = loaded;
+        loaded.tick();
+        assert_eq!((loaded.game_time, loaded.day_time), (1, 6000));
+    }
+
+    #[test]
+    fn player_data() {
+        use crate::entity::player::Player;
+        use crate::item::inventory::ItemStack;
+        use crate::network::outbound::ConnectionHandle;
+        use crate::world::player_data;
+        use simdnbt::owned::{NbtCompound, NbtTag};
+        use uuid::Uuid;
+
+        let directory =
+            std::env::temp_dir().join(format!("spot-player-data-{}", std::process::id()));
+        let uuid = Uuid::from_u128(0x0123_4567_89ab_cdef_0123_4567_89ab_cdef);
+        let (handle, _receivers) = ConnectionHandle::new();
+        let mut player = Player::new(0, 0, handle.clone(), (0.5, -60.0, 0.5), uuid);
+        player.entity.pos = (100.5, 64.0, -200.5);
+        player.entity.yaw = 90.0;
+        player.health = 7.5;
+        player.game_mode = 1;
+        player.previous_game_mode = 0;
+        player.abilities.update(1);
+        player.death_location = Some(("minecraft:overworld".to_string(), 1, 2, 3));
+        player
+            .inventory
+            .set(0, Some(ItemStack::new("minecraft:stone", 64)));
+        player
+            .inventory
+            .set(-106, Some(ItemStack::new("minecraft:shield", 1)));
+        player
+            .ender_chest
+            .set(26, Some(ItemStack::new("minecraft:diamond", 3)));
+        player.experience_level = 30;
+        player.experience_progress = 0.25;
+        player.saved_data.insert("foodLevel", 17);
+        player_data::save(&directory, &player).unwrap();
+        player_data::save(&directory, &player).unwrap();
+        assert!(directory
+            .join(format!("playerdata/{}.dat_old", uuid))
+            .is_file());
+
+        let saved_data = player_data::load(&directory, uuid).unwrap().unwrap();
+        assert_eq!(saved_data.int_array("UUID").unwrap()[0], 0x0123_4567);
+        let mut loaded = Player::new(1, 0, handle, (0.5, -60.0, 0.5), uuid);
+        player_data::read(&mut loaded, saved_data);
+        assert_eq!(loaded.entity.pos, (100.5, 64.0, -200.5));
+        assert_eq!(loaded.center_chunk, (6, -13));
+        assert_eq!((loaded.entity.yaw, loaded.health), (90.0, 7.5));
+        assert_eq!((loaded.game_mode, loaded.previous_game_mode), (1, 0));
+        assert_eq!(loaded.death_location, player.death_location);
+        assert_eq!(loaded.inventory.iter().count(), 2);
+        assert_eq!(
+            loaded.ender_chest.get(26),
+            Some(&ItemStack::new("minecraft:diamond", 3))
+        );
+        assert_eq!(loaded.experience_level, 30);
+        assert_eq!(loaded.abilities, player.abilities);
+        assert_eq!(loaded.saved_data.int("foodLevel"), Some(17));
+        let edited = NbtCompound::from_values(vec![
+            ("playerGameType".into(), NbtTag::Int(7)),
+            ("previousPlayerGameType".into(), NbtTag::Int(-5)),
+        ]);
+        player_data::read(&mut loaded, edited);
+        assert_eq!((loaded.game_mode, loaded.previous_game_mode), (3, -1));
+        assert!(player_data::load(&directory, Uuid::nil())
+            .unwrap()
+            .is_none());
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
+
+mod anvil {
+    #[test]
+    fn load_region() {
+        use crate::registry::protocol_id::get_block_state_id;
+        use crate::registry::BIOMES_INDEX;
+        use crate::world::anvil::RegionStorage;
+        use crate::world::dimension::Dimension;
+        use flate2::write::ZlibEncoder;
+        use flate2::Compression;
+        use simdnbt::owned::{BaseNbt, NbtCompound, NbtList, NbtTag};
+        use std::io::Write;
+
+        let oak_log = get_block_state_id("minecraft:oak_log", [("axis", "x")].into_iter()).unwrap();
+        let chunk_nbt = |x: i32| {
+            let mut block_light = vec![0u8; 2048];
+            block_light[0] = 0x0F;
+            let section = NbtCompound::from_values(vec![
+                ("Y".into(), NbtTag::Byte(-4)),
+                (
+                    "block_states".into(),
+                    NbtTag::Compound(NbtCompound::from_values(vec![
+                        (
+                            "palette".into(),
+                            NbtTag::List(NbtList::Compound(vec![
+                                NbtCompound::from_values(vec![(
+                                    "Name".into(),
+                                    NbtTag::String("minecraft:air".into()),
+                                )]),
+                                NbtCompound::from_values(vec![(
+                                    "Name".into(),
+                                    NbtTag::String("minecraft:stone".into()),
+                                )]),
+                                NbtCompound::from_values(vec![
+                                    ("Name".into(), NbtTag::String("minecraft:oak_log".into())),
+                                    (
+                                        "Properties".into(),
+                                        NbtTag::Compound(NbtCompound::from_values(vec![(
+                                            "axis".into(),
+                                            NbtTag::String("x".into()),
+                                        )])),
+                                    ),
+                                ]),
+                            ])),
+                        ),
+                        (
+                            "data".into(),
+                            NbtTag::LongArray({
+                                let mut data = vec![0; 256];
+                                data[0] = 0x21;
+                                data
+                            }),
+                        ),
+                    ])),
+                ),
+                (
+                    "biomes".into(),
+                    NbtTag::Compound(NbtCompound::from_values(vec![(
+                        "palette".into(),
+                        NbtTag::List(NbtList::String(vec!["minecraft:desert".into()])),
+                    )])),
+                ),
+                ("BlockLight".into(), NbtTag::ByteArray(block_light)),
+            ]);
+            let mut data = Vec::new();
+            BaseNbt::new(
+                "",
+                NbtCompound::from_values(vec![
+                    ("DataVersion".into(), NbtTag::Int(3953)),
+                    ("xPos".into(), NbtTag::Int(x)),
+                    ("zPos".into(), NbtTag::Int(-1)),
+                    ("Status".into(), NbtTag::String("minecraft:full".into())),
+                    (
+                        "sections".into(),
+                        NbtTag::List(NbtList::Compound(vec![section])),
+                    ),
+                ]),
+            )
+            .write(&mut data);
+            data
+        };
+
+        let directory = std::env::temp_dir().join(format!("spot-anvil-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        // Chunk [1, -1] is compressed with zlib inside the region file.
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&chunk_nbt(1)).unwrap();
+        let zlib = encoder.finish().unwrap();
+        let mut region = vec![0u8; 8192];
+        region[993 * 4..994 * 4].copy_from_slice(&(2u32 << 8 | 1).to_be_bytes());
+        region.extend_from_slice(&(zlib.len() as u32 + 1).to_be_bytes());
+        region.push(2);
+        region.extend_from_slice(&zlib);
+        region.resize(3 * 4096, 0);
+        // Chunk [2, -1] is compressed with LZ4 in an external file.
+        region[994 * 4..995 * 4].copy_from_slice(&(3u32 << 8 | 1).to_be_bytes());
+        region.extend_from_slice(&1u32.to_be_bytes());
+        region.push(4 | 128);
+        region.resize(4 * 4096, 0);
+        std::fs::write(directory.join("r.0.-1.mca"), region).unwrap();
+        let nbt = chunk_nbt(2);
+        let compressed = lz4_flex::block::compress(&nbt);
+        let mut lz4 = b"LZ4Block".to_vec();
+        lz4.push(0x20);
+        lz4.extend_from_slice(&(compressed.len() as i32).to_le_bytes());
+        lz4.extend_from_slice(&(nbt.len() as i32).to_le_bytes());
+        lz4.extend_from_slice(&0i32.to_le_bytes());
+        lz4.extend_from_slice(&compressed);
+        lz4.extend_from_slice(b"LZ4Block");
+        lz4.push(0x10);
+        lz4.extend_from_slice(&[0; 12]);
+        std::fs::write(directory.join("c.2.-1.mcc"), lz4).unwrap();
+
+        let dimension = Dimension::new(
+            crate::registry::dimension_type::DIMENSION_TYPES
+                .get("minecraft:overworld")
+                .unwrap()
+                .clone(),
+            "overworld".to_string(),
+            0,
+        );
+        let storage = RegionStorage::new(directory.clone(), -4);
+        let desert = BIOMES_INDEX
+            .iter()
+            .position(|biome| biome == "minecraft:desert")
+            .unwrap() as u16;
+        for x in [1, 2] {
+            let chunk = storage.load_chunk(&dimension, x, -1).unwrap().unwrap();
+            assert_eq!(chunk.get_position(), (x, -1));
+            assert_eq!(chunk.get_block(0, 0, 0), Some(1));
+            assert_eq!(chunk.get_block(1, 0, 0), Some(oak_log));
+            assert_eq!(chunk.get_block(2, 0, 0), Some(0));
+            assert_eq!(chunk.get_block_light(0, 0, 0), Some(15));
+            assert_eq!(chunk.get_block_light(1, 0, 0), Some(0));
+            assert_eq!(chunk.get_sections()[0].get_biome(0, 0, 0), desert);
+        }
+        assert!(storage.load_chunk(&dimension, 3, -1).unwrap().is_none());
+        assert!(storage.load_chunk(&dimension, 0, 0).unwrap().is_none());
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn save_region() {
+        use crate::util::to_dim_xz;
+        use crate::world::anvil::RegionStorage;
+        use crate::world::chunk::Chunk;
+        use crate::world::dimension::Dimension;
+
+        let dimension = Dimension::new(
+            crate::registry::dimension_type::DIMENSION_TYPES
+                .get("minecraft:overworld")
+                .unwrap()
+                .clone(),
+            "overworld".to_string(),
+            0,
+        );
+        let directory =
+            std::env::temp_dir().join(format!("spot-anvil-save-{}", std::process::id()));
+        let storage = RegionStorage::new(directory.clone(), -4);
+        let chunk = Chunk::new(&dimension, to_dim_xz(-33, 5));
+        assert!(!chunk.is_dirty());
+        for x in 0..16 {
+            chunk.set_block(x, 0, x, x as u32 + 1).unwrap();
+        }
+        chunk.set_block(3, 383, 4, 9).unwrap();
+        chunk.set_sky_light(1, 1, 1, 7).unwrap();
+        chunk.get_sections()[2].set_biome(1, 2, 3, 4);
+        assert!(chunk.is_dirty());
+        storage.save_chunk(&chunk).unwrap();
+        let path = directory.join("r.-2.0.mca");
+        let length = std::fs::metadata(&path).unwrap().len();
+        // Saving again reuses the sectors freed by the previous data.
+        storage.save_chunk(&chunk).unwrap();
+        storage.save_chunk(&chunk).unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), length + 4096);
+
+        let loaded = RegionStorage::new(directory.clone(), -4)
+            .load_chunk(&dimension, -33, 5)
+            .unwrap()
+            .unwrap();
+        assert!(!loaded.is_dirty());
+        for x in 0..16 {
+            assert_eq!(loaded.get_block(x, 0, x), Some(x as u32 + 1));
+        }
+        assert_eq!(loaded.get_block(3, 383, 4), Some(9));
+        assert_eq!(loaded.get_block(3, 382, 4), Some(0));
+        assert_eq!(loaded.get_sky_light(1, 1, 1), Some(7));
+        assert_eq!(loaded.get_sections()[2].get_biome(1, 2, 3), 4);
+        assert_eq!(
+            loaded.get_sections()[2].get_biome(0, 0, 0),
+            chunk.get_sections()[0].get_biome(0, 0, 0)
+        );
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn keep_unreadable_chunk() {
+        use crate::util::to_dim_xz;
+        use crate::world::anvil::RegionStorage;
+        use crate::world::chunk::Chunk;
+        use crate::world::dimension::Dimension;
+
+        let dimension = Dimension::new(
+            crate::registry::dimension_type::DIMENSION_TYPES
+                .get("minecraft:overworld")
+                .unwrap()
+                .clone(),
+            "overworld".to_string(),
+            0,
+        );
+        let directory =
+            std::env::temp_dir().join(format!("spot-anvil-unreadable-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        // Chunk [0, 0] has an unknown compression type.
+        let mut region = vec![0u8; 8192];
+        region[..4].copy_from_slice(&(2u32 << 8 | 1).to_be_bytes());
+        region.extend_from_slice(&2u32.to_be_bytes());
+        region.extend_from_slice(&[99, 0]);
+        region.resize(3 * 4096, 0);
+        let path = directory.join("r.0.0.mca");
+        std::fs::write(&path, &region).unwrap();
+
+        let storage = RegionStorage::new(directory.clone(), -4);
+        assert!(storage.load_chunk(&dimension, 0, 0).is_err());
+        let chunk = Chunk::new(&dimension, to_dim_xz(0, 0));
+        chunk.set_block(0, 0, 0, 1).unwrap();
+        storage.save_chunk(&chunk).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), region);
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn reject_invalid_lz4() {
+        use crate::world::anvil::region::decompress;
+
+        let block = |token: u8, compressed: i32, original: i32| {
+            let mut lz4 = b"LZ4Block".to_vec();
+            lz4.push(token);
+            lz4.extend_from_slice(&compressed.to_le_bytes());
+            lz4.extend_from_slice(&original.to_le_bytes());
+            lz4.extend_from_slice(&0i32.to_le_bytes());
+            lz4
+        };
+        assert!(decompress(4, &block(0x20, -1, 16)).is_err());
+        assert!(decompress(4, &block(0x20, 0, -1)).is_err());
+        assert!(decompress(4, &block(0x20, 0, i32::MAX)).is_err());
+        let mut raw = block(0x10, 4, 8);
+        raw.extend_from_slice(&[0; 4]);
+        assert!(decompress(4, &raw).is_err());
+        let mut raw = block(0x10, 4, 4);
+        raw.extend_from_slice(&[1, 2, 3, 4]);
+        assert_eq!(decompress(4, &raw).unwrap(), [1, 2, 3, 4]);
+    }
 }
@@ -1,6 +1,7 @@
 pub mod arc_channel;
 pub mod io;
 pub mod raw;
+pub mod text;
 
 use std::io::{Error, ErrorKind};
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
@@ -12,12 +13,17 @@ pub fn to_chunk_yzx(x: i32, y: i32, z: i32) -> usize {
 
 #[inline]
 pub fn to_dim_xz(x: i32, z: i32) -> u64 {
-    (x as u64) << 32 | (z as u64)
+    (x as u32 as u64) << 32 | (z as u32 as u64)
 }
 pub fn encode_position(x: i32, y: i32, z: i32) -> u64 {
     ((x as u64 & 0x3FFFFFF) << 38) | ((z as u64 & 0x3FFFFFF) << 12) | (y as u64 & 0xFFF)
 }
 
+/// Converts an angle in degrees to the protocol's angle, a step of 1/256 of a full turn.
+pub fn to_angle(degrees: f32) -> u8 {
+    (degrees * 256.0 / 360.0).floor() as i32 as u8
+}
+
 pub async fn read_var_int<R: AsyncRead + Unpin>(reader: &mut R) -> Result<i32, Error> {
     let mut value = 0;
     for i in 0..5 {
@@ -27,7 +33,10 @@ pub async fn read_var_int<R: AsyncRead + Unpin>(reader: &mut R) -> Result<i32, E
             return Ok(value);
         }
     }
-    Err(Error::new(ErrorKind::InvalidData, "VarInt too big"))
+    Err(Error::new(
+        ErrorKind::InvalidData,
+        "VarInt is longer than 5 bytes",
+    ))
 }
 pub async fn write_var_int<W: AsyncWrite + Unpin>(writer: &mut W, value: i32) -> Result<(), Error> {
     let mut value = value as u32;
@@ -42,14 +51,54 @@ pub async fn write_var_int<W: AsyncWrite + Unpin>(writer: &mut W, value: i32) ->
     Ok(())
 }
 
+pub async fn write_var_long<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    value: i64,
+) -> Result<(), Error> {
+    let mut value = value as u64;
+    loop {
+        if (value & !0x7F) == 0 {
+            writer.write_u8(value as u8).await?;
+            break;
+        }
+        writer.write_u8(((value & 0x7F) | 0x80) as u8).await?;
+        value >>= 7;
+    }
+    Ok(())
+}
+
+/// The maximum length of a string in UTF-16 code units, like the vanilla default.
+pub const MAX_STRING_LENGTH: usize = 32767;
+
 pub async fn read_str<R: AsyncRead + Unpin>(reader: &mut R) -> Result<String, Error> {
     let len = read_var_int(reader).await?;
-    let mut buf = Vec::with_capacity(len as usize);
-    reader.read_buf(&mut buf).await?;
-    match String::from_utf8(buf) {
-        Ok(value) => Ok(value),
-        Err(e) => Err(Error::new(ErrorKind::InvalidInput, e)),
+    if len < 0 {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("Invalid string length {}", len),
+        ));
+    }
+    // A UTF-16 code unit takes at most 3 bytes in UTF-8.
+    if len as usize > MAX_STRING_LENGTH * 3 {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "String of {} bytes exceeds the maximum of {} bytes",
+                len,
+                MAX_STRING_LENGTH * 3
+            ),
+        ));
+    }
+    let mut buf = vec![0; len as usize];
+    reader.read_exact(&mut buf).await?;
+    let value = String::from_utf8(buf).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    if value.encode_utf16().count() > MAX_STRING_LENGTH {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("String exceeds the maximum length of {}", MAX_STRING_LENGTH),
+        ));
     }
+    Ok(value)
 }
 
 pub async fn write_str<W: AsyncWrite + Unpin>(writer: &mut W, value: &str) -> Result<(), Error> {
@@ -130,6 +130,9 @@ impl<T> MultipleReceiver<T> {
     pub fn remove(&mut self, index: u64) -> Option<Receiver<T>> {
         self.receivers.remove(&index)
     }
+    pub fn get(&self, index: u64) -> Option<&Receiver<T>> {
+        self.receivers.get(&index)
+    }
     pub fn receive(&self) -> Arc<T> {
         loop {
             for receiver in self.receivers.iter() {
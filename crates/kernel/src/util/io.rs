@@ -1,6 +1,6 @@
-use crate::util::{read_str, read_var_int, write_str, write_var_int};
+use crate::util::{read_str, read_var_int, write_str, write_var_int, write_var_long};
 use bit_set::BitSet;
-use std::io::Error;
+use std::io::{Error, ErrorKind};
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 
 #[allow(async_fn_in_trait)]
@@ -8,6 +8,8 @@ pub trait ReadExt: AsyncRead + Unpin {
     async fn read_var_int(&mut self) -> Result<i32, Error>;
     async fn read_str(&mut self) -> Result<String, Error>;
     async fn read_bool(&mut self) -> Result<bool, Error>;
+    /// Reads a byte array prefixed by its length, which must not exceed `max_len`.
+    async fn read_byte_array(&mut self, max_len: usize) -> Result<Vec<u8>, Error>;
 }
 
 impl<T: AsyncRead + Unpin> ReadExt for T {
@@ -24,19 +26,42 @@ impl<T: AsyncRead + Unpin> ReadExt for T {
             Ok(true)
         }
     }
+    async fn read_byte_array(&mut self, max_len: usize) -> Result<Vec<u8>, Error> {
+        let len = read_var_int(self).await?;
+        if len < 0 {
+            return Err(Error::new(ErrorKind::InvalidData, "Negative array length"));
+        }
+        if len as usize > max_len {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Array of {} bytes exceeds the maximum of {} bytes",
+                    len, max_len
+                ),
+            ));
+        }
+        let mut buf = vec![0; len as usize];
+        self.read_exact(&mut buf).await?;
+        Ok(buf)
+    }
 }
 #[allow(async_fn_in_trait)]
 pub trait WriteExt: AsyncWrite + Unpin {
     async fn write_var_int(&mut self, value: i32) -> Result<(), Error>;
+    async fn write_var_long(&mut self, value: i64) -> Result<(), Error>;
     async fn write_str(&mut self, value: &str) -> Result<(), Error>;
     async fn write_bool(&mut self, value: bool) -> Result<(), Error>;
     async fn write_bitset(&mut self, value: &BitSet) -> Result<(), Error>;
+    async fn write_byte_array(&mut self, value: &[u8]) -> Result<(), Error>;
 }
 
 impl<T: AsyncWrite + Unpin> WriteExt for T {
     async fn write_var_int(&mut self, value: i32) -> Result<(), Error> {
         write_var_int(self, value).await
     }
+    async fn write_var_long(&mut self, value: i64) -> Result<(), Error> {
+        write_var_long(self, value).await
+    }
     async fn write_str(&mut self, value: &str) -> Result<(), Error> {
         write_str(self, value).await
     }
@@ -50,16 +75,15 @@ impl<T: AsyncWrite + Unpin> WriteExt for T {
     }
 
     async fn write_bitset(&mut self, value: &BitSet) -> Result<(), Error> {
-        let array = value.get_ref().storage();
-        let mut vec = Vec::with_capacity(array.len() / 2);
-        for i in (0..array.len()).step_by(2) {
-            let mut entry: u64 = array[i] as u64;
-            if i + 1 < array.len() {
-                entry |= (array[i + 1] as u64) << 32;
-            } else {
-                break;
-            }
-            vec.push(entry);
+        let mut vec: Vec<u64> = value
+            .get_ref()
+            .storage()
+            .chunks(2)
+            .map(|words| words[0] as u64 | (*words.get(1).unwrap_or(&0) as u64) << 32)
+            .collect();
+        // Trailing empty longs are omitted, like Java's `BitSet.toLongArray`.
+        while vec.last() == Some(&0) {
+            vec.pop();
         }
         write_var_int(self, vec.len() as i32).await?;
         for entry in vec {
@@ -67,4 +91,10 @@ impl<T: AsyncWrite + Unpin> WriteExt for T {
         }
         Ok(())
     }
+
+    async fn write_byte_array(&mut self, value: &[u8]) -> Result<(), Error> {
+        write_var_int(self, value.len() as i32).await?;
+        self.write_all(value).await?;
+        Ok(())
+    }
 }
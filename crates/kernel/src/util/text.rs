@@ -0,0 +1,76 @@
+use crate::registry::NbtSerializable;
+use dashmap::DashMap;
+use serde_derive::{Deserialize, Serialize};
+use spotlight::nbt::{serde_nbt, NbtByte, NbtCompound, NbtList, NbtString, NbtTag};
+use spotlight::{nbt_byte, nbt_str};
+
+/// A text component, the formatted text shown to players.
+///
+/// It is sent as NBT in the configuration and play states, and as JSON in the login state.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextComponent {
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bold: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub italic: Option<bool>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra: Vec<TextComponent>,
+}
+
+impl TextComponent {
+    pub fn text(text: impl Into<String>) -> TextComponent {
+        TextComponent {
+            text: text.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Gets the text of the component and its children, without formatting.
+    pub fn to_plain_text(&self) -> String {
+        let mut text = self.text.clone();
+        for extra in self.extra.iter() {
+            text.push_str(&extra.to_plain_text());
+        }
+        text
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Encodes the component as network NBT, a compound without a root name.
+    pub fn to_network_nbt(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.text.len() + 16);
+        serde_nbt(self.to_nbt(), &mut buf);
+        buf
+    }
+}
+
+impl NbtSerializable for TextComponent {
+    fn to_nbt(&self) -> NbtCompound {
+        let data: DashMap<String, Box<dyn NbtTag>> = DashMap::with_capacity(2);
+        data.insert("text".to_string(), nbt_str!(self.text.clone()));
+        if let Some(color) = &self.color {
+            data.insert("color".to_string(), nbt_str!(color.clone()));
+        }
+        if let Some(bold) = self.bold {
+            data.insert("bold".to_string(), nbt_byte!(bold as i8));
+        }
+        if let Some(italic) = self.italic {
+            data.insert("italic".to_string(), nbt_byte!(italic as i8));
+        }
+        if !self.extra.is_empty() {
+            data.insert(
+                "extra".to_string(),
+                Box::new(NbtList {
+                    data: self.extra.iter().map(|extra| extra.to_nbt()).collect(),
+                    tag_type: 10,
+                }),
+            );
+        }
+        NbtCompound { data }
+    }
+}
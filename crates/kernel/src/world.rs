@@ -1,4 +1,5 @@
 use crate::block::BLOCKS_BY_ID;
+use crate::config::{LEVEL_NAME, SEED};
 use crate::entity::entity_manager::EntityManager;
 use crate::registry::dimension_type::DIMENSION_TYPES;
 use crate::registry::DIMENSION_TYPES_INDEX;
@@ -6,25 +7,46 @@ use crate::util::raw::Raw;
 use crate::world::block_update::BlockUpdateType::{NeighbourChange, PostPlacement};
 use crate::world::block_update::{BlockUpdate, BlockUpdateType};
 use crate::world::dimension::Dimension;
+use crate::world::level::LevelData;
+use crate::world::ticket::TicketType;
 use dashmap::DashSet;
 use parking_lot::Mutex;
 use rayon::prelude::*;
+use simdnbt::owned::NbtTag;
 use spotlight::event::{ActionResult, EventCallback};
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use tracing::{error, info};
 
+pub mod anvil;
 pub mod block_update;
 pub mod chunk;
 pub mod dimension;
+pub mod game_rules;
 pub mod gen;
 mod height_map;
+pub mod level;
+pub mod player_data;
+pub mod ticket;
 
 static WORLD_TICK_CALLBACK: EventCallback<Raw<World>> = EventCallback::new();
 
 pub struct World {
+    /// The directory the world is stored in.
+    pub directory: PathBuf,
     default_dimension: usize,
     pub dimensions: Vec<Arc<Dimension>>,
     pub entities: EntityManager,
+    /// The time, the weather and the spawn point.
+    pub level_data: Mutex<LevelData>,
+    /// How strong the rain and the thunder are, fading in and out from 0 to 1.
+    pub weather_levels: Mutex<(f32, f32)>,
+    /// Whether the time or the spawn point were changed and must be sent to the players.
+    pub(crate) level_changed: AtomicBool,
+    /// The chunk and the radius of the spawn ticket, following the spawn point
+    /// and the `spawnChunkRadius` game rule.
+    spawn_ticket: Mutex<Option<((i32, i32), u8)>>,
     block_update_queue_0: Mutex<Vec<BlockUpdate>>,
     block_update_queue_1: Mutex<Vec<BlockUpdate>>,
     use_2: AtomicBool,
@@ -42,17 +64,62 @@ impl World {
             )));
             idx += 1;
         }
-        World {
-            default_dimension: dimensions
-                .iter()
-                .position(|it| it.dimension_name == "minecraft:overworld")
-                .unwrap(),
+        let default_dimension = dimensions
+            .iter()
+            .position(|it| it.dimension_name == "minecraft:overworld")
+            .unwrap();
+        let directory = PathBuf::from(*LEVEL_NAME);
+        let mut level_data = match LevelData::load(&directory) {
+            Ok(Some(level_data)) => {
+                info!("Loaded the level data of {}.", level_data.level_name);
+                level_data
+            }
+            Ok(None) => {
+                let mut level_data = LevelData::new(*LEVEL_NAME, *SEED);
+                let dimension = &dimensions[default_dimension];
+                let chunk = dimension.get_chunk(0, 0);
+                // The height of the topmost block above the origin, from the bottom of the dimension.
+                let top = (0..dimension.dimension_type.height)
+                    .rev()
+                    .find(|y| chunk.get_block(0, *y, 0).is_some_and(|block| block != 0))
+                    .unwrap_or(-1);
+                level_data.spawn = (0, dimension.dimension_type.min_y + top + 1, 0);
+                level_data
+            }
+            Err(err) => {
+                error!(
+                    "Failed to load the level data of {:?} from level.dat or level.dat_old: {:?}",
+                    directory, err
+                );
+                std::process::exit(1);
+            }
+        };
+        let settings = &mut level_data.world_gen_settings;
+        if settings.dimensions.is_empty() {
+            for dimension in dimensions.iter() {
+                if let Some(generator) = dimension.get_generator_settings() {
+                    settings.dimensions.insert(
+                        dimension.dimension_name.as_str(),
+                        NbtTag::Compound(generator),
+                    );
+                }
+            }
+        }
+        let world = World {
+            directory,
+            default_dimension,
             dimensions,
             block_update_queue_0: Mutex::new(Vec::new()),
             block_update_queue_1: Mutex::new(Vec::new()),
             entities: EntityManager::default(),
+            level_data: Mutex::new(level_data),
+            weather_levels: Mutex::new((0.0, 0.0)),
+            level_changed: AtomicBool::new(false),
+            spawn_ticket: Mutex::new(None),
             use_2: AtomicBool::new(false),
-        }
+        };
+        world.update_spawn_ticket();
+        world
     }
     #[inline]
     fn get_queue(&self) -> &Mutex<Vec<BlockUpdate>> {
@@ -87,6 +154,10 @@ impl World {
         if let ActionResult::Fail = WORLD_TICK_CALLBACK.interact(Raw::from(self)) {
             return;
         }
+        self.update_spawn_ticket();
+        for dimension in self.dimensions.iter() {
+            dimension.tick_chunks();
+        }
         self.swap_queues();
         let mut queue = self.get_internal_queue().lock();
         let new: Mutex<Vec<BlockUpdate>> = Mutex::new(Vec::new());
@@ -221,9 +292,88 @@ impl World {
         )
     }
 
+    /// Counts the players currently spawned in the world.
+    pub fn online_players(&self) -> usize {
+        self.entities
+            .lookup()
+            .entity_type("minecraft:player")
+            .map_or(0, |players| players.get_all().len())
+    }
+
     pub fn get_world_spawn_point(&self) -> (usize, i32, i32, i32) {
-        // TODO
-        (self.default_dimension, 0, 0, 0)
+        let (x, y, z) = self.level_data.lock().spawn;
+        (self.default_dimension, x, y, z)
+    }
+
+    /// Saves the world to the disk.
+    ///
+    /// # Returns
+    /// The last error, once every dimension was tried.
+    pub fn save(&self) -> anyhow::Result<()> {
+        let mut result = Ok(());
+        for dimension in self.dimensions.iter() {
+            if let Err(err) = dimension.save() {
+                result = Err(err);
+            }
+        }
+        let level_data = self.level_data.lock().serialize()?;
+        LevelData::save(&self.directory, &level_data)?;
+        result
+    }
+
+    /// Moves the spawn point of the world, in the default dimension,
+    /// along with the chunks kept loaded around it.
+    pub fn set_world_spawn_point(&self, pos: (i32, i32, i32), angle: f32) {
+        {
+            let mut level_data = self.level_data.lock();
+            level_data.spawn = pos;
+            level_data.spawn_angle = angle;
+        }
+        self.update_spawn_ticket();
+        self.level_changed.store(true, Ordering::Release);
+    }
+
+    /// Moves the spawn ticket to the spawn point with the radius of the `spawnChunkRadius` game rule,
+    /// if either changed since it was added.
+    fn update_spawn_ticket(&self) {
+        let ticket = {
+            let level_data = self.level_data.lock();
+            let (x, _, z) = level_data.spawn;
+            get_spawn_chunk_radius(&level_data).map(|radius| ((x >> 4, z >> 4), radius))
+        };
+        let mut current = self.spawn_ticket.lock();
+        if *current == ticket {
+            return;
+        }
+        let dimension = &self.dimensions[self.default_dimension];
+        if let Some(((x, z), _)) = *current {
+            dimension.remove_ticket(x, z, &TicketType::Spawn);
+        }
+        if let Some(((x, z), radius)) = ticket {
+            dimension.add_ticket(x, z, TicketType::Spawn, radius);
+        }
+        *current = ticket;
+    }
+
+    /// Sets the time of day.
+    pub fn set_day_time(&self, day_time: i64) {
+        self.level_data.lock().day_time = day_time;
+        self.level_changed.store(true, Ordering::Release);
+    }
+
+    /// Advances the time and the weather by a tick.
+    ///
+    /// # Returns
+    /// The rain and thunder levels before and after the tick.
+    pub(crate) fn tick_level(&self) -> ((f32, f32), (f32, f32)) {
+        let mut level_data = self.level_data.lock();
+        level_data.tick();
+        let mut levels = self.weather_levels.lock();
+        let previous = *levels;
+        let step = |level: f32, on: bool| (level + if on { 0.01 } else { -0.01 }).clamp(0.0, 1.0);
+        levels.0 = step(levels.0, level_data.raining);
+        levels.1 = step(levels.1, level_data.thundering);
+        (previous, *levels)
     }
 }
 
@@ -232,3 +382,15 @@ impl Default for World {
         World::new()
     }
 }
+
+/// Gets the radius of the chunks kept loaded around the spawn point,
+/// from the `spawnChunkRadius` game rule.
+///
+/// # Returns
+/// `None` if no chunks are kept loaded.
+fn get_spawn_chunk_radius(level_data: &LevelData) -> Option<u8> {
+    match level_data.game_rules.get_int("spawnChunkRadius") {
+        radius @ 1.. => Some(radius.min(32) as u8),
+        _ => None,
+    }
+}
@@ -0,0 +1,168 @@
+use crate::world::anvil::region::RegionFile;
+use crate::world::chunk::Chunk;
+use crate::world::dimension::Dimension;
+use hashbrown::{HashMap, HashSet};
+use parking_lot::Mutex;
+use simdnbt::owned::{BaseNbt, Nbt};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::error;
+
+pub mod chunk_nbt;
+pub mod region;
+
+type Region = Arc<Mutex<RegionFile>>;
+
+/// The region files of a dimension, opened lazily and kept open once used.
+pub struct RegionStorage {
+    directory: PathBuf,
+    /// The y coordinate of the lowest section of the dimension, in sections.
+    min_section: i32,
+    regions: Mutex<HashMap<(i32, i32), Option<Region>>>,
+    /// The chunks unloaded with unsaved changes, waiting to be saved.
+    unloaded: Mutex<Vec<Arc<Chunk>>>,
+    /// The chunks which failed to load, never overwritten so their data can be recovered.
+    unreadable: Mutex<HashSet<(i32, i32)>>,
+}
+
+impl RegionStorage {
+    pub fn new(directory: PathBuf, min_section: i32) -> RegionStorage {
+        RegionStorage {
+            directory,
+            min_section,
+            regions: Mutex::new(HashMap::new()),
+            unloaded: Mutex::new(Vec::new()),
+            unreadable: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Loads a chunk from the region files.
+    ///
+    /// # Returns
+    /// - `Ok(Some(Chunk))`: The loaded chunk.
+    /// - `Ok(None)`: If the chunk is not stored, or not fully generated.
+    /// - `Err(anyhow::Error)`: If the chunk cannot be read,
+    ///   in which case [`RegionStorage::save_chunk`] no longer writes it.
+    pub fn load_chunk(
+        &self,
+        dimension: &Dimension,
+        x: i32,
+        z: i32,
+    ) -> anyhow::Result<Option<Chunk>> {
+        let result = self.read_chunk(dimension, x, z);
+        if result.is_err() {
+            self.unreadable.lock().insert((x, z));
+        }
+        result
+    }
+
+    fn read_chunk(&self, dimension: &Dimension, x: i32, z: i32) -> anyhow::Result<Option<Chunk>> {
+        let Some(region) = self.get_region(x >> 5, z >> 5, false)? else {
+            return Ok(None);
+        };
+        let Some(data) = region.lock().read_chunk(x, z)? else {
+            return Ok(None);
+        };
+        match simdnbt::owned::read(&mut Cursor::new(&data))? {
+            Nbt::Some(nbt) => chunk_nbt::read_chunk(dimension, x, z, &nbt),
+            Nbt::None => Ok(None),
+        }
+    }
+
+    /// Writes a chunk to its region file, creating the file if needed.
+    ///
+    /// A chunk which failed to load is skipped, keeping the stored data instead of the one generated again.
+    pub fn save_chunk(&self, chunk: &Chunk) -> anyhow::Result<()> {
+        let (x, z) = chunk.get_position();
+        if self.unreadable.lock().contains(&(x, z)) {
+            return Ok(());
+        }
+        let nbt = chunk_nbt::write_chunk(chunk, self.min_section)?;
+        let mut data = Vec::with_capacity(16384);
+        BaseNbt::new("", nbt).write(&mut data);
+        let region = self
+            .get_region(x >> 5, z >> 5, true)?
+            .expect("The region file is created when missing");
+        let mut region = region.lock();
+        region.write_chunk(x, z, &data)
+    }
+
+    /// Queues a chunk unloaded with unsaved changes, to be saved by [`RegionStorage::save_unloaded`].
+    pub(crate) fn queue_unloaded(&self, chunk: Arc<Chunk>) {
+        self.unloaded.lock().push(chunk);
+    }
+
+    /// Whether chunks are waiting to be saved after they were unloaded.
+    pub(crate) fn has_unloaded(&self) -> bool {
+        !self.unloaded.lock().is_empty()
+    }
+
+    /// Saves the chunks unloaded since the last call.
+    ///
+    /// The chunks are then released, and leave the dimension unless they were loaded again meanwhile.
+    /// A chunk failing to save is queued again when released.
+    ///
+    /// # Returns
+    /// The last error, once every chunk was tried.
+    pub(crate) fn save_unloaded(&self) -> anyhow::Result<()> {
+        let chunks = std::mem::take(&mut *self.unloaded.lock());
+        let mut result = Ok(());
+        for chunk in chunks.iter() {
+            if !chunk.take_dirty() {
+                continue;
+            }
+            if let Err(err) = self.save_chunk(chunk) {
+                chunk.mark_dirty();
+                let (x, z) = chunk.get_position();
+                error!("Failed to save chunk [{}, {}]: {:?}", x, z, err);
+                result = Err(err);
+            }
+        }
+        result
+    }
+
+    /// Gets an opened region file.
+    ///
+    /// # Parameters
+    /// - `create`: Whether a missing region file is created, instead of returning `None`.
+    fn get_region(
+        &self,
+        region_x: i32,
+        region_z: i32,
+        create: bool,
+    ) -> anyhow::Result<Option<Region>> {
+        let mut regions = self.regions.lock();
+        match regions.get(&(region_x, region_z)) {
+            Some(Some(region)) => return Ok(Some(region.clone())),
+            Some(None) if !create => return Ok(None),
+            _ => {}
+        }
+        let path = self
+            .directory
+            .join(format!("r.{}.{}.mca", region_x, region_z));
+        if create {
+            std::fs::create_dir_all(&self.directory)?;
+        }
+        let region = if create || path.is_file() {
+            Some(Arc::new(Mutex::new(RegionFile::open(path)?)))
+        } else {
+            None
+        };
+        regions.insert((region_x, region_z), region.clone());
+        Ok(region)
+    }
+}
+
+/// Gets the directory of a dimension in the world, laid out like vanilla does.
+pub fn get_dimension_directory(level: &Path, dimension_name: &str) -> PathBuf {
+    match dimension_name {
+        "minecraft:overworld" => level.to_path_buf(),
+        "minecraft:the_nether" => level.join("DIM-1"),
+        "minecraft:the_end" => level.join("DIM1"),
+        name => {
+            let (namespace, path) = name.split_once(':').unwrap_or(("minecraft", name));
+            level.join("dimensions").join(namespace).join(path)
+        }
+    }
+}
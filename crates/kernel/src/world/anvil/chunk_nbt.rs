@@ -0,0 +1,348 @@
+use crate::registry::protocol_id::{get_block_state_id, get_block_state_properties};
+use crate::registry::BIOMES_INDEX;
+use crate::util::to_dim_xz;
+use crate::world::chunk::{Chunk, Section, DEFAULT_BIOME};
+use crate::world::dimension::Dimension;
+use crate::DATA_VERSION;
+use anyhow::anyhow;
+use hashbrown::HashMap;
+use simdnbt::owned::{NbtCompound, NbtList, NbtTag};
+use tracing::debug;
+
+/// The first data version storing sections with paletted block states and biomes (1.18).
+const MIN_DATA_VERSION: i32 = 2844;
+
+/// Builds a chunk from its NBT data, as stored in region files.
+///
+/// # Returns
+/// - `Ok(Some(Chunk))`: The loaded chunk.
+/// - `Ok(None)`: If the chunk is not fully generated, so it must be generated again.
+/// - `Err(anyhow::Error)`: If the data is invalid or written by an unsupported version.
+pub fn read_chunk(
+    dimension: &Dimension,
+    x: i32,
+    z: i32,
+    nbt: &NbtCompound,
+) -> anyhow::Result<Option<Chunk>> {
+    let data_version = nbt.int("DataVersion").unwrap_or(0);
+    if data_version < MIN_DATA_VERSION {
+        return Err(anyhow!("Unsupported data version: {}", data_version));
+    }
+    match nbt.string("Status").map(|status| status.to_str()) {
+        Some(status) if status == "minecraft:full" || status == "full" => {}
+        _ => return Ok(None),
+    }
+    let (pos_x, pos_z) = (nbt.int("xPos"), nbt.int("zPos"));
+    if pos_x != Some(x) || pos_z != Some(z) {
+        return Err(anyhow!(
+            "Chunk [{}, {}] is stored at [{:?}, {:?}]",
+            x,
+            z,
+            pos_x,
+            pos_z
+        ));
+    }
+    let chunk = Chunk::new(dimension, to_dim_xz(x, z));
+    let min_section = dimension.dimension_type.min_y >> 4;
+    let sections = chunk.get_sections();
+    for section_nbt in nbt
+        .list("sections")
+        .and_then(|sections| sections.compounds())
+        .unwrap_or_default()
+    {
+        let y = section_nbt
+            .byte("Y")
+            .ok_or_else(|| anyhow!("Missing section Y"))? as i32;
+        // Vanilla also stores the light of the sections right below and above the world.
+        let Some(section) = usize::try_from(y - min_section)
+            .ok()
+            .and_then(|index| sections.get(index))
+        else {
+            continue;
+        };
+        let mut states = Box::new([0; 4096]);
+        if let Some(block_states) = section_nbt.compound("block_states") {
+            read_block_states(block_states, &mut states)?;
+        }
+        let mut biomes = [*DEFAULT_BIOME; 64];
+        if let Some(biomes_nbt) = section_nbt.compound("biomes") {
+            read_biomes(biomes_nbt, &mut biomes)?;
+        }
+        section.load(
+            &states,
+            &biomes,
+            read_light(section_nbt, "SkyLight")?,
+            read_light(section_nbt, "BlockLight")?,
+        );
+    }
+    if let Some(height_maps) = nbt.compound("Heightmaps") {
+        if let Some(data) = height_maps.long_array("WORLD_SURFACE") {
+            chunk.get_world_surface().deserialize(data)?;
+        }
+        if let Some(data) = height_maps.long_array("MOTION_BLOCKING") {
+            chunk.get_motion_blocking().deserialize(data)?;
+        }
+    }
+    Ok(Some(chunk))
+}
+
+/// Converts a chunk to its NBT data, as stored in region files.
+///
+/// The light is marked as not computed, so vanilla lights the chunk again when loading it.
+///
+/// # Parameters
+/// - `min_section`: The y coordinate of the lowest section of the dimension, in sections.
+pub fn write_chunk(chunk: &Chunk, min_section: i32) -> anyhow::Result<NbtCompound> {
+    let (x, z) = chunk.get_position();
+    let sections = chunk
+        .get_sections()
+        .iter()
+        .enumerate()
+        .map(|(index, section)| write_section(min_section + index as i32, section))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let height_maps = NbtCompound::from_values(vec![
+        (
+            "MOTION_BLOCKING".into(),
+            NbtTag::LongArray(chunk.get_motion_blocking().serialize()?),
+        ),
+        (
+            "WORLD_SURFACE".into(),
+            NbtTag::LongArray(chunk.get_world_surface().serialize()?),
+        ),
+    ]);
+    let structures = NbtCompound::from_values(vec![
+        ("References".into(), NbtTag::Compound(NbtCompound::new())),
+        ("starts".into(), NbtTag::Compound(NbtCompound::new())),
+    ]);
+    Ok(NbtCompound::from_values(vec![
+        ("DataVersion".into(), NbtTag::Int(DATA_VERSION)),
+        ("xPos".into(), NbtTag::Int(x)),
+        ("yPos".into(), NbtTag::Int(min_section)),
+        ("zPos".into(), NbtTag::Int(z)),
+        ("Status".into(), NbtTag::String("minecraft:full".into())),
+        ("LastUpdate".into(), NbtTag::Long(0)),
+        ("InhabitedTime".into(), NbtTag::Long(0)),
+        ("isLightOn".into(), NbtTag::Byte(0)),
+        ("sections".into(), NbtTag::List(NbtList::Compound(sections))),
+        ("Heightmaps".into(), NbtTag::Compound(height_maps)),
+        ("block_entities".into(), NbtTag::List(NbtList::Empty)),
+        ("block_ticks".into(), NbtTag::List(NbtList::Empty)),
+        ("fluid_ticks".into(), NbtTag::List(NbtList::Empty)),
+        ("PostProcessing".into(), NbtTag::List(NbtList::Empty)),
+        ("structures".into(), NbtTag::Compound(structures)),
+    ]))
+}
+
+fn write_section(y: i32, section: &Section) -> anyhow::Result<NbtCompound> {
+    let states = Box::new(*section.get_data_guard().data);
+    let (palette, data) = write_container(states.as_slice(), |len| match len {
+        1 => 0,
+        len => ceil_log2(len).max(4),
+    });
+    let palette = palette
+        .into_iter()
+        .map(|state| {
+            let (name, properties) = get_block_state_properties(state)
+                .ok_or_else(|| anyhow!("Unknown block state: {}", state))?;
+            let mut nbt =
+                NbtCompound::from_values(vec![("Name".into(), NbtTag::String(name.into()))]);
+            if !properties.is_empty() {
+                let properties = properties
+                    .iter()
+                    .map(|(key, value)| {
+                        (key.as_str().into(), NbtTag::String(value.as_str().into()))
+                    })
+                    .collect();
+                nbt.insert(
+                    "Properties",
+                    NbtTag::Compound(NbtCompound::from_values(properties)),
+                );
+            }
+            Ok(nbt)
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let mut block_states = NbtCompound::from_values(vec![(
+        "palette".into(),
+        NbtTag::List(NbtList::Compound(palette)),
+    )]);
+    if let Some(data) = data {
+        block_states.insert("data", NbtTag::LongArray(data));
+    }
+
+    let biomes = section.get_biomes();
+    let (palette, data) = write_container(&biomes, ceil_log2);
+    let palette = palette
+        .into_iter()
+        .map(|biome| {
+            BIOMES_INDEX
+                .get(biome as usize)
+                .map(|name| name.as_str().into())
+                .ok_or_else(|| anyhow!("Unknown biome: {}", biome))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let mut biomes = NbtCompound::from_values(vec![(
+        "palette".into(),
+        NbtTag::List(NbtList::String(palette)),
+    )]);
+    if let Some(data) = data {
+        biomes.insert("data", NbtTag::LongArray(data));
+    }
+
+    let mut nbt = NbtCompound::from_values(vec![
+        ("Y".into(), NbtTag::Byte(y as i8)),
+        ("block_states".into(), NbtTag::Compound(block_states)),
+        ("biomes".into(), NbtTag::Compound(biomes)),
+    ]);
+    let light = section.get_light_guard();
+    if light.sky_light.iter().any(|value| *value != 0) {
+        nbt.insert("SkyLight", NbtTag::ByteArray(light.sky_light.to_vec()));
+    }
+    if light.block_light.iter().any(|value| *value != 0) {
+        nbt.insert("BlockLight", NbtTag::ByteArray(light.block_light.to_vec()));
+    }
+    Ok(nbt)
+}
+
+/// Builds the palette of a container and packs the indices into longs.
+///
+/// # Parameters
+/// - `get_bits`: Gets the bits per entry from the length of the palette.
+///
+/// # Returns
+/// The palette, and the packed data unless the container is single valued.
+fn write_container<T: Copy + Eq + std::hash::Hash>(
+    entries: &[T],
+    get_bits: impl Fn(usize) -> usize,
+) -> (Vec<T>, Option<Vec<i64>>) {
+    let mut palette = Vec::with_capacity(16);
+    let mut indices: HashMap<T, u64> = HashMap::with_capacity(16);
+    for entry in entries {
+        indices.entry(*entry).or_insert_with(|| {
+            palette.push(*entry);
+            palette.len() as u64 - 1
+        });
+    }
+    let bits = get_bits(palette.len());
+    if bits == 0 {
+        return (palette, None);
+    }
+    let per_long = 64 / bits;
+    let data = entries
+        .chunks(per_long)
+        .map(|entries| {
+            let mut long = 0u64;
+            for (i, entry) in entries.iter().enumerate() {
+                long |= indices[entry] << (i * bits);
+            }
+            long as i64
+        })
+        .collect();
+    (palette, Some(data))
+}
+
+fn read_block_states(nbt: &NbtCompound, states: &mut [u32; 4096]) -> anyhow::Result<()> {
+    let palette = nbt
+        .list("palette")
+        .and_then(|palette| palette.compounds())
+        .ok_or_else(|| anyhow!("Missing block state palette"))?;
+    let palette: Vec<u32> = palette
+        .iter()
+        .map(|state| {
+            let name = state
+                .string("Name")
+                .map(|name| name.to_str())
+                .unwrap_or_default();
+            let properties: Vec<(String, String)> = state
+                .compound("Properties")
+                .map(|properties| {
+                    properties
+                        .iter()
+                        .filter_map(|(key, value)| {
+                            Some((key.to_string(), value.string()?.to_string()))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            let properties = properties
+                .iter()
+                .map(|(key, value)| (key.as_str(), value.as_str()));
+            get_block_state_id(&name, properties).unwrap_or_else(|| {
+                debug!("Unknown block {} replaced with air.", name);
+                0
+            })
+        })
+        .collect();
+    let bits = match palette.len() {
+        0 | 1 => 0,
+        len => ceil_log2(len).max(4),
+    };
+    read_container(&palette, nbt.long_array("data"), bits, states)
+}
+
+fn read_biomes(nbt: &NbtCompound, biomes: &mut [u16; 64]) -> anyhow::Result<()> {
+    let palette = nbt
+        .list("palette")
+        .and_then(|palette| palette.strings())
+        .ok_or_else(|| anyhow!("Missing biome palette"))?;
+    let palette: Vec<u16> = palette
+        .iter()
+        .map(|biome| {
+            let biome = biome.to_str();
+            BIOMES_INDEX
+                .iter()
+                .position(|name| *name == biome)
+                .map(|index| index as u16)
+                .unwrap_or(*DEFAULT_BIOME)
+        })
+        .collect();
+    let bits = ceil_log2(palette.len());
+    read_container(&palette, nbt.long_array("data"), bits, biomes)
+}
+
+/// Unpacks the indices of a paletted container into the values of its palette.
+///
+/// Like the network format, entries never span two longs.
+fn read_container<T: Copy>(
+    palette: &[T],
+    data: Option<&[i64]>,
+    bits: usize,
+    result: &mut [T],
+) -> anyhow::Result<()> {
+    let Some(first) = palette.first() else {
+        return Err(anyhow!("Empty palette"));
+    };
+    if bits == 0 {
+        result.fill(*first);
+        return Ok(());
+    }
+    let data = data.ok_or_else(|| anyhow!("Missing paletted container data"))?;
+    let per_long = 64 / bits;
+    if data.len() != result.len().div_ceil(per_long) {
+        return Err(anyhow!("Invalid paletted container length: {}", data.len()));
+    }
+    let mask = (1u64 << bits) - 1;
+    for (i, entry) in result.iter_mut().enumerate() {
+        let long = data[i / per_long] as u64;
+        let index = ((long >> ((i % per_long) * bits)) & mask) as usize;
+        *entry = *palette
+            .get(index)
+            .ok_or_else(|| anyhow!("Palette index out of bounds: {}", index))?;
+    }
+    Ok(())
+}
+
+fn read_light<'a>(nbt: &'a NbtCompound, name: &str) -> anyhow::Result<Option<&'a [u8; 2048]>> {
+    match nbt.byte_array(name) {
+        Some(light) => {
+            Ok(Some(light.try_into().map_err(|_| {
+                anyhow!("Invalid {} length: {}", name, light.len())
+            })?))
+        }
+        None => Ok(None),
+    }
+}
+
+#[inline]
+fn ceil_log2(value: usize) -> usize {
+    (usize::BITS - (value.max(1) - 1).leading_zeros()) as usize
+}
@@ -0,0 +1,267 @@
+use anyhow::anyhow;
+use bit_set::BitSet;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const SECTOR_SIZE: usize = 4096;
+/// The location table and the timestamp table, a sector each.
+const HEADER_SECTORS: usize = 2;
+/// The most sectors a chunk can use, larger chunks are stored in their own file.
+const MAX_SECTORS: usize = 255;
+/// Set on the compression type when the chunk is stored in its own `.mcc` file.
+const EXTERNAL_FLAG: u8 = 128;
+const COMPRESSION_GZIP: u8 = 1;
+const COMPRESSION_ZLIB: u8 = 2;
+const COMPRESSION_NONE: u8 = 3;
+const COMPRESSION_LZ4: u8 = 4;
+const LZ4_MAGIC: &[u8; 8] = b"LZ4Block";
+const LZ4_METHOD_RAW: u8 = 0x10;
+const LZ4_METHOD_LZ4: u8 = 0x20;
+/// The largest decompressed chunk, also the largest block written by LZ4Java.
+const MAX_CHUNK_SIZE: usize = 1 << 25;
+
+/// A region file, holding the chunks of a 32x32 chunk area.
+///
+/// The file starts with a table of the location of every chunk,
+/// followed by a table of their last modification times.
+pub struct RegionFile {
+    path: PathBuf,
+    file: File,
+    locations: [u32; 1024],
+    /// The sectors used by the header and the chunks.
+    used_sectors: BitSet,
+}
+
+impl RegionFile {
+    /// Opens a region file and reads its location table.
+    ///
+    /// The file is created if it does not exist.
+    pub fn open(path: PathBuf) -> anyhow::Result<RegionFile> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+        let header_length = (HEADER_SECTORS * SECTOR_SIZE) as u64;
+        if file.metadata()?.len() < header_length {
+            file.set_len(header_length)?;
+        }
+        let mut header = Vec::with_capacity(SECTOR_SIZE);
+        // A file shorter than the header has no chunks stored in the missing part.
+        (&mut file)
+            .take(SECTOR_SIZE as u64)
+            .read_to_end(&mut header)?;
+        header.resize(SECTOR_SIZE, 0);
+        let mut locations = [0; 1024];
+        for (location, bytes) in locations.iter_mut().zip(header.chunks_exact(4)) {
+            *location = u32::from_be_bytes(bytes.try_into()?);
+        }
+        let mut used_sectors = BitSet::with_capacity(HEADER_SECTORS + 1024);
+        for sector in 0..HEADER_SECTORS {
+            used_sectors.insert(sector);
+        }
+        for location in locations.iter().filter(|location| **location != 0) {
+            let offset = (location >> 8) as usize;
+            for sector in offset..offset + (location & 0xFF) as usize {
+                used_sectors.insert(sector);
+            }
+        }
+        Ok(RegionFile {
+            path,
+            file,
+            locations,
+            used_sectors,
+        })
+    }
+
+    /// Reads the uncompressed NBT data of a chunk.
+    ///
+    /// # Parameters
+    /// - `x`, `z`: The coordinates of the chunk in the world.
+    ///
+    /// # Returns
+    /// - `Ok(Some(Vec<u8>))`: The NBT data of the chunk.
+    /// - `Ok(None)`: If the chunk is not stored in this region.
+    /// - `Err(anyhow::Error)`: If the data is corrupted or cannot be read.
+    pub fn read_chunk(&mut self, x: i32, z: i32) -> anyhow::Result<Option<Vec<u8>>> {
+        let location = self.locations[get_index(x, z)];
+        if location == 0 {
+            return Ok(None);
+        }
+        let offset = (location >> 8) as u64;
+        let sectors = (location & 0xFF) as u64;
+        if offset < 2 {
+            return Err(anyhow!("Invalid sector offset: {}", offset));
+        }
+        let mut data = Vec::with_capacity(sectors as usize * SECTOR_SIZE);
+        self.file
+            .seek(SeekFrom::Start(offset * SECTOR_SIZE as u64))?;
+        (&mut self.file)
+            .take(sectors * SECTOR_SIZE as u64)
+            .read_to_end(&mut data)?;
+        if data.len() < 5 {
+            return Err(anyhow!("Truncated chunk header"));
+        }
+        let length = u32::from_be_bytes(data[0..4].try_into()?) as usize;
+        if length == 0 {
+            return Ok(None);
+        }
+        let compression = data[4];
+        if compression & EXTERNAL_FLAG != 0 {
+            let external = std::fs::read(self.get_external_path(x, z))?;
+            return Ok(Some(decompress(compression & !EXTERNAL_FLAG, &external)?));
+        }
+        let payload = data
+            .get(5..4 + length)
+            .ok_or_else(|| anyhow!("Chunk length {} exceeds its sectors", length))?;
+        Ok(Some(decompress(compression, payload)?))
+    }
+
+    /// Compresses the NBT data of a chunk with zlib and writes it to the region.
+    ///
+    /// The data is written to free sectors before the header points to it,
+    /// so the previous data stays intact if the server stops while writing.
+    ///
+    /// # Parameters
+    /// - `x`, `z`: The coordinates of the chunk in the world.
+    /// - `data`: The uncompressed NBT data of the chunk.
+    pub fn write_chunk(&mut self, x: i32, z: i32, data: &[u8]) -> anyhow::Result<()> {
+        let mut encoder =
+            ZlibEncoder::new(Vec::with_capacity(data.len() / 2), Compression::default());
+        encoder.write_all(data)?;
+        let compressed = encoder.finish()?;
+        let external_path = self.get_external_path(x, z);
+        let mut payload = Vec::with_capacity(compressed.len() + 5);
+        if compressed.len() + 5 > MAX_SECTORS * SECTOR_SIZE {
+            std::fs::write(&external_path, &compressed)?;
+            payload.extend_from_slice(&1u32.to_be_bytes());
+            payload.push(COMPRESSION_ZLIB | EXTERNAL_FLAG);
+        } else {
+            payload.extend_from_slice(&(compressed.len() as u32 + 1).to_be_bytes());
+            payload.push(COMPRESSION_ZLIB);
+            payload.extend_from_slice(&compressed);
+            match std::fs::remove_file(&external_path) {
+                Err(err) if err.kind() != ErrorKind::NotFound => return Err(err.into()),
+                _ => {}
+            }
+        }
+        let sectors = payload.len().div_ceil(SECTOR_SIZE);
+        payload.resize(sectors * SECTOR_SIZE, 0);
+        let offset = self.allocate(sectors);
+        self.file
+            .seek(SeekFrom::Start((offset * SECTOR_SIZE) as u64))?;
+        self.file.write_all(&payload)?;
+
+        let index = get_index(x, z);
+        let previous = self.locations[index];
+        self.locations[index] = ((offset as u32) << 8) | sectors as u32;
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as u32;
+        self.file.seek(SeekFrom::Start((index * 4) as u64))?;
+        self.file.write_all(&self.locations[index].to_be_bytes())?;
+        self.file
+            .seek(SeekFrom::Start((SECTOR_SIZE + index * 4) as u64))?;
+        self.file.write_all(&timestamp.to_be_bytes())?;
+        if previous != 0 {
+            let previous_offset = (previous >> 8) as usize;
+            for sector in previous_offset..previous_offset + (previous & 0xFF) as usize {
+                self.used_sectors.remove(sector);
+            }
+        }
+        Ok(())
+    }
+
+    /// Finds the first run of free sectors large enough and marks it as used.
+    fn allocate(&mut self, sectors: usize) -> usize {
+        let mut start = HEADER_SECTORS;
+        while let Some(used) =
+            (start..start + sectors).find(|sector| self.used_sectors.contains(*sector))
+        {
+            start = used + 1;
+        }
+        for sector in start..start + sectors {
+            self.used_sectors.insert(sector);
+        }
+        start
+    }
+
+    /// Gets the path of the file storing a chunk too large for the region file.
+    fn get_external_path(&self, x: i32, z: i32) -> PathBuf {
+        self.path.with_file_name(format!("c.{}.{}.mcc", x, z))
+    }
+}
+
+/// Gets the index of a chunk in the tables of its region.
+#[inline]
+fn get_index(x: i32, z: i32) -> usize {
+    ((x & 31) + (z & 31) * 32) as usize
+}
+
+/// Decompresses the payload of a chunk with the given compression type.
+pub fn decompress(compression: u8, data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut result = Vec::with_capacity(data.len() * 4);
+    match compression {
+        COMPRESSION_GZIP => {
+            GzDecoder::new(data).read_to_end(&mut result)?;
+        }
+        COMPRESSION_ZLIB => {
+            ZlibDecoder::new(data).read_to_end(&mut result)?;
+        }
+        COMPRESSION_NONE => result.extend_from_slice(data),
+        COMPRESSION_LZ4 => decompress_lz4(data, &mut result)?,
+        _ => return Err(anyhow!("Unknown compression type: {}", compression)),
+    }
+    Ok(result)
+}
+
+/// Decompresses the block stream written by LZ4Java's `LZ4BlockOutputStream`.
+///
+/// Every block starts with the magic, a token holding the compression method,
+/// the compressed and the original length and a checksum, which is not verified.
+/// A block with an original length of 0 ends the stream.
+fn decompress_lz4(mut data: &[u8], result: &mut Vec<u8>) -> anyhow::Result<()> {
+    const HEADER_LENGTH: usize = LZ4_MAGIC.len() + 13;
+    while !data.is_empty() {
+        if data.len() < HEADER_LENGTH || &data[..LZ4_MAGIC.len()] != LZ4_MAGIC {
+            return Err(anyhow!("Invalid LZ4 block header"));
+        }
+        let token = data[8];
+        let compressed = i32::from_le_bytes(data[9..13].try_into()?);
+        let original = i32::from_le_bytes(data[13..17].try_into()?);
+        if compressed < 0 || original < 0 {
+            return Err(anyhow!("Negative LZ4 block length"));
+        }
+        let (compressed, original) = (compressed as usize, original as usize);
+        if result.len() + original > MAX_CHUNK_SIZE {
+            return Err(anyhow!(
+                "LZ4 stream exceeds the maximum chunk size of {} bytes",
+                MAX_CHUNK_SIZE
+            ));
+        }
+        let block = data
+            .get(HEADER_LENGTH..HEADER_LENGTH + compressed)
+            .ok_or_else(|| anyhow!("Truncated LZ4 block"))?;
+        data = &data[HEADER_LENGTH + compressed..];
+        if original == 0 {
+            break;
+        }
+        match token & 0xF0 {
+            LZ4_METHOD_RAW if compressed == original => result.extend_from_slice(block),
+            LZ4_METHOD_RAW => return Err(anyhow!("Raw LZ4 block of mismatched lengths")),
+            LZ4_METHOD_LZ4 => {
+                let start = result.len();
+                result.resize(start + original, 0);
+                if lz4_flex::block::decompress_into(block, &mut result[start..])? != original {
+                    return Err(anyhow!("LZ4 block shorter than its original length"));
+                }
+            }
+            method => return Err(anyhow!("Unknown LZ4 compression method: {}", method)),
+        }
+    }
+    Ok(())
+}
@@ -1,25 +1,52 @@
 use crate::entity::player::Player;
 use crate::entity::Entity;
+use crate::network::compression::{frame_packet, CachedFrame};
+use crate::registry::protocol_id::BLOCK_STATES;
+use crate::registry::BIOMES_INDEX;
 use crate::util::arc_channel::ArcChannel;
 use crate::util::io::WriteExt;
 use crate::util::raw::Raw;
+use crate::world::anvil::RegionStorage;
 use crate::world::chunk::ChunkUpdate::BlockChange;
 use crate::world::dimension::Dimension;
 use crate::world::height_map::HeightMap;
 use anyhow::anyhow;
 use arc_swap::ArcSwapOption;
 use bit_set::BitSet;
-use bytes::BytesMut;
+use bytes::{Bytes, BytesMut};
 use dashmap::DashMap;
 use hashbrown::HashMap;
 use parking_lot::{Mutex, MutexGuard, RwLock};
 use rayon::prelude::*;
 use simdnbt::owned::NbtTag::LongArray;
 use simdnbt::owned::{BaseNbt, NbtCompound};
-use std::sync::atomic::{AtomicI16, Ordering};
-use std::sync::{Arc, Weak};
+use std::sync::atomic::{AtomicBool, AtomicI16, AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Weak};
 use tokio::io::{AsyncWrite, AsyncWriteExt};
 
+/// Bits per entry of the direct block state palette, enough to hold every block state id.
+static GLOBAL_PALETTE_BITS: LazyLock<u8> = LazyLock::new(|| {
+    let max = BLOCK_STATES
+        .as_object()
+        .unwrap()
+        .values()
+        .flat_map(|block| block["states"].as_array().unwrap())
+        .map(|state| state["id"].as_u64().unwrap())
+        .max()
+        .unwrap_or(0);
+    (u64::BITS - max.leading_zeros()) as u8
+});
+/// Bits per entry of the direct biome palette.
+static GLOBAL_BIOME_PALETTE_BITS: LazyLock<u8> =
+    LazyLock::new(|| (usize::BITS - (BIOMES_INDEX.len() - 1).leading_zeros()) as u8);
+/// The biome of new sections.
+pub(crate) static DEFAULT_BIOME: LazyLock<u16> = LazyLock::new(|| {
+    BIOMES_INDEX
+        .iter()
+        .position(|biome| biome == "minecraft:plains")
+        .unwrap_or(0) as u16
+});
+
 pub struct Chunk {
     world_surface: Mutex<HeightMap>,
     motion_blocking: Mutex<HeightMap>,
@@ -28,7 +55,19 @@ pub struct Chunk {
     height: i32,
     dimension: Raw<DashMap<u64, Weak<Chunk>>>,
     channel: RwLock<ArcChannel<ChunkUpdate>>,
-    cache: ArcSwapOption<BytesMut>,
+    cache: ArcSwapOption<Cached<Arc<BytesMut>>>,
+    frame_cache: ArcSwapOption<Cached<CachedFrame>>,
+    /// Incremented after every change, so the data serialized before it is never used.
+    generation: AtomicU64,
+    storage: Arc<RegionStorage>,
+    /// Whether the chunk changed since it was loaded or saved.
+    dirty: AtomicBool,
+}
+
+/// A value built from the data of a chunk, valid as long as the chunk is at the same generation.
+struct Cached<T> {
+    generation: u64,
+    value: T,
 }
 
 impl Chunk {
@@ -60,6 +99,10 @@ impl Chunk {
             pos,
             channel: RwLock::default(),
             cache: ArcSwapOption::empty(),
+            frame_cache: ArcSwapOption::empty(),
+            generation: AtomicU64::new(0),
+            storage: dimension.storage.clone(),
+            dirty: AtomicBool::new(false),
         }
     }
     /// Retrieves the mutex guard for the world-surface height map.
@@ -98,16 +141,64 @@ impl Chunk {
     /// - `Ok(Arc<BytesMut>)`: The serialized data wrapped in an `Arc<BytesMut>`.
     /// - `Err(anyhow::Error)`: An error if serialization or cache operations fail.
     pub async fn get_serialized(&self) -> anyhow::Result<Arc<BytesMut>> {
-        match self.cache.load().as_ref() {
-            Some(cache) => Ok(cache.clone()),
-            None => {
-                let mut buf = Vec::with_capacity(2048);
-                self.serialize(&mut buf).await?;
-                let buf = Arc::from(BytesMut::from(buf.as_slice()));
-                self.cache.store(Some(buf.clone()));
-                Ok(buf)
+        let generation = self.generation.load(Ordering::Acquire);
+        if let Some(cache) = self.cache.load().as_ref() {
+            if cache.generation == generation {
+                return Ok(cache.value.clone());
             }
         }
+        let mut buf = Vec::with_capacity(2048);
+        self.serialize(&mut buf).await?;
+        let buf = Arc::from(BytesMut::from(buf.as_slice()));
+        // Data changed during the serialization is sent, but not cached.
+        if self.generation.load(Ordering::Acquire) == generation {
+            self.cache.store(Some(Arc::new(Cached {
+                generation,
+                value: buf.clone(),
+            })));
+        }
+        Ok(buf)
+    }
+
+    /// Retrieves the serialized chunk data as a complete packet frame.
+    ///
+    /// The frame is built from [`Chunk::get_serialized`] and cached for its packet id and threshold,
+    /// so the chunk is only compressed once no matter how many players receive it.
+    ///
+    /// # Parameters
+    /// - `packet_id`: The id of the packet carrying the chunk data.
+    /// - `threshold`: The compression threshold of the receiving connection.
+    ///
+    /// # Returns
+    /// - `Ok(Bytes)`: The frame, ready to be written to the socket.
+    /// - `Err(anyhow::Error)`: An error if serialization or compression fail.
+    pub async fn get_frame(&self, packet_id: i32, threshold: Option<i32>) -> anyhow::Result<Bytes> {
+        let generation = self.generation.load(Ordering::Acquire);
+        if let Some(cache) = self.frame_cache.load().as_ref() {
+            let frame = &cache.value;
+            if cache.generation == generation
+                && frame.packet_id == packet_id
+                && frame.threshold == threshold
+            {
+                return Ok(frame.frame.clone());
+            }
+        }
+        let serialized = self.get_serialized().await?;
+        let mut packet = Vec::with_capacity(serialized.len() + 5);
+        packet.write_var_int(packet_id).await?;
+        packet.extend_from_slice(&serialized);
+        let frame = Bytes::from(frame_packet(&packet, threshold).await?);
+        if self.generation.load(Ordering::Acquire) == generation {
+            self.frame_cache.store(Some(Arc::new(Cached {
+                generation,
+                value: CachedFrame {
+                    packet_id,
+                    threshold,
+                    frame: frame.clone(),
+                },
+            })));
+        }
+        Ok(frame)
     }
 
     pub fn player_enter(&self, player: &mut Player) {
@@ -123,9 +214,61 @@ impl Chunk {
             self.channel.write().remove(&recv);
         }
     }
+    /// Drops the serialized data after a change, marking the chunk to be saved.
+    ///
+    /// The generation is incremented, so the data a concurrent serialization read before the change
+    /// is never used, even if it is stored after this call.
     #[inline]
     fn invalidate_cache(&self) {
+        self.generation.fetch_add(1, Ordering::AcqRel);
         self.cache.store(None);
+        self.frame_cache.store(None);
+        self.mark_dirty();
+    }
+
+    /// Marks the chunk to be saved.
+    #[inline]
+    pub fn mark_dirty(&self) {
+        self.dirty.store(true, Ordering::Release);
+    }
+
+    /// Whether the chunk changed since it was loaded or saved.
+    #[inline]
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.load(Ordering::Acquire)
+    }
+
+    /// Clears the dirty flag before saving, so changes made while saving are saved next time.
+    ///
+    /// # Returns
+    /// Whether the chunk was dirty.
+    #[inline]
+    pub(crate) fn take_dirty(&self) -> bool {
+        self.dirty.swap(false, Ordering::AcqRel)
+    }
+    /// Moves the data out of a chunk being dropped, leaving it empty.
+    fn take_data(&mut self) -> Chunk {
+        let height = self.height;
+        Chunk {
+            world_surface: std::mem::replace(
+                &mut self.world_surface,
+                Mutex::new(HeightMap::new(height)),
+            ),
+            motion_blocking: std::mem::replace(
+                &mut self.motion_blocking,
+                Mutex::new(HeightMap::new(height)),
+            ),
+            data: std::mem::take(&mut self.data),
+            pos: self.pos,
+            height,
+            dimension: self.dimension.clone(),
+            channel: RwLock::default(),
+            cache: ArcSwapOption::empty(),
+            frame_cache: ArcSwapOption::empty(),
+            generation: AtomicU64::new(0),
+            storage: self.storage.clone(),
+            dirty: AtomicBool::new(true),
+        }
     }
     /// Get the block information at the specified coordinates (x, y, z).
     ///
@@ -170,12 +313,13 @@ impl Chunk {
         } else if y < 0 || y >= self.height {
             return Err(anyhow!("Invalid y coord: {}", y));
         }
-        self.invalidate_cache();
-        self.channel.read().broadcast(BlockChange(x, y, z, block));
         let idx = y as usize / 16;
         let section = self.data.get(idx).ok_or(anyhow!("Invalid position"))?;
         let sy = ((y as usize) - (16 * idx)) as u32;
         section.set_state(x as u32, sy, z as u32, block);
+        // Invalidated after the change, so the data serialized before it is dropped by its generation.
+        self.invalidate_cache();
+        self.channel.read().broadcast(BlockChange(x, y, z, block));
         Ok(())
     }
     /// Get the section data at the specified index
@@ -195,6 +339,11 @@ impl Chunk {
         self.invalidate_cache();
         Some(self.data.get(y / 16)?.get_data_guard())
     }
+    /// Gets the sections of the chunk, from the bottom of the dimension upwards.
+    #[inline]
+    pub(crate) fn get_sections(&self) -> &[Section] {
+        &self.data
+    }
     /// Get a guard object that provides pre-locked access to the chunk data.
     pub fn get_guard(&self) -> ChunkGuard<'_> {
         let sections = self.data.len();
@@ -204,6 +353,7 @@ impl Chunk {
             data.push(self.data.get(i).unwrap().get_data_guard());
         }
         ChunkGuard {
+            chunk: self,
             data,
             height: self.height,
         }
@@ -340,16 +490,14 @@ impl Chunk {
         // Block Data
         let mut section_data = Vec::with_capacity(2048);
         for section in self.data.iter() {
-            section
-                .get_data_guard()
-                .serialize(&mut section_data)
-                .await?;
+            section.serialize(&mut section_data).await?;
         }
         buffer.write_var_int(section_data.len() as i32).await?;
         buffer.write_all(&section_data).await?;
         // Block Entities (WIP)
         buffer.write_var_int(0).await?;
         // Light (WIP)
+        // Bit 0 of the masks is the section below the world, so the chunk's sections start at 1.
         let sky_light_mask = BitSet::with_capacity(self.data.len() + 2);
         let block_light_mask = BitSet::with_capacity(self.data.len() + 2);
         let mut empty_sky_light_mask = BitSet::with_capacity(self.data.len() + 2);
@@ -391,19 +539,19 @@ impl Chunk {
 
                 if sky_not_empty {
                     sky.lock()[index] = true;
-                    sky_light_mask.lock().insert(index);
-                    empty_sky_light_mask.lock().remove(index);
+                    sky_light_mask.lock().insert(index + 1);
+                    empty_sky_light_mask.lock().remove(index + 1);
                 } else {
-                    sky_light_mask.lock().remove(index);
-                    empty_sky_light_mask.lock().insert(index);
+                    sky_light_mask.lock().remove(index + 1);
+                    empty_sky_light_mask.lock().insert(index + 1);
                 }
                 if block_not_empty {
                     block.lock()[index] = true;
-                    block_light_mask.lock().insert(index);
-                    empty_block_light_mask.lock().remove(index);
+                    block_light_mask.lock().insert(index + 1);
+                    empty_block_light_mask.lock().remove(index + 1);
                 } else {
-                    block_light_mask.lock().remove(index);
-                    empty_block_light_mask.lock().insert(index);
+                    block_light_mask.lock().remove(index + 1);
+                    empty_block_light_mask.lock().insert(index + 1);
                 }
             });
         let sky_mask = sky_light_mask.into_inner();
@@ -447,21 +595,47 @@ impl Chunk {
 
 impl Drop for Chunk {
     fn drop(&mut self) {
-        if let Some(chunks) = self.dimension.get() {
-            chunks.remove(&self.pos);
+        let dimension = self.dimension.clone();
+        let Some(chunks) = dimension.get() else {
+            return;
+        };
+        let this: *const Chunk = self;
+        if self.is_dirty() {
+            if let Some(mut entry) = chunks.get_mut(&self.pos) {
+                if std::ptr::eq(entry.as_ptr(), this) {
+                    // The data moves to a new chunk, kept in the dimension until it is saved,
+                    // so loading the chunk meanwhile gets its latest data instead of the stored one.
+                    let chunk = Arc::new(self.take_data());
+                    *entry = Arc::downgrade(&chunk);
+                    drop(entry);
+                    self.storage.queue_unloaded(chunk);
+                    return;
+                }
+            }
         }
+        chunks.remove_if(&self.pos, |_, chunk| std::ptr::eq(chunk.as_ptr(), this));
     }
 }
 
+/// A change of a chunk, broadcast to the players that hold it.
 pub enum ChunkUpdate {
+    /// A block changed, at coordinates relative to the chunk
+    /// with y counted from the bottom of the dimension.
     BlockChange(i32, i32, i32, u32),
 }
 
 pub struct ChunkGuard<'a> {
+    chunk: &'a Chunk,
     data: Vec<SectionDataGuard<'a>>,
     pub height: i32,
 }
 
+impl Drop for ChunkGuard<'_> {
+    fn drop(&mut self) {
+        self.chunk.invalidate_cache();
+    }
+}
+
 impl ChunkGuard<'_> {
     /// Retrieves the block information at the specified coordinates (x, y, z).
     ///
@@ -529,6 +703,7 @@ pub struct Section {
     sky_light: Mutex<[u8; 2048]>,
     block_light: Mutex<[u8; 2048]>,
     data: Mutex<[u32; 4096]>,
+    biomes: Mutex<[u16; 64]>,
     block_count: AtomicI16,
 }
 
@@ -538,51 +713,59 @@ impl Section {
             sky_light: Mutex::new([0; 2048]),
             block_light: Mutex::new([0; 2048]),
             data: Mutex::new([0; 4096]),
+            biomes: Mutex::new([*DEFAULT_BIOME; 64]),
             block_count: AtomicI16::new(0),
         }
     }
 
     pub fn set_sky_light(&self, x: u32, y: u32, z: u32, light: u8) {
-        let index = (((y << 8) | (z << 4) | x) / 2) as usize;
-        let mut lock = self.sky_light.lock();
-        let base = lock[index];
-        if x % 2 == 0 {
-            lock[index] = (light << 4) | (base & 0xF);
-        } else {
-            lock[index] = (light & 0xF) | (base & 0xF0);
-        }
+        set_nibble(&mut self.sky_light.lock(), x, y, z, light);
     }
 
     pub fn get_sky_light(&self, x: u32, y: u32, z: u32) -> u8 {
-        let index = (((y << 8) | (z << 4) | x) / 2) as usize;
-        let lock = self.sky_light.lock();
-        let value = lock[index];
-        if x % 2 == 0 {
-            value >> 4
-        } else {
-            value
-        }
+        get_nibble(&self.sky_light.lock(), x, y, z)
     }
 
     pub fn set_block_light(&self, x: u32, y: u32, z: u32, light: u8) {
-        let index = (((y << 8) | (z << 4) | x) / 2) as usize;
-        let mut lock = self.block_light.lock();
-        let base = lock[index];
-        if x % 2 == 0 {
-            lock[index] = (light << 4) | (base & 0xF);
-        } else {
-            lock[index] = (light & 0xF) | (base & 0xF0)
-        }
+        set_nibble(&mut self.block_light.lock(), x, y, z, light);
     }
 
     pub fn get_block_light(&self, x: u32, y: u32, z: u32) -> u8 {
-        let index = (((y << 8) | (z << 4) | x) / 2) as usize;
-        let lock = self.block_light.lock();
-        let value = lock[index];
-        if x % 2 == 0 {
-            value >> 4
-        } else {
-            value
+        get_nibble(&self.block_light.lock(), x, y, z)
+    }
+
+    /// Gets the biome of a 4x4x4 cell, with coordinates ranging from 0 to 3.
+    pub fn get_biome(&self, x: u32, y: u32, z: u32) -> u16 {
+        self.biomes.lock()[((y << 4) | (z << 2) | x) as usize]
+    }
+
+    /// Sets the biome of a 4x4x4 cell, with coordinates ranging from 0 to 3.
+    pub fn set_biome(&self, x: u32, y: u32, z: u32, biome: u16) {
+        self.biomes.lock()[((y << 4) | (z << 2) | x) as usize] = biome;
+    }
+
+    /// Gets a copy of the biomes of the 4x4x4 cells, indexed like the block states.
+    pub(crate) fn get_biomes(&self) -> [u16; 64] {
+        *self.biomes.lock()
+    }
+
+    /// Replaces the whole content of the section, recounting the non-air blocks.
+    pub(crate) fn load(
+        &self,
+        states: &[u32; 4096],
+        biomes: &[u16; 64],
+        sky_light: Option<&[u8; 2048]>,
+        block_light: Option<&[u8; 2048]>,
+    ) {
+        let count = states.iter().filter(|state| **state != 0).count();
+        *self.data.lock() = *states;
+        self.block_count.store(count as i16, Ordering::SeqCst);
+        *self.biomes.lock() = *biomes;
+        if let Some(sky_light) = sky_light {
+            *self.sky_light.lock() = *sky_light;
+        }
+        if let Some(block_light) = block_light {
+            *self.block_light.lock() = *block_light;
         }
     }
 
@@ -631,6 +814,7 @@ impl Section {
         SectionDataGuard {
             data: self.data.lock(),
             count: &self.block_count,
+            biomes: &self.biomes,
         }
     }
 
@@ -645,6 +829,13 @@ impl Section {
     pub fn get_block_count(&self) -> i16 {
         self.block_count.load(Ordering::Acquire)
     }
+
+    /// Serializes a copy of the section, so the lock is not held across the writes.
+    pub async fn serialize<W: AsyncWrite + Unpin>(&self, buffer: &mut W) -> anyhow::Result<()> {
+        let data = Box::new(*self.data.lock());
+        let biomes = *self.biomes.lock();
+        serialize_section(self.get_block_count(), &data, &biomes, buffer).await
+    }
 }
 
 impl Default for Section {
@@ -660,49 +851,40 @@ pub struct SectionLightGuard<'a> {
 
 impl SectionLightGuard<'_> {
     pub fn get_sky_light(&self, x: u32, y: u32, z: u32) -> u8 {
-        let index = (((y << 8) | (z << 4) | x) / 2) as usize;
-        let base = self.sky_light[index];
-        if x % 2 == 0 {
-            base >> 4
-        } else {
-            base
-        }
+        get_nibble(&self.sky_light, x, y, z)
     }
 
     pub fn get_block_light(&self, x: u32, y: u32, z: u32) -> u8 {
-        let index = (((y << 8) | (z << 4) | x) / 2) as usize;
-        let base = self.block_light[index];
-        if x % 2 == 0 {
-            base >> 4
-        } else {
-            base
-        }
+        get_nibble(&self.block_light, x, y, z)
     }
 
     pub fn set_sky_light(&mut self, x: u32, y: u32, z: u32, light: u8) {
-        let index = (((y << 8) | (z << 4) | x) / 2) as usize;
-        let base = self.sky_light[index];
-        if x % 2 == 0 {
-            self.sky_light[index] = (light << 4) | (base & 0xF);
-        } else {
-            self.sky_light[index] = (light & 0xF) | (base & 0xF0)
-        }
+        set_nibble(&mut self.sky_light, x, y, z, light);
     }
 
     pub fn set_block_light(&mut self, x: u32, y: u32, z: u32, light: u8) {
-        let index = (((y << 8) | (z << 4) | x) / 2) as usize;
-        let base = self.block_light[index];
-        if x % 2 == 0 {
-            self.block_light[index] = (light << 4) | (base & 0xF);
-        } else {
-            self.block_light[index] = (light & 0xF) | (base & 0xF0)
-        }
+        set_nibble(&mut self.block_light, x, y, z, light);
     }
 }
 
+/// Reads a light value, even indices being stored in the low nibble like vanilla does.
+#[inline]
+fn get_nibble(light: &[u8; 2048], x: u32, y: u32, z: u32) -> u8 {
+    let index = ((y << 8) | (z << 4) | x) as usize;
+    (light[index >> 1] >> ((index & 1) << 2)) & 0xF
+}
+
+#[inline]
+fn set_nibble(light: &mut [u8; 2048], x: u32, y: u32, z: u32, value: u8) {
+    let index = ((y << 8) | (z << 4) | x) as usize;
+    let shift = (index & 1) << 2;
+    light[index >> 1] = (light[index >> 1] & !(0xF << shift)) | ((value & 0xF) << shift);
+}
+
 pub struct SectionDataGuard<'a> {
     pub data: MutexGuard<'a, [u32; 4096]>,
     pub count: &'a AtomicI16,
+    biomes: &'a Mutex<[u16; 64]>,
 }
 
 impl SectionDataGuard<'_> {
@@ -781,50 +963,94 @@ impl SectionDataGuard<'_> {
     ///
     /// # Returns
     /// Returns an `anyhow::Result<()>`, indicating the result of the asynchronous operation.
-    ///
     pub async fn serialize<W: AsyncWrite + Unpin>(&self, buffer: &mut W) -> anyhow::Result<()> {
-        let count_all = self.count.load(Ordering::SeqCst);
-        buffer.write_i16(count_all).await?;
-        let mut set: HashMap<u32, u32> = HashMap::with_capacity(1024);
-        let mut entry: i32 = -1;
-        let mut palette = Vec::with_capacity(4096);
-        for i in self.data.iter() {
-            let id: u32 = match set.get(i) {
-                None => unsafe {
-                    entry += 1;
-                    if entry >= 1024 {
-                        palette = self.data.to_vec();
-                        break;
-                    }
-                    set.insert_unique_unchecked(*i, entry as u32);
-                    entry as u32
-                },
-                Some(id) => *id,
-            };
-            palette.push(id);
-        }
-        if set.len() == 1 {
-            buffer.write_u8(0).await?;
-            buffer
-                .write_var_int(unsafe { *palette.get_unchecked(0) as i32 })
-                .await?;
-            buffer.write_var_int(0).await?;
-            return Ok(());
+        let biomes = *self.biomes.lock();
+        serialize_section(
+            self.count.load(Ordering::SeqCst),
+            &self.data,
+            &biomes,
+            buffer,
+        )
+        .await
+    }
+}
+
+/// Writes the block count, the block states and the biomes of a section.
+async fn serialize_section<W: AsyncWrite + Unpin>(
+    count: i16,
+    data: &[u32; 4096],
+    biomes: &[u16; 64],
+    buffer: &mut W,
+) -> anyhow::Result<()> {
+    buffer.write_i16(count).await?;
+    write_container(buffer, data, 4, 8, *GLOBAL_PALETTE_BITS).await?;
+    let biomes: Vec<u32> = biomes.iter().map(|biome| *biome as u32).collect();
+    write_container(buffer, &biomes, 1, 3, *GLOBAL_BIOME_PALETTE_BITS).await
+}
+
+/// Writes a paletted container, picking the single valued, indirect or direct format.
+///
+/// # Parameters
+/// - `min_bits`, `max_bits`: The range of bits per entry of the indirect palette.
+/// - `direct_bits`: The bits per entry of the global palette.
+async fn write_container<W: AsyncWrite + Unpin>(
+    buffer: &mut W,
+    data: &[u32],
+    min_bits: u8,
+    max_bits: u8,
+    direct_bits: u8,
+) -> anyhow::Result<()> {
+    let mut palette: Vec<u32> = Vec::with_capacity(16);
+    let mut indices: HashMap<u32, u32> = HashMap::with_capacity(16);
+    for state in data.iter() {
+        if !indices.contains_key(state) {
+            indices.insert(*state, palette.len() as u32);
+            palette.push(*state);
         }
-        let bit_len = (palette.len() as f32).log2().ceil() as u8;
-        buffer.write_u8(bit_len).await?;
-        let entry_per_long = 64 / bit_len as i32;
-        let data_array_len = 4096 / entry_per_long;
-        buffer.write_var_int(data_array_len).await?;
-        for i in 0..data_array_len {
-            let mut long = 0;
-            for j in 0..entry_per_long {
-                let id = unsafe { *palette.get_unchecked((i * entry_per_long + j) as usize) };
-                long |= (id as u64) << (j * bit_len as i32);
+    }
+    if palette.len() == 1 {
+        // Single valued
+        buffer.write_u8(0).await?;
+        buffer.write_var_int(palette[0] as i32).await?;
+        buffer.write_var_int(0).await?;
+    } else {
+        let bits = ((u32::BITS - (palette.len() as u32 - 1).leading_zeros()) as u8).max(min_bits);
+        if bits <= max_bits {
+            // Indirect
+            buffer.write_u8(bits).await?;
+            buffer.write_var_int(palette.len() as i32).await?;
+            for state in palette.iter() {
+                buffer.write_var_int(*state as i32).await?;
             }
-            buffer.write_u64(long).await?;
+            let entries: Vec<u32> = data.iter().map(|state| indices[state]).collect();
+            write_packed(buffer, bits, &entries).await?;
+        } else {
+            // Direct
+            buffer.write_u8(direct_bits).await?;
+            write_packed(buffer, direct_bits, data).await?;
         }
-        // TODO: Biome palette
-        Ok(())
     }
+    Ok(())
+}
+
+/// Writes the data array of a paletted container.
+///
+/// Entries never span two longs, so the remaining bits of each long are left empty.
+async fn write_packed<W: AsyncWrite + Unpin>(
+    buffer: &mut W,
+    bits: u8,
+    entries: &[u32],
+) -> anyhow::Result<()> {
+    let per_long = 64 / bits as usize;
+    buffer
+        .write_var_int(entries.len().div_ceil(per_long) as i32)
+        .await?;
+    for entries in entries.chunks(per_long) {
+        let mut long = 0u64;
+        for (i, entry) in entries.iter().enumerate() {
+            long |= (*entry as u64) << (i * bits as usize);
+        }
+        buffer.write_u64(long).await?;
+    }
+    Ok(())
 }
@@ -1,17 +1,35 @@
-use crate::config::WORLDGEN_IMPLEMENTATION;
+use crate::config::{LEVEL_NAME, WORLDGEN_IMPLEMENTATION};
 use crate::registry::dimension_type::DimensionType;
 use crate::util::to_dim_xz;
+use crate::world::anvil::{get_dimension_directory, RegionStorage};
 use crate::world::chunk::Chunk;
 use crate::world::gen::{Worldgen, IMPLEMENTS};
+use crate::world::ticket::{self, ChunkTickets, LoadLevel, TicketType, MAX_LOADS_PER_TICK};
+use dashmap::mapref::entry::Entry;
 use dashmap::DashMap;
+use parking_lot::{Mutex, RwLock};
+use simdnbt::owned::{NbtCompound, NbtTag};
+use spotlight::event::EventCallback;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Weak};
+use tracing::error;
+
+/// Called every tick for each chunk at the [ticking](LoadLevel::Ticking) level or above.
+pub static CHUNK_TICK_CALLBACK: RwLock<EventCallback<Arc<Chunk>>> =
+    RwLock::new(EventCallback::new());
 
 pub struct Dimension {
     pub(crate) dim_idx: u32,
     pub dimension_type: DimensionType,
     pub dimension_name: String,
+    /// The tickets keeping chunks loaded, which hold the chunks they load.
+    ///
+    /// Declared before `chunks`, since the chunks remove themselves from it when dropped.
+    tickets: Mutex<ChunkTickets>,
     pub chunks: DashMap<u64, Weak<Chunk>>,
     worldgen: &'static dyn Worldgen,
+    directory: PathBuf,
+    pub(crate) storage: Arc<RegionStorage>,
 }
 impl Dimension {
     /// Creates a new dimension object.
@@ -33,7 +51,27 @@ impl Dimension {
     /// The world generator is responsible
     /// for generating the logic and rules of the dimension world based on the configuration of the dimension object.
     pub fn new(dimension_type: DimensionType, dimension_name: String, dim_idx: u32) -> Dimension {
+        let directory = get_dimension_directory(Path::new(*LEVEL_NAME), &dimension_name);
+        let mut tickets = ChunkTickets::default();
+        match ticket::load_forced(&directory) {
+            Ok(forced) => {
+                for (x, z) in forced {
+                    tickets.set_forced(x, z, true);
+                }
+                tickets.take_forced_changed();
+            }
+            Err(err) => error!(
+                "Failed to load the forced chunks of {}: {:?}",
+                dimension_name, err
+            ),
+        }
         Dimension {
+            storage: Arc::new(RegionStorage::new(
+                directory.join("region"),
+                dimension_type.min_y >> 4,
+            )),
+            directory,
+            tickets: Mutex::new(tickets),
             dimension_type,
             dimension_name,
             chunks: DashMap::with_capacity(512),
@@ -57,19 +95,43 @@ impl Dimension {
     /// Returns an `Arc`-wrapped `Chunk` object representing the chunk at the specified location.
     pub fn get_chunk(&self, chunk_x: i32, chunk_z: i32) -> Arc<Chunk> {
         let key = to_dim_xz(chunk_x, chunk_z);
-        let tv = self.chunks.get_mut(&key);
-        if tv.is_some() {
-            let tvv = tv.unwrap().value_mut().upgrade();
-            if let Some(ret) = tvv {
-                return ret;
+        loop {
+            match self.chunks.get(&key).map(|chunk| chunk.upgrade()) {
+                Some(Some(chunk)) => return chunk,
+                // The chunk is being dropped, and removes or replaces its entry right after.
+                Some(None) => {
+                    std::thread::yield_now();
+                    continue;
+                }
+                None => {}
+            }
+            let chunk = Arc::new(self.create_new_chunk(chunk_x, chunk_z));
+            match self.chunks.entry(key) {
+                // Loaded by another thread meanwhile, the copy loaded here is dropped.
+                Entry::Occupied(entry) => {
+                    if let Some(chunk) = entry.get().upgrade() {
+                        return chunk;
+                    }
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(Arc::downgrade(&chunk));
+                    return chunk;
+                }
             }
         }
-        let chunk = Arc::new(self.create_new_chunk(chunk_x, chunk_z));
-        self.insert_new_chunk(chunk_x, chunk_z, &chunk);
-        chunk
     }
+    /// Loads the chunk from the region files, or generates it if it is not stored.
+    ///
+    /// A chunk which cannot be read is generated again, but never saved over the stored one.
     fn create_new_chunk(&self, x: i32, z: i32) -> Chunk {
-        // TODO
+        match self.storage.load_chunk(self, x, z) {
+            Ok(Some(chunk)) => return chunk,
+            Ok(None) => {}
+            Err(err) => error!(
+                "Failed to load chunk [{}, {}] of {}, generating it again without saving it: {}",
+                x, z, self.dimension_name, err
+            ),
+        }
         let chunk = Chunk::new(self, to_dim_xz(x, z));
         self.worldgen.gen(chunk)
     }
@@ -120,8 +182,129 @@ impl Dimension {
             .unwrap();
         chunk
     }
-    fn insert_new_chunk(&self, x: i32, z: i32, chunk: &Arc<Chunk>) {
-        self.chunks.insert(to_dim_xz(x, z), Arc::downgrade(chunk));
+    /// Adds a ticket keeping the chunks within a radius around a chunk loaded.
+    ///
+    /// The chunks are loaded on the next tick.
+    ///
+    /// # Parameters
+    /// - `radius`: How far the chunks are loaded,
+    ///   the ones within `radius - 1` being ticked and within `radius - 2` ticking their entities.
+    pub fn add_ticket(&self, chunk_x: i32, chunk_z: i32, ticket_type: TicketType, radius: u8) {
+        self.tickets
+            .lock()
+            .add(chunk_x, chunk_z, ticket_type, radius);
+    }
+    /// Removes one ticket of a type from a chunk.
+    ///
+    /// The chunks no longer kept loaded are released on the next tick.
+    ///
+    /// # Returns
+    /// Whether the chunk had a ticket of this type.
+    pub fn remove_ticket(&self, chunk_x: i32, chunk_z: i32, ticket_type: &TicketType) -> bool {
+        self.tickets.lock().remove(chunk_x, chunk_z, ticket_type)
+    }
+    /// Gets what is done with a chunk, depending on the tickets around it.
+    pub fn get_load_level(&self, chunk_x: i32, chunk_z: i32) -> LoadLevel {
+        LoadLevel::from_level(self.tickets.lock().get_level(chunk_x, chunk_z))
+    }
+    /// Marks a chunk to be kept loaded and ticked until it is unmarked, like `forceload`.
+    ///
+    /// # Returns
+    /// Whether the chunk was changed, `false` if it was already marked or unmarked.
+    pub fn set_chunk_forced(&self, chunk_x: i32, chunk_z: i32, forced: bool) -> bool {
+        self.tickets.lock().set_forced(chunk_x, chunk_z, forced)
+    }
+    /// Gets the positions of the chunks marked by [`set_chunk_forced`](Dimension::set_chunk_forced), sorted.
+    pub fn get_forced_chunks(&self) -> Vec<(i32, i32)> {
+        self.tickets.lock().get_forced()
+    }
+    /// Loads and releases the chunks following the tickets, then ticks the ticking chunks.
+    ///
+    /// At most [`MAX_LOADS_PER_TICK`] chunks are loaded in a tick, so new tickets do not stall it.
+    pub(crate) fn tick_chunks(&self) {
+        let (to_load, unloaded) = {
+            let mut tickets = self.tickets.lock();
+            let unloaded = tickets.update();
+            (tickets.take_to_load(MAX_LOADS_PER_TICK), unloaded)
+        };
+        // Dropped without the lock, since the chunks are saved when unloaded.
+        drop(unloaded);
+        if !to_load.is_empty() {
+            let chunks = to_load
+                .into_iter()
+                .map(|(x, z)| self.get_chunk(x, z))
+                .collect();
+            self.tickets.lock().load(chunks);
+        }
+        let callback = CHUNK_TICK_CALLBACK.read();
+        for chunk in self.tickets.lock().get_ticking_chunks() {
+            callback.interact(chunk);
+        }
+    }
+    /// Saves the loaded chunks that changed since they were loaded or saved,
+    /// and the forced chunks if they changed.
+    ///
+    /// A chunk failing to save stays dirty, so it is saved again later.
+    ///
+    /// # Returns
+    /// The last error, once every chunk was tried.
+    pub fn save(&self) -> anyhow::Result<()> {
+        let chunks: Vec<Arc<Chunk>> = self
+            .chunks
+            .iter()
+            .filter_map(|chunk| chunk.value().upgrade())
+            .collect();
+        let mut result = Ok(());
+        let forced = {
+            let mut tickets = self.tickets.lock();
+            tickets.take_forced_changed().then(|| tickets.get_forced())
+        };
+        if let Some(forced) = forced {
+            if let Err(err) = ticket::save_forced(&self.directory, &forced) {
+                self.tickets.lock().set_forced_changed();
+                error!(
+                    "Failed to save the forced chunks of {}: {:?}",
+                    self.dimension_name, err
+                );
+                result = Err(err);
+            }
+        }
+        for chunk in chunks {
+            if !chunk.take_dirty() {
+                continue;
+            }
+            if let Err(err) = self.storage.save_chunk(&chunk) {
+                chunk.mark_dirty();
+                let (x, z) = chunk.get_position();
+                error!(
+                    "Failed to save chunk [{}, {}] of {}: {:?}",
+                    x, z, self.dimension_name, err
+                );
+                result = Err(err);
+            }
+        }
+        // Saved with the other chunks above, so they are only released.
+        if let Err(err) = self.storage.save_unloaded() {
+            result = Err(err);
+        }
+        result
+    }
+    /// Gets the dimension type and the generator of the dimension, as written to `level.dat`.
+    ///
+    /// # Returns
+    /// `None` if the world generator has no vanilla equivalent for this dimension.
+    pub fn get_generator_settings(&self) -> Option<NbtCompound> {
+        let generator = match self.worldgen.get_generator() {
+            Some(generator) => generator,
+            None => get_vanilla_generator(&self.dimension_name)?,
+        };
+        Some(NbtCompound::from_values(vec![
+            (
+                "type".into(),
+                NbtTag::String(self.dimension_name.as_str().into()),
+            ),
+            ("generator".into(), NbtTag::Compound(generator)),
+        ]))
     }
 }
 
@@ -131,3 +314,45 @@ impl PartialEq for Dimension {
         self.dim_idx == other.dim_idx
     }
 }
+
+/// Gets the default generator of a vanilla dimension.
+fn get_vanilla_generator(dimension_name: &str) -> Option<NbtCompound> {
+    let (settings, biome_source) = match dimension_name {
+        "minecraft:overworld" => (
+            "minecraft:overworld",
+            vec![
+                (
+                    "type".into(),
+                    NbtTag::String("minecraft:multi_noise".into()),
+                ),
+                (
+                    "preset".into(),
+                    NbtTag::String("minecraft:overworld".into()),
+                ),
+            ],
+        ),
+        "minecraft:the_nether" => (
+            "minecraft:nether",
+            vec![
+                (
+                    "type".into(),
+                    NbtTag::String("minecraft:multi_noise".into()),
+                ),
+                ("preset".into(), NbtTag::String("minecraft:nether".into())),
+            ],
+        ),
+        "minecraft:the_end" => (
+            "minecraft:end",
+            vec![("type".into(), NbtTag::String("minecraft:the_end".into()))],
+        ),
+        _ => return None,
+    };
+    Some(NbtCompound::from_values(vec![
+        ("type".into(), NbtTag::String("minecraft:noise".into())),
+        ("settings".into(), NbtTag::String(settings.into())),
+        (
+            "biome_source".into(),
+            NbtTag::Compound(NbtCompound::from_values(biome_source)),
+        ),
+    ]))
+}
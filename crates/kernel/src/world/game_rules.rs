@@ -0,0 +1,125 @@
+use simdnbt::owned::{NbtCompound, NbtTag};
+use std::collections::BTreeMap;
+
+/// The game rules of vanilla and their default values.
+const DEFAULT_GAME_RULES: &[(&str, &str)] = &[
+    ("announceAdvancements", "true"),
+    ("blockExplosionDropDecay", "true"),
+    ("commandBlockOutput", "true"),
+    ("commandModificationBlockLimit", "32768"),
+    ("disableElytraMovementCheck", "false"),
+    ("disableRaids", "false"),
+    ("doDaylightCycle", "true"),
+    ("doEntityDrops", "true"),
+    ("doFireTick", "true"),
+    ("doImmediateRespawn", "false"),
+    ("doInsomnia", "true"),
+    ("doLimitedCrafting", "false"),
+    ("doMobLoot", "true"),
+    ("doMobSpawning", "true"),
+    ("doPatrolSpawning", "true"),
+    ("doTileDrops", "true"),
+    ("doTraderSpawning", "true"),
+    ("doVinesSpread", "true"),
+    ("doWardenSpawning", "true"),
+    ("doWeatherCycle", "true"),
+    ("drowningDamage", "true"),
+    ("enderPearlsVanishOnDeath", "true"),
+    ("fallDamage", "true"),
+    ("fireDamage", "true"),
+    ("forgiveDeadPlayers", "true"),
+    ("freezeDamage", "true"),
+    ("globalSoundEvents", "true"),
+    ("keepInventory", "false"),
+    ("lavaSourceConversion", "false"),
+    ("logAdminCommands", "true"),
+    ("maxCommandChainLength", "65536"),
+    ("maxCommandForkCount", "65536"),
+    ("maxEntityCramming", "24"),
+    ("mobExplosionDropDecay", "true"),
+    ("mobGriefing", "true"),
+    ("naturalRegeneration", "true"),
+    ("playersNetherPortalCreativeDelay", "1"),
+    ("playersNetherPortalDefaultDelay", "80"),
+    ("playersSleepingPercentage", "100"),
+    ("projectilesCanBreakBlocks", "true"),
+    ("randomTickSpeed", "3"),
+    ("reducedDebugInfo", "false"),
+    ("sendCommandFeedback", "true"),
+    ("showDeathMessages", "true"),
+    ("snowAccumulationHeight", "1"),
+    ("spawnChunkRadius", "2"),
+    ("spawnRadius", "10"),
+    ("spectatorsGenerateChunks", "true"),
+    ("tntExplosionDropDecay", "false"),
+    ("universalAnger", "false"),
+    ("waterSourceConversion", "true"),
+];
+
+/// The game rules of a world, stored as strings like in `level.dat`.
+///
+/// Rules unknown to the server are kept, so they are written back unchanged.
+#[derive(Clone)]
+pub struct GameRules {
+    rules: BTreeMap<String, String>,
+}
+
+impl GameRules {
+    /// Gets the value of a rule.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.rules.get(name).map(String::as_str)
+    }
+
+    /// Gets the value of a boolean rule, `false` if it is not set.
+    pub fn get_bool(&self, name: &str) -> bool {
+        self.get(name) == Some("true")
+    }
+
+    /// Gets the value of an integer rule, 0 if it is not set.
+    pub fn get_int(&self, name: &str) -> i32 {
+        self.get(name)
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(0)
+    }
+
+    pub fn set(&mut self, name: &str, value: impl ToString) {
+        self.rules.insert(name.to_string(), value.to_string());
+    }
+
+    /// Iterates over the rules, sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.rules
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    /// Reads the rules stored in `level.dat`, the missing ones keeping their default value.
+    pub fn read(nbt: &NbtCompound) -> GameRules {
+        let mut rules = GameRules::default();
+        for (name, value) in nbt.iter() {
+            if let Some(value) = value.string() {
+                rules.set(&name.to_str(), value.to_str());
+            }
+        }
+        rules
+    }
+
+    pub fn write(&self) -> NbtCompound {
+        NbtCompound::from_values(
+            self.iter()
+                .map(|(name, value)| (name.into(), NbtTag::String(value.into())))
+                .collect(),
+        )
+    }
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        GameRules {
+            rules: DEFAULT_GAME_RULES
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+        }
+    }
+}
@@ -5,6 +5,7 @@ use crate::registry::registries::register_vanilla;
 use crate::world::chunk::Chunk;
 use crate::world::gen::impls::SuperFlatWorldgen;
 use hashbrown::HashMap;
+use simdnbt::owned::NbtCompound;
 use std::sync::LazyLock;
 
 pub static IMPLEMENTS: LazyLock<HashMap<String, Box<dyn Worldgen>>> = LazyLock::new(|| {
@@ -26,4 +27,11 @@ pub static IMPLEMENTS: LazyLock<HashMap<String, Box<dyn Worldgen>>> = LazyLock::
 
 pub trait Worldgen: Send + Sync {
     fn gen(&self, chunk: Chunk) -> Chunk;
+    /// Gets the vanilla generator matching this one, written to `level.dat`
+    /// so vanilla generates the same terrain next to the saved chunks.
+    ///
+    /// The default vanilla generator of the dimension is used if `None`.
+    fn get_generator(&self) -> Option<NbtCompound> {
+        None
+    }
 }
@@ -1,5 +1,7 @@
+use crate::registry::protocol_id::get_block_state_properties;
 use crate::world::chunk::Chunk;
 use crate::world::gen::Worldgen;
+use simdnbt::owned::{NbtCompound, NbtList, NbtTag};
 
 pub struct SuperFlatWorldgen {
     start_y: u32,
@@ -20,6 +22,40 @@ impl Worldgen for SuperFlatWorldgen {
         drop(guard);
         chunk
     }
+
+    fn get_generator(&self) -> Option<NbtCompound> {
+        let mut layers: Vec<(&str, i32)> = Vec::with_capacity(self.blocks.len() + 1);
+        if self.start_y > 0 {
+            layers.push(("minecraft:air", self.start_y as i32));
+        }
+        for block in self.blocks.iter() {
+            let name = get_block_state_properties(*block)?.0;
+            match layers.last_mut() {
+                Some((last, height)) if *last == name => *height += 1,
+                _ => layers.push((name, 1)),
+            }
+        }
+        let layers = layers
+            .into_iter()
+            .map(|(block, height)| {
+                NbtCompound::from_values(vec![
+                    ("block".into(), NbtTag::String(block.into())),
+                    ("height".into(), NbtTag::Int(height)),
+                ])
+            })
+            .collect();
+        let settings = NbtCompound::from_values(vec![
+            ("layers".into(), NbtTag::List(NbtList::Compound(layers))),
+            ("biome".into(), NbtTag::String("minecraft:plains".into())),
+            ("features".into(), NbtTag::Byte(0)),
+            ("lakes".into(), NbtTag::Byte(0)),
+            ("structure_overrides".into(), NbtTag::List(NbtList::Empty)),
+        ]);
+        Some(NbtCompound::from_values(vec![
+            ("type".into(), NbtTag::String("minecraft:flat".into())),
+            ("settings".into(), NbtTag::Compound(settings)),
+        ]))
+    }
 }
 
 impl SuperFlatWorldgen {
@@ -45,7 +45,7 @@ impl HeightMap {
         if !(0..16).contains(&x) || !(0..16).contains(&z) {
             return None;
         }
-        Some(unsafe { *self.height_map.get_unchecked((x << 4 | z) as usize) })
+        Some(unsafe { *self.height_map.get_unchecked((z << 4 | x) as usize) })
     }
 
     pub fn set(&mut self, x: i32, z: i32, value: u16) -> anyhow::Result<()> {
@@ -54,21 +54,37 @@ impl HeightMap {
         } else if !(0..16).contains(&z) {
             return Err(anyhow!("Invalid z coord: {}", z));
         }
-        unsafe { *self.height_map.get_unchecked_mut((x << 4 | z) as usize) = value };
+        unsafe { *self.height_map.get_unchecked_mut((z << 4 | x) as usize) = value };
         Ok(())
     }
 
     pub fn serialize(&self) -> anyhow::Result<Vec<i64>> {
-        let mut result = Vec::with_capacity(self.height_map.len() / 8 + 1);
-        for i in 0..self.height_map.len() / 8 {
+        // Entries never span two longs, so the remaining bits of each long are left empty.
+        let per_long = self.u as usize;
+        let mut result = Vec::with_capacity(self.height_map.len().div_ceil(per_long));
+        for entries in self.height_map.chunks(per_long) {
             let mut value = 0;
-            for j in 0..8 {
-                value |= (self.height_map[i * 8 + j] as u64) << (j * self.bit_per_entry as usize);
+            for (j, entry) in entries.iter().enumerate() {
+                value |= (*entry as u64) << (j * self.bit_per_entry as usize);
             }
             result.push(value as i64);
         }
         Ok(result)
     }
+
+    /// Reads the height map back from the packed longs written by [`HeightMap::serialize`].
+    pub fn deserialize(&mut self, data: &[i64]) -> anyhow::Result<()> {
+        let per_long = self.u as usize;
+        if data.len() != self.height_map.len().div_ceil(per_long) {
+            return Err(anyhow!("Invalid height map length: {}", data.len()));
+        }
+        let mask = (1u64 << self.bit_per_entry) - 1;
+        for (i, entry) in self.height_map.iter_mut().enumerate() {
+            let long = data[i / per_long] as u64;
+            *entry = ((long >> ((i % per_long) * self.bit_per_entry as usize)) & mask) as u16;
+        }
+        Ok(())
+    }
 }
 
 impl AsRef<[u16; 256]> for HeightMap {
@@ -0,0 +1,362 @@
+use crate::world::game_rules::GameRules;
+use crate::{DATA_VERSION, MINECRAFT_VERSION};
+use anyhow::anyhow;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use sha2::{Digest, Sha256};
+use simdnbt::owned::{BaseNbt, Nbt, NbtCompound, NbtList, NbtTag};
+use std::io::{Cursor, Read, Write};
+use std::ops::Range;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+/// The ticks of a day.
+pub const DAY_LENGTH: i64 = 24000;
+/// The version of the `level.dat` format.
+const LEVEL_VERSION: i32 = 19133;
+
+const RAIN_DELAY: Range<i32> = 12000..180001;
+const RAIN_DURATION: Range<i32> = 12000..24001;
+const THUNDER_DELAY: Range<i32> = 12000..180001;
+const THUNDER_DURATION: Range<i32> = 3600..15601;
+
+/// The time, the weather, the spawn point and the settings of the world,
+/// stored in `level.dat`.
+pub struct LevelData {
+    pub level_name: String,
+    /// The ticks since the world was created.
+    pub game_time: i64,
+    /// The time of day, in ticks since the first sunrise.
+    pub day_time: i64,
+    /// The position players spawn at, in the default dimension.
+    pub spawn: (i32, i32, i32),
+    pub spawn_angle: f32,
+    pub raining: bool,
+    /// The ticks until the rain toggles.
+    pub rain_time: i32,
+    pub thundering: bool,
+    /// The ticks until the thunder toggles.
+    pub thunder_time: i32,
+    /// The ticks the weather stays clear, set by the weather command.
+    pub clear_weather_time: i32,
+    pub game_rules: GameRules,
+    pub world_gen_settings: WorldGenSettings,
+    /// The data version the world was last saved with.
+    pub data_version: i32,
+    /// The fields of `level.dat` the server does not use, written back unchanged.
+    raw: NbtCompound,
+}
+
+/// The seed and the generators of the dimensions.
+pub struct WorldGenSettings {
+    pub seed: i64,
+    pub generate_features: bool,
+    pub bonus_chest: bool,
+    /// The dimension type and the generator of every dimension, by dimension name.
+    pub dimensions: NbtCompound,
+}
+
+impl WorldGenSettings {
+    fn read(nbt: &NbtCompound) -> WorldGenSettings {
+        WorldGenSettings {
+            seed: nbt.long("seed").unwrap_or(0),
+            generate_features: nbt.byte("generate_features").unwrap_or(1) != 0,
+            bonus_chest: nbt.byte("bonus_chest").unwrap_or(0) != 0,
+            dimensions: nbt.compound("dimensions").cloned().unwrap_or_default(),
+        }
+    }
+
+    fn write(&self) -> NbtCompound {
+        NbtCompound::from_values(vec![
+            ("seed".into(), NbtTag::Long(self.seed)),
+            (
+                "generate_features".into(),
+                NbtTag::Byte(self.generate_features as i8),
+            ),
+            ("bonus_chest".into(), NbtTag::Byte(self.bonus_chest as i8)),
+            (
+                "dimensions".into(),
+                NbtTag::Compound(self.dimensions.clone()),
+            ),
+        ])
+    }
+}
+
+impl LevelData {
+    /// Creates the level data of a new world.
+    pub fn new(level_name: &str, seed: i64) -> LevelData {
+        LevelData {
+            level_name: level_name.to_string(),
+            game_time: 0,
+            day_time: 0,
+            spawn: (0, 0, 0),
+            spawn_angle: 0.0,
+            raining: false,
+            rain_time: 0,
+            thundering: false,
+            thunder_time: 0,
+            clear_weather_time: 0,
+            game_rules: GameRules::default(),
+            world_gen_settings: WorldGenSettings {
+                seed,
+                generate_features: true,
+                bonus_chest: false,
+                dimensions: NbtCompound::new(),
+            },
+            data_version: DATA_VERSION,
+            raw: NbtCompound::new(),
+        }
+    }
+
+    /// Loads the level data of a world, from `level.dat` or its backup `level.dat_old`.
+    ///
+    /// # Returns
+    /// - `Ok(Some(LevelData))`: The level data.
+    /// - `Ok(None)`: If the world has no level data, so it is a new world.
+    /// - `Err(anyhow::Error)`: If neither file can be read.
+    pub fn load(directory: &Path) -> anyhow::Result<Option<LevelData>> {
+        let path = directory.join("level.dat");
+        let backup = directory.join("level.dat_old");
+        if !path.is_file() && !backup.is_file() {
+            return Ok(None);
+        }
+        let level_data = match std::fs::read(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|data| LevelData::deserialize(&data))
+        {
+            Ok(level_data) => level_data,
+            Err(err) => {
+                warn!("Failed to read {:?}, trying the backup: {:?}", path, err);
+                LevelData::deserialize(&std::fs::read(&backup)?)?
+            }
+        };
+        if level_data.data_version > DATA_VERSION {
+            warn!(
+                "The world was saved by a newer version (data version {}).",
+                level_data.data_version
+            );
+        }
+        Ok(Some(level_data))
+    }
+
+    /// Writes the serialized level data to `level.dat`,
+    /// keeping the previous file as `level.dat_old`.
+    pub fn save(directory: &Path, data: &[u8]) -> anyhow::Result<()> {
+        std::fs::create_dir_all(directory)?;
+        let path = directory.join("level.dat");
+        let new = directory.join("level.dat_new");
+        std::fs::write(&new, data)?;
+        if path.is_file() {
+            std::fs::rename(&path, directory.join("level.dat_old"))?;
+        }
+        std::fs::rename(&new, &path)?;
+        Ok(())
+    }
+
+    /// Reads the level data from the gzipped NBT of `level.dat`.
+    pub fn deserialize(data: &[u8]) -> anyhow::Result<LevelData> {
+        let mut decompressed = Vec::with_capacity(data.len() * 4);
+        GzDecoder::new(data).read_to_end(&mut decompressed)?;
+        let Nbt::Some(root) = simdnbt::owned::read(&mut Cursor::new(&decompressed))? else {
+            return Err(anyhow!("Empty level data"));
+        };
+        let nbt = root
+            .compound("Data")
+            .ok_or_else(|| anyhow!("Missing level data"))?;
+        let world_gen_settings = match nbt.compound("WorldGenSettings") {
+            Some(settings) => WorldGenSettings::read(settings),
+            // Written before 1.16, with the seed at the top level.
+            None => WorldGenSettings {
+                seed: nbt.long("RandomSeed").unwrap_or(0),
+                generate_features: nbt.byte("MapFeatures").unwrap_or(1) != 0,
+                bonus_chest: false,
+                dimensions: NbtCompound::new(),
+            },
+        };
+        Ok(LevelData {
+            level_name: nbt
+                .string("LevelName")
+                .map(|name| name.to_string())
+                .unwrap_or_default(),
+            game_time: nbt.long("Time").unwrap_or(0),
+            day_time: nbt.long("DayTime").unwrap_or(0),
+            spawn: (
+                nbt.int("SpawnX").unwrap_or(0),
+                nbt.int("SpawnY").unwrap_or(0),
+                nbt.int("SpawnZ").unwrap_or(0),
+            ),
+            spawn_angle: nbt.float("SpawnAngle").unwrap_or(0.0),
+            raining: nbt.byte("raining").unwrap_or(0) != 0,
+            rain_time: nbt.int("rainTime").unwrap_or(0),
+            thundering: nbt.byte("thundering").unwrap_or(0) != 0,
+            thunder_time: nbt.int("thunderTime").unwrap_or(0),
+            clear_weather_time: nbt.int("clearWeatherTime").unwrap_or(0),
+            game_rules: nbt
+                .compound("GameRules")
+                .map(GameRules::read)
+                .unwrap_or_default(),
+            world_gen_settings,
+            data_version: nbt.int("DataVersion").unwrap_or(0),
+            raw: nbt.clone(),
+        })
+    }
+
+    /// Writes the level data as the gzipped NBT of `level.dat`.
+    pub fn serialize(&self) -> anyhow::Result<Vec<u8>> {
+        let mut nbt = self.raw.clone();
+        let last_played = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as i64;
+        let version = NbtCompound::from_values(vec![
+            ("Id".into(), NbtTag::Int(DATA_VERSION)),
+            ("Name".into(), NbtTag::String(MINECRAFT_VERSION.into())),
+            ("Series".into(), NbtTag::String("main".into())),
+            ("Snapshot".into(), NbtTag::Byte(0)),
+        ]);
+        let values = [
+            ("DataVersion", NbtTag::Int(DATA_VERSION)),
+            ("version", NbtTag::Int(LEVEL_VERSION)),
+            ("Version", NbtTag::Compound(version)),
+            ("LevelName", NbtTag::String(self.level_name.as_str().into())),
+            ("LastPlayed", NbtTag::Long(last_played)),
+            ("Time", NbtTag::Long(self.game_time)),
+            ("DayTime", NbtTag::Long(self.day_time)),
+            ("SpawnX", NbtTag::Int(self.spawn.0)),
+            ("SpawnY", NbtTag::Int(self.spawn.1)),
+            ("SpawnZ", NbtTag::Int(self.spawn.2)),
+            ("SpawnAngle", NbtTag::Float(self.spawn_angle)),
+            ("raining", NbtTag::Byte(self.raining as i8)),
+            ("rainTime", NbtTag::Int(self.rain_time)),
+            ("thundering", NbtTag::Byte(self.thundering as i8)),
+            ("thunderTime", NbtTag::Int(self.thunder_time)),
+            ("clearWeatherTime", NbtTag::Int(self.clear_weather_time)),
+            ("GameRules", NbtTag::Compound(self.game_rules.write())),
+            (
+                "WorldGenSettings",
+                NbtTag::Compound(self.world_gen_settings.write()),
+            ),
+            ("initialized", NbtTag::Byte(1)),
+        ];
+        for (name, value) in values {
+            nbt.remove(name);
+            nbt.insert(name, value);
+        }
+        // Written for new worlds, so vanilla can open them.
+        let defaults = [
+            ("GameType", NbtTag::Int(0)),
+            ("Difficulty", NbtTag::Byte(2)),
+            ("hardcore", NbtTag::Byte(0)),
+            ("allowCommands", NbtTag::Byte(0)),
+            (
+                "DataPacks",
+                NbtTag::Compound(NbtCompound::from_values(vec![
+                    (
+                        "Enabled".into(),
+                        NbtTag::List(NbtList::String(vec!["vanilla".into()])),
+                    ),
+                    ("Disabled".into(), NbtTag::List(NbtList::Empty)),
+                ])),
+            ),
+            (
+                "ServerBrands",
+                NbtTag::List(NbtList::String(vec!["Spot".into()])),
+            ),
+        ];
+        for (name, value) in defaults {
+            if !nbt.contains(name) {
+                nbt.insert(name, value);
+            }
+        }
+        let mut data = Vec::with_capacity(4096);
+        BaseNbt::new(
+            "",
+            NbtCompound::from_values(vec![("Data".into(), NbtTag::Compound(nbt))]),
+        )
+        .write(&mut data);
+        let mut encoder =
+            GzEncoder::new(Vec::with_capacity(data.len() / 2), Compression::default());
+        encoder.write_all(&data)?;
+        Ok(encoder.finish()?)
+    }
+
+    /// Gets the first 8 bytes of the SHA-256 hash of the seed, sent to the clients.
+    pub fn get_hashed_seed(&self) -> i64 {
+        let hash = Sha256::digest(self.world_gen_settings.seed.to_be_bytes());
+        i64::from_be_bytes(hash[..8].try_into().unwrap())
+    }
+
+    /// Advances the time and the weather cycle by a tick.
+    pub fn tick(&mut self) {
+        self.game_time += 1;
+        if self.game_rules.get_bool("doDaylightCycle") {
+            self.day_time += 1;
+        }
+        if !self.game_rules.get_bool("doWeatherCycle") {
+            return;
+        }
+        if self.clear_weather_time > 0 {
+            self.clear_weather_time -= 1;
+            self.thunder_time = if self.thundering { 0 } else { 1 };
+            self.rain_time = if self.raining { 0 } else { 1 };
+            self.thundering = false;
+            self.raining = false;
+            return;
+        }
+        if self.thunder_time > 0 {
+            self.thunder_time -= 1;
+            if self.thunder_time == 0 {
+                self.thundering = !self.thundering;
+            }
+        } else if self.thundering {
+            self.thunder_time = fastrand::i32(THUNDER_DURATION);
+        } else {
+            self.thunder_time = fastrand::i32(THUNDER_DELAY);
+        }
+        if self.rain_time > 0 {
+            self.rain_time -= 1;
+            if self.rain_time == 0 {
+                self.raining = !self.raining;
+            }
+        } else if self.raining {
+            self.rain_time = fastrand::i32(RAIN_DURATION);
+        } else {
+            self.rain_time = fastrand::i32(RAIN_DELAY);
+        }
+    }
+
+    /// Sets the weather for a duration.
+    ///
+    /// # Parameters
+    /// - `duration`: The ticks the weather lasts, a random duration if `None`.
+    pub fn set_weather(&mut self, raining: bool, thundering: bool, duration: Option<i32>) {
+        if raining {
+            let duration = duration.unwrap_or_else(|| {
+                if thundering {
+                    fastrand::i32(THUNDER_DURATION)
+                } else {
+                    fastrand::i32(RAIN_DURATION)
+                }
+            });
+            self.clear_weather_time = 0;
+            self.rain_time = duration;
+            self.thunder_time = if thundering { duration } else { 0 };
+        } else {
+            self.clear_weather_time = duration.unwrap_or_else(|| fastrand::i32(RAIN_DELAY));
+            self.rain_time = 0;
+            self.thunder_time = 0;
+        }
+        self.raining = raining;
+        self.thundering = thundering;
+    }
+
+    /// Gets the day the time of day is in, the first one being 0.
+    pub fn get_day(&self) -> i64 {
+        self.day_time / DAY_LENGTH
+    }
+}
+
+impl Default for LevelData {
+    fn default() -> Self {
+        LevelData::new("world", 0)
+    }
+}
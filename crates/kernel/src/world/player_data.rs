@@ -0,0 +1,258 @@
+use crate::entity::metadata::HEALTH;
+use crate::entity::player::{chunk_pos, Player};
+use crate::item::inventory::Inventory;
+use crate::DATA_VERSION;
+use crate::WORLD;
+use anyhow::anyhow;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use simdnbt::owned::{BaseNbt, Nbt, NbtCompound, NbtList, NbtTag};
+use std::io::{Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+use uuid::Uuid;
+
+fn get_path(directory: &Path, uuid: Uuid, extension: &str) -> PathBuf {
+    directory
+        .join("playerdata")
+        .join(format!("{}.{}", uuid.hyphenated(), extension))
+}
+
+/// Loads the saved data of a player, from `playerdata/<uuid>.dat` or its backup `<uuid>.dat_old`.
+///
+/// # Returns
+/// - `Ok(Some(NbtCompound))`: The saved data.
+/// - `Ok(None)`: If the player never joined the world.
+/// - `Err(anyhow::Error)`: If neither file can be read.
+pub fn load(directory: &Path, uuid: Uuid) -> anyhow::Result<Option<NbtCompound>> {
+    let path = get_path(directory, uuid, "dat");
+    let backup = get_path(directory, uuid, "dat_old");
+    if !path.is_file() && !backup.is_file() {
+        return Ok(None);
+    }
+    match std::fs::read(&path)
+        .map_err(anyhow::Error::from)
+        .and_then(|data| deserialize(&data))
+    {
+        Ok(nbt) => Ok(Some(nbt)),
+        Err(err) => {
+            warn!("Failed to read {:?}, trying the backup: {:?}", path, err);
+            Ok(Some(deserialize(&std::fs::read(&backup)?)?))
+        }
+    }
+}
+
+/// Writes the data of a player to `playerdata/<uuid>.dat`,
+/// keeping the previous file as `<uuid>.dat_old`.
+pub fn save(directory: &Path, player: &Player) -> anyhow::Result<()> {
+    save_data(directory, player.entity.uuid, &write(player))
+}
+
+/// Writes the data of a player built by [`write`], like [`save`] does,
+/// so the file can be written without holding the player.
+pub fn save_data(directory: &Path, uuid: Uuid, nbt: &NbtCompound) -> anyhow::Result<()> {
+    let data = serialize(nbt)?;
+    std::fs::create_dir_all(directory.join("playerdata"))?;
+    let path = get_path(directory, uuid, "dat");
+    let new = get_path(directory, uuid, "dat_new");
+    std::fs::write(&new, data)?;
+    if path.is_file() {
+        std::fs::rename(&path, get_path(directory, uuid, "dat_old"))?;
+    }
+    std::fs::rename(&new, &path)?;
+    Ok(())
+}
+
+fn deserialize(data: &[u8]) -> anyhow::Result<NbtCompound> {
+    let mut decompressed = Vec::with_capacity(data.len() * 4);
+    GzDecoder::new(data).read_to_end(&mut decompressed)?;
+    let Nbt::Some(root) = simdnbt::owned::read(&mut Cursor::new(&decompressed))? else {
+        return Err(anyhow!("Empty player data"));
+    };
+    Ok(root.into_inner())
+}
+
+fn serialize(nbt: &NbtCompound) -> anyhow::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    BaseNbt::new("", nbt.clone()).write(&mut data);
+    let mut encoder = GzEncoder::new(Vec::with_capacity(data.len() / 2), Compression::default());
+    encoder.write_all(&data)?;
+    Ok(encoder.finish()?)
+}
+
+/// Applies the saved data of a player, moving it to its saved position and dimension.
+///
+/// The data is kept, so the fields the server does not use are written back unchanged.
+pub fn read(player: &mut Player, nbt: NbtCompound) {
+    let dimension = nbt.string("Dimension").and_then(|name| {
+        WORLD
+            .dimensions
+            .iter()
+            .position(|dimension| dimension.dimension_name == name.to_str())
+    });
+    let pos = nbt.list("Pos").and_then(NbtList::doubles);
+    // Players in an unknown dimension stay at the spawn point.
+    if let (Some(dimension), Some([x, y, z])) = (dimension, pos.as_deref()) {
+        player.entity.dimension = dimension;
+        player.entity.pos = (*x, *y, *z);
+        player.center_chunk = chunk_pos(player.entity.pos);
+    }
+    if let Some([x, y, z]) = nbt.list("Motion").and_then(NbtList::doubles).as_deref() {
+        player.entity.velocity = (*x as f32, *y as f32, *z as f32);
+    }
+    if let Some([yaw, pitch]) = nbt.list("Rotation").and_then(NbtList::floats).as_deref() {
+        player.entity.yaw = *yaw;
+        player.entity.head_yaw = *yaw;
+        player.entity.pitch = *pitch;
+    }
+    player.entity.on_ground = nbt.byte("OnGround").unwrap_or(0) != 0;
+    player.entity.portal_cooldown = nbt.int("PortalCooldown").unwrap_or(0);
+    if let Some(health) = nbt.float("Health") {
+        player.health = health;
+        player.metadata.set(HEALTH, health);
+    }
+    // Out of range game types, from an edited file, fall back to the closest valid one.
+    if let Some(game_mode) = nbt.int("playerGameType") {
+        player.game_mode = game_mode.clamp(0, 3) as u8;
+    }
+    player.previous_game_mode = nbt.int("previousPlayerGameType").unwrap_or(-1).clamp(-1, 3) as i8;
+    player.death_location = nbt.compound("LastDeathLocation").and_then(|location| {
+        let dimension = location.string("dimension")?.to_string();
+        let [x, y, z] = location.int_array("pos")? else {
+            return None;
+        };
+        Some((dimension, *x, *y, *z))
+    });
+    player.inventory = nbt
+        .list("Inventory")
+        .map(Inventory::read)
+        .unwrap_or_default();
+    player.ender_chest = nbt
+        .list("EnderItems")
+        .map(Inventory::read)
+        .unwrap_or_default();
+    player.selected_slot = nbt.int("SelectedItemSlot").unwrap_or(0);
+    player.experience_level = nbt.int("XpLevel").unwrap_or(0);
+    player.experience_progress = nbt.float("XpP").unwrap_or(0.0);
+    player.total_experience = nbt.int("XpTotal").unwrap_or(0);
+    match nbt.compound("abilities") {
+        Some(abilities) => {
+            let flag = |name| abilities.byte(name).map(|value| value != 0);
+            let player = &mut player.abilities;
+            player.invulnerable = flag("invulnerable").unwrap_or(player.invulnerable);
+            player.flying = flag("flying").unwrap_or(player.flying);
+            player.may_fly = flag("mayfly").unwrap_or(player.may_fly);
+            player.instabuild = flag("instabuild").unwrap_or(player.instabuild);
+            player.may_build = flag("mayBuild").unwrap_or(player.may_build);
+            player.flying_speed = abilities.float("flySpeed").unwrap_or(player.flying_speed);
+            player.walking_speed = abilities.float("walkSpeed").unwrap_or(player.walking_speed);
+        }
+        None => player.abilities.update(player.game_mode),
+    }
+    player.saved_data = nbt;
+}
+
+/// Writes the data of a player in the format of `playerdata/<uuid>.dat`.
+pub fn write(player: &Player) -> NbtCompound {
+    let mut nbt = player.saved_data.clone();
+    let entity = &player.entity;
+    let uuid = entity.uuid.as_u128();
+    let abilities = &player.abilities;
+    let mut values = vec![
+        ("DataVersion", NbtTag::Int(DATA_VERSION)),
+        (
+            "UUID",
+            NbtTag::IntArray(
+                (0..4)
+                    .map(|i| (uuid >> (96 - i * 32)) as u32 as i32)
+                    .collect(),
+            ),
+        ),
+        (
+            "Pos",
+            NbtTag::List(NbtList::Double(vec![
+                entity.pos.0,
+                entity.pos.1,
+                entity.pos.2,
+            ])),
+        ),
+        (
+            "Motion",
+            NbtTag::List(NbtList::Double(vec![
+                entity.velocity.0 as f64,
+                entity.velocity.1 as f64,
+                entity.velocity.2 as f64,
+            ])),
+        ),
+        (
+            "Rotation",
+            NbtTag::List(NbtList::Float(vec![entity.yaw, entity.pitch])),
+        ),
+        ("OnGround", NbtTag::Byte(entity.on_ground as i8)),
+        ("PortalCooldown", NbtTag::Int(entity.portal_cooldown)),
+        ("Health", NbtTag::Float(player.health)),
+        (
+            "Dimension",
+            NbtTag::String(
+                WORLD.dimensions[entity.dimension]
+                    .dimension_name
+                    .as_str()
+                    .into(),
+            ),
+        ),
+        ("playerGameType", NbtTag::Int(player.game_mode as i32)),
+        ("Inventory", NbtTag::List(player.inventory.write())),
+        ("EnderItems", NbtTag::List(player.ender_chest.write())),
+        ("SelectedItemSlot", NbtTag::Int(player.selected_slot)),
+        ("XpLevel", NbtTag::Int(player.experience_level)),
+        ("XpP", NbtTag::Float(player.experience_progress)),
+        ("XpTotal", NbtTag::Int(player.total_experience)),
+        (
+            "abilities",
+            NbtTag::Compound(NbtCompound::from_values(vec![
+                (
+                    "invulnerable".into(),
+                    NbtTag::Byte(abilities.invulnerable as i8),
+                ),
+                ("flying".into(), NbtTag::Byte(abilities.flying as i8)),
+                ("mayfly".into(), NbtTag::Byte(abilities.may_fly as i8)),
+                (
+                    "instabuild".into(),
+                    NbtTag::Byte(abilities.instabuild as i8),
+                ),
+                ("mayBuild".into(), NbtTag::Byte(abilities.may_build as i8)),
+                ("flySpeed".into(), NbtTag::Float(abilities.flying_speed)),
+                ("walkSpeed".into(), NbtTag::Float(abilities.walking_speed)),
+            ])),
+        ),
+    ];
+    if player.previous_game_mode >= 0 {
+        values.push((
+            "previousPlayerGameType",
+            NbtTag::Int(player.previous_game_mode as i32),
+        ));
+    } else {
+        nbt.remove("previousPlayerGameType");
+    }
+    match &player.death_location {
+        Some((dimension, x, y, z)) => values.push((
+            "LastDeathLocation",
+            NbtTag::Compound(NbtCompound::from_values(vec![
+                (
+                    "dimension".into(),
+                    NbtTag::String(dimension.as_str().into()),
+                ),
+                ("pos".into(), NbtTag::IntArray(vec![*x, *y, *z])),
+            ])),
+        )),
+        None => {
+            nbt.remove("LastDeathLocation");
+        }
+    }
+    for (name, value) in values {
+        nbt.remove(name);
+        nbt.insert(name, value);
+    }
+    nbt
+}
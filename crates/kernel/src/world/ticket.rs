@@ -0,0 +1,326 @@
+use crate::world::chunk::Chunk;
+use crate::DATA_VERSION;
+use anyhow::anyhow;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use hashbrown::HashMap;
+use simdnbt::owned::{BaseNbt, Nbt, NbtCompound, NbtTag};
+use std::io::{Cursor, Read, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+/// The highest level at which a chunk is loaded, at the border of the loaded area.
+pub const BORDER_LEVEL: u8 = 33;
+/// The highest level at which the blocks of a chunk are ticked.
+pub const TICKING_LEVEL: u8 = 32;
+/// The highest level at which the entities of a chunk are ticked.
+pub const ENTITY_TICKING_LEVEL: u8 = 31;
+
+/// Why a chunk is kept loaded.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum TicketType {
+    /// Around a player, up to the simulation distance.
+    Player,
+    /// Around the spawn point, up to the `spawnChunkRadius` game rule.
+    Spawn,
+    /// Marked by `forceload`, and saved with the dimension.
+    Forced,
+    /// Around an entity going through a portal, for [`PORTAL_TICKET_LIFETIME`] ticks.
+    Portal,
+    /// Added by a plugin, identified by its name.
+    Plugin(String),
+}
+
+impl TicketType {
+    /// How many ticks the tickets of this type last, `None` if they are kept until removed.
+    fn get_lifetime(&self) -> Option<u64> {
+        match self {
+            TicketType::Portal => Some(PORTAL_TICKET_LIFETIME),
+            _ => None,
+        }
+    }
+}
+
+/// The radius of the tickets added by `forceload`.
+pub const FORCED_TICKET_RADIUS: u8 = 2;
+/// The radius of the tickets added for portals.
+pub const PORTAL_TICKET_RADIUS: u8 = 3;
+/// How many ticks the tickets added for portals last.
+pub const PORTAL_TICKET_LIFETIME: u64 = 300;
+/// How many chunks are loaded for their tickets in a tick, the others waiting for the next ticks.
+pub const MAX_LOADS_PER_TICK: usize = 16;
+
+/// What is done with a chunk, depending on its level.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum LoadLevel {
+    /// The chunk is not kept loaded.
+    Inaccessible,
+    /// The chunk is loaded, but not ticked.
+    Border,
+    /// The blocks of the chunk are ticked.
+    Ticking,
+    /// The blocks and the entities of the chunk are ticked.
+    EntityTicking,
+}
+
+impl LoadLevel {
+    pub fn from_level(level: u8) -> LoadLevel {
+        match level {
+            0..=ENTITY_TICKING_LEVEL => LoadLevel::EntityTicking,
+            TICKING_LEVEL => LoadLevel::Ticking,
+            BORDER_LEVEL => LoadLevel::Border,
+            _ => LoadLevel::Inaccessible,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Ticket {
+    ticket_type: TicketType,
+    level: u8,
+    /// The tick the ticket is removed at.
+    expires_at: Option<u64>,
+}
+
+/// The tickets of a dimension, and the chunks they keep loaded.
+///
+/// A ticket of radius `r` gives its chunk the level `33 - r`,
+/// and the level grows by one with each chunk away from it,
+/// so the chunks within `r` are loaded, within `r - 1` ticking and within `r - 2` entity ticking.
+/// A chunk takes the lowest level of the tickets around it.
+#[derive(Default)]
+pub struct ChunkTickets {
+    tickets: HashMap<(i32, i32), Vec<Ticket>>,
+    /// The level of the chunks with a level up to [`BORDER_LEVEL`].
+    levels: HashMap<(i32, i32), u8>,
+    /// The chunks held because of their level.
+    loaded: HashMap<(i32, i32), Arc<Chunk>>,
+    /// The chunks waiting to be loaded, the lowest levels last.
+    to_load: Vec<(i32, i32)>,
+    /// Whether the tickets changed since the levels were computed.
+    changed: bool,
+    /// Whether the forced chunks changed since they were saved.
+    forced_changed: bool,
+    tick: u64,
+}
+
+impl ChunkTickets {
+    pub fn add(&mut self, x: i32, z: i32, ticket_type: TicketType, radius: u8) {
+        let expires_at = ticket_type
+            .get_lifetime()
+            .map(|lifetime| self.tick + lifetime);
+        self.tickets.entry((x, z)).or_default().push(Ticket {
+            ticket_type,
+            level: BORDER_LEVEL.saturating_sub(radius),
+            expires_at,
+        });
+        self.changed = true;
+    }
+
+    /// Removes one ticket of a type from a chunk.
+    ///
+    /// # Returns
+    /// Whether the chunk had a ticket of this type.
+    pub fn remove(&mut self, x: i32, z: i32, ticket_type: &TicketType) -> bool {
+        let Some(tickets) = self.tickets.get_mut(&(x, z)) else {
+            return false;
+        };
+        let Some(index) = tickets
+            .iter()
+            .position(|ticket| ticket.ticket_type == *ticket_type)
+        else {
+            return false;
+        };
+        tickets.remove(index);
+        if tickets.is_empty() {
+            self.tickets.remove(&(x, z));
+        }
+        self.changed = true;
+        true
+    }
+
+    pub fn has(&self, x: i32, z: i32, ticket_type: &TicketType) -> bool {
+        self.tickets.get(&(x, z)).is_some_and(|tickets| {
+            tickets
+                .iter()
+                .any(|ticket| ticket.ticket_type == *ticket_type)
+        })
+    }
+
+    /// Gets the level of a chunk, as of the last [`update`](ChunkTickets::update).
+    pub fn get_level(&self, x: i32, z: i32) -> u8 {
+        self.levels
+            .get(&(x, z))
+            .copied()
+            .unwrap_or(BORDER_LEVEL + 1)
+    }
+
+    /// Marks a chunk to be loaded until it is unmarked.
+    ///
+    /// # Returns
+    /// Whether the chunk was not already marked as forced.
+    pub fn set_forced(&mut self, x: i32, z: i32, forced: bool) -> bool {
+        if self.has(x, z, &TicketType::Forced) == forced {
+            return false;
+        }
+        if forced {
+            self.add(x, z, TicketType::Forced, FORCED_TICKET_RADIUS);
+        } else {
+            self.remove(x, z, &TicketType::Forced);
+        }
+        self.forced_changed = true;
+        true
+    }
+
+    /// Gets the positions of the forced chunks, sorted.
+    pub fn get_forced(&self) -> Vec<(i32, i32)> {
+        let mut forced: Vec<(i32, i32)> = self
+            .tickets
+            .keys()
+            .filter(|(x, z)| self.has(*x, *z, &TicketType::Forced))
+            .copied()
+            .collect();
+        forced.sort_unstable();
+        forced
+    }
+
+    /// Removes the expired tickets and computes the level of the chunks again if the tickets changed.
+    ///
+    /// # Returns
+    /// The chunks no longer held.
+    /// The chunks to load are then taken with [`ChunkTickets::take_to_load`].
+    pub fn update(&mut self) -> Vec<Arc<Chunk>> {
+        self.tick += 1;
+        let tick = self.tick;
+        self.tickets.retain(|_, tickets| {
+            let len = tickets.len();
+            tickets.retain(|ticket| ticket.expires_at.is_none_or(|expires_at| expires_at > tick));
+            self.changed |= tickets.len() != len;
+            !tickets.is_empty()
+        });
+        if !self.changed {
+            return Vec::new();
+        }
+        self.changed = false;
+        self.levels.clear();
+        for (&(x, z), tickets) in self.tickets.iter() {
+            let level = tickets.iter().map(|ticket| ticket.level).min().unwrap();
+            let radius = BORDER_LEVEL.saturating_sub(level) as i32;
+            for dx in -radius..=radius {
+                for dz in -radius..=radius {
+                    let level = level + dx.abs().max(dz.abs()) as u8;
+                    self.levels
+                        .entry((x + dx, z + dz))
+                        .and_modify(|current| *current = (*current).min(level))
+                        .or_insert(level);
+                }
+            }
+        }
+        let unloaded = self
+            .loaded
+            .extract_if(|pos, _| !self.levels.contains_key(pos))
+            .map(|(_, chunk)| chunk)
+            .collect();
+        self.to_load = self
+            .levels
+            .iter()
+            .filter(|(pos, _)| !self.loaded.contains_key(*pos))
+            .map(|(pos, _)| *pos)
+            .collect();
+        self.to_load
+            .sort_unstable_by_key(|pos| std::cmp::Reverse(self.levels[pos]));
+        unloaded
+    }
+
+    /// Takes the positions of the next chunks to load, the closest to the tickets first,
+    /// which are then given to [`ChunkTickets::load`].
+    pub fn take_to_load(&mut self, max: usize) -> Vec<(i32, i32)> {
+        let start = self.to_load.len().saturating_sub(max);
+        self.to_load.split_off(start)
+    }
+
+    /// Holds the chunks loaded for their level, unless it changed since they were asked for.
+    pub fn load(&mut self, chunks: Vec<Arc<Chunk>>) {
+        for chunk in chunks {
+            let pos = chunk.get_position();
+            if self.levels.contains_key(&pos) {
+                self.loaded.insert(pos, chunk);
+            }
+        }
+    }
+
+    /// Gets the loaded chunks whose blocks are ticked.
+    pub fn get_ticking_chunks(&self) -> Vec<Arc<Chunk>> {
+        self.loaded
+            .iter()
+            .filter(|(pos, _)| {
+                self.levels
+                    .get(*pos)
+                    .is_some_and(|level| *level <= TICKING_LEVEL)
+            })
+            .map(|(_, chunk)| chunk.clone())
+            .collect()
+    }
+
+    /// Takes whether the forced chunks changed since the last call.
+    pub(crate) fn take_forced_changed(&mut self) -> bool {
+        std::mem::take(&mut self.forced_changed)
+    }
+
+    /// Marks the forced chunks as changed, so they are saved again.
+    pub(crate) fn set_forced_changed(&mut self) {
+        self.forced_changed = true;
+    }
+}
+
+/// Reads the forced chunks from `data/chunks.dat` of a dimension.
+///
+/// # Returns
+/// No chunks if the file does not exist.
+pub(crate) fn load_forced(directory: &Path) -> anyhow::Result<Vec<(i32, i32)>> {
+    let path = directory.join("data").join("chunks.dat");
+    if !path.is_file() {
+        return Ok(Vec::new());
+    }
+    let data = std::fs::read(&path)?;
+    let mut decompressed = Vec::with_capacity(data.len() * 4);
+    GzDecoder::new(data.as_slice()).read_to_end(&mut decompressed)?;
+    let Nbt::Some(root) = simdnbt::owned::read(&mut Cursor::new(&decompressed))? else {
+        return Err(anyhow!("Empty forced chunks"));
+    };
+    let forced = root
+        .compound("data")
+        .and_then(|data| data.long_array("Forced"))
+        .unwrap_or_default();
+    Ok(forced
+        .iter()
+        .map(|pos| (*pos as i32, (*pos >> 32) as i32))
+        .collect())
+}
+
+/// Writes the forced chunks to `data/chunks.dat` of a dimension.
+pub(crate) fn save_forced(directory: &Path, forced: &[(i32, i32)]) -> anyhow::Result<()> {
+    let forced = forced
+        .iter()
+        .map(|(x, z)| (*x as u32 as i64) | ((*z as i64) << 32))
+        .collect();
+    let nbt = NbtCompound::from_values(vec![
+        (
+            "data".into(),
+            NbtTag::Compound(NbtCompound::from_values(vec![(
+                "Forced".into(),
+                NbtTag::LongArray(forced),
+            )])),
+        ),
+        ("DataVersion".into(), NbtTag::Int(DATA_VERSION)),
+    ]);
+    let mut data = Vec::new();
+    BaseNbt::new("", nbt).write(&mut data);
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&data)?;
+    let directory = directory.join("data");
+    std::fs::create_dir_all(&directory)?;
+    std::fs::write(directory.join("chunks.dat"), encoder.finish()?)?;
+    Ok(())
+}